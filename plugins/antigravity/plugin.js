@@ -3,9 +3,10 @@
 
   // --- LS discovery ---
 
+  // Every running Antigravity language server (one per window); the binary name differs per OS.
   function discoverLs(ctx) {
-    return ctx.host.ls.discover({
-      processName: "language_server_macos",
+    return ctx.host.ls.discoverAll({
+      processName: "language_server",
       markers: ["antigravity"],
       csrfFlag: "--csrf_token",
       portFlag: "--extension_server_port",
@@ -52,6 +53,14 @@
     return null
   }
 
+  function findWorkingServer(ctx, servers) {
+    for (var i = 0; i < servers.length; i++) {
+      var found = findWorkingPort(ctx, servers[i])
+      if (found) return { port: found.port, scheme: found.scheme, csrf: servers[i].csrf }
+    }
+    return null
+  }
+
   function callLs(ctx, port, scheme, csrf, method, body) {
     var resp = ctx.host.http.request({
       method: "POST",
@@ -107,10 +116,10 @@
   // --- Probe ---
 
   function probe(ctx) {
-    var discovery = discoverLs(ctx)
-    if (!discovery) throw "Start Antigravity and try again."
+    var servers = discoverLs(ctx) || []
+    if (servers.length === 0) throw "Start Antigravity and try again."
 
-    var found = findWorkingPort(ctx, discovery)
+    var found = findWorkingServer(ctx, servers)
     if (!found) throw "Start Antigravity and try again."
 
     ctx.host.log.info("using LS at " + found.scheme + "://127.0.0.1:" + found.port)
@@ -125,7 +134,7 @@
     // Try GetUserStatus first, fall back to GetCommandModelConfigs
     var data = null
     try {
-      data = callLs(ctx, found.port, found.scheme, discovery.csrf, "GetUserStatus", { metadata: metadata })
+      data = callLs(ctx, found.port, found.scheme, found.csrf, "GetUserStatus", { metadata: metadata })
     } catch (e) {
       ctx.host.log.warn("GetUserStatus threw: " + String(e))
     }
@@ -133,7 +142,7 @@
 
     if (!hasUserStatus) {
       ctx.host.log.warn("GetUserStatus failed, trying GetCommandModelConfigs")
-      data = callLs(ctx, found.port, found.scheme, discovery.csrf, "GetCommandModelConfigs", { metadata: metadata })
+      data = callLs(ctx, found.port, found.scheme, found.csrf, "GetCommandModelConfigs", { metadata: metadata })
     }
 
     // Parse model configs
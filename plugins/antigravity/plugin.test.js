@@ -74,7 +74,7 @@ function makeUserStatusResponse(overrides) {
 }
 
 function setupHttpMock(ctx, discovery, responseBody) {
-  ctx.host.ls.discover.mockReturnValue(discovery)
+  ctx.host.ls.discoverAll.mockReturnValue([discovery])
   ctx.host.http.request.mockImplementation((opts) => {
     if (String(opts.url).includes("GetUnleashData")) {
       return { status: 200, bodyText: "{}" }
@@ -93,14 +93,14 @@ describe("antigravity plugin", () => {
 
   it("throws when LS not found", async () => {
     const ctx = makeCtx()
-    ctx.host.ls.discover.mockReturnValue(null)
+    ctx.host.ls.discoverAll.mockReturnValue([])
     const plugin = await loadPlugin()
     expect(() => plugin.probe(ctx)).toThrow("Start Antigravity and try again.")
   })
 
   it("throws when no working port found", async () => {
     const ctx = makeCtx()
-    ctx.host.ls.discover.mockReturnValue(makeDiscovery())
+    ctx.host.ls.discoverAll.mockReturnValue([makeDiscovery()])
     ctx.host.http.request.mockImplementation(() => {
       throw new Error("connection refused")
     })
@@ -110,7 +110,7 @@ describe("antigravity plugin", () => {
 
   it("throws when both GetUserStatus and GetCommandModelConfigs fail", async () => {
     const ctx = makeCtx()
-    ctx.host.ls.discover.mockReturnValue(makeDiscovery())
+    ctx.host.ls.discoverAll.mockReturnValue([makeDiscovery()])
     ctx.host.http.request.mockImplementation((opts) => {
       if (String(opts.url).includes("GetUnleashData")) {
         return { status: 200, bodyText: "{}" }
@@ -179,7 +179,7 @@ describe("antigravity plugin", () => {
 
   it("falls back to GetCommandModelConfigs when GetUserStatus fails", async () => {
     const ctx = makeCtx()
-    ctx.host.ls.discover.mockReturnValue(makeDiscovery())
+    ctx.host.ls.discoverAll.mockReturnValue([makeDiscovery()])
     ctx.host.http.request.mockImplementation((opts) => {
       if (String(opts.url).includes("GetUnleashData")) {
         return { status: 200, bodyText: "{}" }
@@ -217,7 +217,7 @@ describe("antigravity plugin", () => {
 
   it("uses extension port as fallback when all ports fail probing", async () => {
     const ctx = makeCtx()
-    ctx.host.ls.discover.mockReturnValue(makeDiscovery({ ports: [99999], extensionPort: 42010 }))
+    ctx.host.ls.discoverAll.mockReturnValue([makeDiscovery({ ports: [99999], extensionPort: 42010 })])
 
     let usedPort = null
     ctx.host.http.request.mockImplementation((opts) => {
@@ -309,7 +309,7 @@ describe("antigravity plugin", () => {
 
   it("probes ports with HTTPS first, then HTTP, picks first success", async () => {
     const ctx = makeCtx()
-    ctx.host.ls.discover.mockReturnValue(makeDiscovery({ ports: [10001, 10002] }))
+    ctx.host.ls.discoverAll.mockReturnValue([makeDiscovery({ ports: [10001, 10002] })])
 
     const probed = []
     ctx.host.http.request.mockImplementation((opts) => {
@@ -334,4 +334,27 @@ describe("antigravity plugin", () => {
       { port: 10002, scheme: "https" },
     ])
   })
+
+  it("uses the first discovered instance that answers", async () => {
+    const ctx = makeCtx()
+    ctx.host.ls.discoverAll.mockReturnValue([
+      makeDiscovery({ pid: 1, csrf: "stale", ports: [20001] }),
+      makeDiscovery({ pid: 2, csrf: "live", ports: [20002] }),
+    ])
+    const csrfs = []
+    ctx.host.http.request.mockImplementation((opts) => {
+      const url = String(opts.url)
+      if (url.includes("GetUnleashData")) {
+        if (url.includes(":20002/")) return { status: 200, bodyText: "{}" }
+        throw new Error("refused")
+      }
+      csrfs.push(opts.headers["x-codeium-csrf-token"])
+      return { status: 200, bodyText: JSON.stringify(makeUserStatusResponse()) }
+    })
+
+    const plugin = await loadPlugin()
+    plugin.probe(ctx)
+    expect(ctx.host.ls.discoverAll.mock.calls[0][0].processName).toBe("language_server")
+    expect(csrfs).toEqual(["live"])
+  })
 })
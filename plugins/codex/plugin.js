@@ -134,7 +134,8 @@
       auth.last_refresh = new Date().toISOString()
 
       try {
-        ctx.host.fs.writeText(authPath, JSON.stringify(auth, null, 2))
+        // Merge only what we changed so fields the Codex CLI wrote meanwhile survive
+        ctx.host.fs.mergeJson(authPath, { tokens: auth.tokens, last_refresh: auth.last_refresh })
         ctx.host.log.info("refresh succeeded, auth file updated")
       } catch (e) {
         ctx.host.log.warn("refresh succeeded but failed to save auth: " + String(e))
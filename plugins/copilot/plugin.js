@@ -1,5 +1,7 @@
 (function () {
-  const KEYCHAIN_SERVICE = "OpenUsage-copilot";
+  const SECRET_TOKEN_KEY = "token";
+  // Where the plugin cached the token before host.secrets existed (macOS only)
+  const LEGACY_KEYCHAIN_SERVICE = "OpenUsage-copilot";
   const GH_KEYCHAIN_SERVICE = "gh:github.com";
   const USAGE_URL = "https://api.github.com/copilot_internal/user";
 
@@ -24,34 +26,47 @@
 
   function saveToken(ctx, token) {
     try {
-      ctx.host.keychain.writeGenericPassword(
-        KEYCHAIN_SERVICE,
-        JSON.stringify({ token: token }),
-      );
+      ctx.host.secrets.set(SECRET_TOKEN_KEY, token);
     } catch (e) {
-      ctx.host.log.warn("keychain write failed: " + String(e));
+      ctx.host.log.warn("secrets write failed: " + String(e));
     }
     writeJson(ctx, ctx.app.pluginDataDir + "/auth.json", { token: token });
   }
 
   function clearCachedToken(ctx) {
     try {
-      ctx.host.keychain.deleteGenericPassword(KEYCHAIN_SERVICE);
+      ctx.host.secrets.delete(SECRET_TOKEN_KEY);
     } catch (e) {
-      ctx.host.log.info("keychain delete failed: " + String(e));
+      ctx.host.log.info("secrets delete failed: " + String(e));
     }
     writeJson(ctx, ctx.app.pluginDataDir + "/auth.json", null);
   }
 
-  function loadTokenFromKeychain(ctx) {
+  function loadTokenFromSecrets(ctx) {
     try {
-      const raw = ctx.host.keychain.readGenericPassword(KEYCHAIN_SERVICE);
-      if (raw) {
-        const parsed = ctx.util.tryParseJson(raw);
-        if (parsed && parsed.token) {
-          ctx.host.log.info("token loaded from OpenUsage keychain");
-          return { token: parsed.token, source: "keychain" };
-        }
+      const token = ctx.host.secrets.get(SECRET_TOKEN_KEY);
+      if (token) {
+        ctx.host.log.info("token loaded from OpenUsage secrets");
+        return { token: token, source: "secrets" };
+      }
+    } catch (e) {
+      ctx.host.log.info("OpenUsage secrets read failed: " + String(e));
+    }
+    return null;
+  }
+
+  // Moves a token cached by older versions into secrets, so users do not have to log in
+  // again. The old entry is read only once; the marker keeps a stale token from coming back.
+  function migrateLegacyKeychainToken(ctx) {
+    const marker = ctx.app.pluginDataDir + "/keychain-migrated";
+    if (ctx.host.fs.exists(marker)) return null;
+    writeJson(ctx, marker, true);
+    try {
+      const parsed = ctx.util.tryParseJson(ctx.host.keychain.readGenericPassword(LEGACY_KEYCHAIN_SERVICE));
+      if (parsed && parsed.token) {
+        saveToken(ctx, parsed.token);
+        ctx.host.log.info("token migrated from OpenUsage keychain to secrets");
+        return { token: parsed.token, source: "secrets" };
       }
     } catch (e) {
       ctx.host.log.info("OpenUsage keychain read failed: " + String(e));
@@ -92,7 +107,8 @@
 
   function loadToken(ctx) {
     return (
-      loadTokenFromKeychain(ctx) ||
+      loadTokenFromSecrets(ctx) ||
+      migrateLegacyKeychainToken(ctx) ||
       loadTokenFromGhCli(ctx) ||
       loadTokenFromStateFile(ctx)
     );
@@ -162,7 +178,7 @@
 
     if (resp.status === 401 || resp.status === 403) {
       // If cached token is stale, clear it and try fallback sources
-      if (source === "keychain") {
+      if (source === "secrets") {
         ctx.host.log.info("cached token invalid, trying fallback sources");
         clearCachedToken(ctx);
         const fallback = loadTokenFromGhCli(ctx);
@@ -196,7 +212,7 @@
       );
     }
 
-    // Persist gh-cli token to OpenUsage keychain for future use
+    // Persist gh-cli token to OpenUsage secrets for future use
     if (source === "gh-cli") {
       saveToken(ctx, token);
     }
@@ -28,11 +28,8 @@ function makeUsageResponse(overrides = {}) {
   };
 }
 
-function setKeychainToken(ctx, token) {
-  ctx.host.keychain.readGenericPassword.mockImplementation((service) => {
-    if (service === "OpenUsage-copilot") return JSON.stringify({ token });
-    return null;
-  });
+function setSecretToken(ctx, token) {
+  ctx.host.secrets.get.mockImplementation((key) => (key === "token" ? token : null));
 }
 
 function setGhCliKeychain(ctx, value) {
@@ -68,15 +65,15 @@ describe("copilot plugin", () => {
     expect(() => plugin.probe(ctx)).toThrow("Not logged in. Run `gh auth login` first.");
   });
 
-  it("loads token from OpenUsage keychain", async () => {
+  it("loads token from OpenUsage secrets", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "ghu_keychain");
+    setSecretToken(ctx, "ghu_secret");
     mockUsageOk(ctx);
     const plugin = await loadPlugin();
     const result = plugin.probe(ctx);
     expect(result.lines.find((l) => l.label === "Premium")).toBeTruthy();
     const call = ctx.host.http.request.mock.calls[0][0];
-    expect(call.headers.Authorization).toBe("token ghu_keychain");
+    expect(call.headers.Authorization).toBe("token ghu_secret");
   });
 
   it("loads token from gh CLI keychain (plain)", async () => {
@@ -113,60 +110,71 @@ describe("copilot plugin", () => {
     expect(call.headers.Authorization).toBe("token ghu_state");
   });
 
-  it("prefers keychain over gh-cli", async () => {
+  it("migrates the token cached in the old OpenUsage keychain entry", async () => {
     const ctx = makePluginTestContext();
-    ctx.host.keychain.readGenericPassword.mockImplementation((service) => {
-      if (service === "OpenUsage-copilot")
-        return JSON.stringify({ token: "ghu_keychain" });
-      if (service === "gh:github.com") return "gho_ghcli";
-      return null;
-    });
+    ctx.host.keychain.readGenericPassword.mockImplementation((service) =>
+      service === "OpenUsage-copilot" ? JSON.stringify({ token: "ghu_legacy" }) : null,
+    );
+    mockUsageOk(ctx);
+    const plugin = await loadPlugin();
+    plugin.probe(ctx);
+    const call = ctx.host.http.request.mock.calls[0][0];
+    expect(call.headers.Authorization).toBe("token ghu_legacy");
+    expect(ctx.host.secrets.set).toHaveBeenCalledWith("token", "ghu_legacy");
+
+    // The old entry is only read once
+    ctx.host.keychain.readGenericPassword.mockClear();
+    plugin.probe(ctx);
+    expect(ctx.host.keychain.readGenericPassword).not.toHaveBeenCalledWith("OpenUsage-copilot");
+  });
+
+  it("prefers secrets over gh-cli", async () => {
+    const ctx = makePluginTestContext();
+    setSecretToken(ctx, "ghu_secret");
+    setGhCliKeychain(ctx, "gho_ghcli");
     mockUsageOk(ctx);
     const plugin = await loadPlugin();
     plugin.probe(ctx);
     const call = ctx.host.http.request.mock.calls[0][0];
-    expect(call.headers.Authorization).toBe("token ghu_keychain");
+    expect(call.headers.Authorization).toBe("token ghu_secret");
   });
 
-  it("prefers keychain over state file", async () => {
+  it("prefers secrets over state file", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "ghu_keychain");
+    setSecretToken(ctx, "ghu_secret");
     setStateFileToken(ctx, "ghu_state");
     mockUsageOk(ctx);
     const plugin = await loadPlugin();
     plugin.probe(ctx);
     const call = ctx.host.http.request.mock.calls[0][0];
-    expect(call.headers.Authorization).toBe("token ghu_keychain");
+    expect(call.headers.Authorization).toBe("token ghu_secret");
   });
 
-  it("persists token from gh-cli to keychain and state file", async () => {
+  it("persists token from gh-cli to secrets and state file", async () => {
     const ctx = makePluginTestContext();
     setGhCliKeychain(ctx, "gho_persist");
     mockUsageOk(ctx);
     const plugin = await loadPlugin();
     plugin.probe(ctx);
-    expect(ctx.host.keychain.writeGenericPassword).toHaveBeenCalledWith(
-      "OpenUsage-copilot",
-      JSON.stringify({ token: "gho_persist" }),
-    );
+    expect(ctx.host.secrets.set).toHaveBeenCalledWith("token", "gho_persist");
     const stateFile = ctx.host.fs.readText(
       ctx.app.pluginDataDir + "/auth.json",
     );
     expect(JSON.parse(stateFile).token).toBe("gho_persist");
   });
 
-  it("does not persist token loaded from OpenUsage keychain", async () => {
+  it("does not persist token loaded from OpenUsage secrets", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "ghu_already");
+    setSecretToken(ctx, "ghu_already");
     mockUsageOk(ctx);
     const plugin = await loadPlugin();
     plugin.probe(ctx);
-    expect(ctx.host.keychain.writeGenericPassword).not.toHaveBeenCalled();
+    expect(ctx.host.secrets.set).not.toHaveBeenCalled();
   });
 
   it("renders both Premium and Chat lines for paid tier", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     mockUsageOk(ctx);
     const plugin = await loadPlugin();
     const result = plugin.probe(ctx);
@@ -181,7 +189,7 @@ describe("copilot plugin", () => {
 
   it("renders only Premium when Chat is missing", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockReturnValue({
       status: 200,
       bodyText: JSON.stringify(
@@ -205,7 +213,7 @@ describe("copilot plugin", () => {
 
   it("shows 'No usage data' when both snapshots missing", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockReturnValue({
       status: 200,
       bodyText: JSON.stringify({ copilot_plan: "free" }),
@@ -217,7 +225,7 @@ describe("copilot plugin", () => {
 
   it("returns plan label from copilot_plan", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     mockUsageOk(ctx);
     const plugin = await loadPlugin();
     const result = plugin.probe(ctx);
@@ -226,7 +234,7 @@ describe("copilot plugin", () => {
 
   it("capitalizes multi-word plan labels", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockReturnValue({
       status: 200,
       bodyText: JSON.stringify(
@@ -240,7 +248,7 @@ describe("copilot plugin", () => {
 
   it("propagates resetsAt from quota_reset_date", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     mockUsageOk(ctx);
     const plugin = await loadPlugin();
     const result = plugin.probe(ctx);
@@ -250,7 +258,7 @@ describe("copilot plugin", () => {
 
   it("clamps usedPercent to 0 when percent_remaining > 100", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockReturnValue({
       status: 200,
       bodyText: JSON.stringify(
@@ -273,7 +281,7 @@ describe("copilot plugin", () => {
 
   it("throws on 401", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockReturnValue({ status: 401, bodyText: "" });
     const plugin = await loadPlugin();
     expect(() => plugin.probe(ctx)).toThrow("Token invalid. Run `gh auth login` to re-authenticate.");
@@ -281,7 +289,7 @@ describe("copilot plugin", () => {
 
   it("throws on 403", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockReturnValue({ status: 403, bodyText: "" });
     const plugin = await loadPlugin();
     expect(() => plugin.probe(ctx)).toThrow("Token invalid. Run `gh auth login` to re-authenticate.");
@@ -289,7 +297,7 @@ describe("copilot plugin", () => {
 
   it("throws on HTTP 500", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockReturnValue({ status: 500, bodyText: "" });
     const plugin = await loadPlugin();
     expect(() => plugin.probe(ctx)).toThrow(
@@ -299,7 +307,7 @@ describe("copilot plugin", () => {
 
   it("throws on network error", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockImplementation(() => {
       throw new Error("ECONNREFUSED");
     });
@@ -311,7 +319,7 @@ describe("copilot plugin", () => {
 
   it("throws on invalid JSON response", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockReturnValue({
       status: 200,
       bodyText: "not-json",
@@ -324,7 +332,7 @@ describe("copilot plugin", () => {
 
   it("uses 'token' auth header format (not 'Bearer')", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "ghu_format");
+    setSecretToken(ctx, "ghu_format");
     mockUsageOk(ctx);
     const plugin = await loadPlugin();
     plugin.probe(ctx);
@@ -335,7 +343,7 @@ describe("copilot plugin", () => {
 
   it("includes correct User-Agent and editor headers", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     mockUsageOk(ctx);
     const plugin = await loadPlugin();
     plugin.probe(ctx);
@@ -347,7 +355,7 @@ describe("copilot plugin", () => {
 
   it("includes periodDurationMs on paid tier progress lines", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     mockUsageOk(ctx);
     const plugin = await loadPlugin();
     const result = plugin.probe(ctx);
@@ -359,7 +367,7 @@ describe("copilot plugin", () => {
 
   it("renders Chat and Completions for free tier (limited_user_quotas)", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockReturnValue({
       status: 200,
       bodyText: JSON.stringify({
@@ -382,7 +390,7 @@ describe("copilot plugin", () => {
 
   it("includes periodDurationMs on free tier progress lines", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockReturnValue({
       status: 200,
       bodyText: JSON.stringify({
@@ -402,7 +410,7 @@ describe("copilot plugin", () => {
 
   it("propagates resetsAt from limited_user_reset_date for free tier", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockReturnValue({
       status: 200,
       bodyText: JSON.stringify({
@@ -420,7 +428,7 @@ describe("copilot plugin", () => {
 
   it("handles free tier with partially used quotas", async () => {
     const ctx = makePluginTestContext();
-    setKeychainToken(ctx, "tok");
+    setSecretToken(ctx, "tok");
     ctx.host.http.request.mockReturnValue({
       status: 200,
       bodyText: JSON.stringify({
@@ -438,31 +446,24 @@ describe("copilot plugin", () => {
     expect(completions.used).toBe(50); // 50% used
   });
 
-  it("handles graceful keychain write failure", async () => {
+  it("handles graceful secrets write failure", async () => {
     const ctx = makePluginTestContext();
     setGhCliKeychain(ctx, "gho_tok");
     mockUsageOk(ctx);
-    ctx.host.keychain.writeGenericPassword.mockImplementation(() => {
-      throw new Error("keychain locked");
+    ctx.host.secrets.set.mockImplementation(() => {
+      throw new Error("secrets locked");
     });
     const plugin = await loadPlugin();
     expect(() => plugin.probe(ctx)).not.toThrow();
     expect(ctx.host.log.warn).toHaveBeenCalled();
   });
 
-  it("retries with gh-cli token when cached keychain token is stale", async () => {
+  it("retries with gh-cli token when cached secrets token is stale", async () => {
     const ctx = makePluginTestContext();
     let callCount = 0;
-    // First call returns stale keychain token, second call returns fresh gh-cli token
-    ctx.host.keychain.readGenericPassword.mockImplementation((service) => {
-      if (service === "OpenUsage-copilot") {
-        return JSON.stringify({ token: "stale_token" });
-      }
-      if (service === "gh:github.com") {
-        return "fresh_gh_token";
-      }
-      return null;
-    });
+    // Cached token is stale, gh CLI has a fresh one
+    setSecretToken(ctx, "stale_token");
+    setGhCliKeychain(ctx, "fresh_gh_token");
     // First request with stale token returns 401, second with fresh token succeeds
     ctx.host.http.request.mockImplementation((opts) => {
       callCount++;
@@ -476,19 +477,14 @@ describe("copilot plugin", () => {
     expect(result.lines.find((l) => l.label === "Premium")).toBeTruthy();
     expect(callCount).toBe(2);
     // Should have cleared the stale token
-    expect(ctx.host.keychain.deleteGenericPassword).toHaveBeenCalledWith("OpenUsage-copilot");
+    expect(ctx.host.secrets.delete).toHaveBeenCalledWith("token");
     // Should have saved the fresh token
-    expect(ctx.host.keychain.writeGenericPassword).toHaveBeenCalled();
+    expect(ctx.host.secrets.set).toHaveBeenCalledWith("token", "fresh_gh_token");
   });
 
-  it("throws when stale keychain token and no fallback available", async () => {
+  it("throws when stale secrets token and no fallback available", async () => {
     const ctx = makePluginTestContext();
-    ctx.host.keychain.readGenericPassword.mockImplementation((service) => {
-      if (service === "OpenUsage-copilot") {
-        return JSON.stringify({ token: "stale_token" });
-      }
-      return null; // No gh-cli fallback
-    });
+    setSecretToken(ctx, "stale_token"); // No gh-cli fallback
     ctx.host.http.request.mockReturnValue({ status: 401, bodyText: "" });
     const plugin = await loadPlugin();
     expect(() => plugin.probe(ctx)).toThrow("Token invalid");
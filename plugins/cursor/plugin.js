@@ -1,6 +1,4 @@
 (function () {
-  const STATE_DB =
-    "~/Library/Application Support/Cursor/User/globalStorage/state.vscdb"
   const BASE_URL = "https://api2.cursor.sh"
   const USAGE_URL = BASE_URL + "/aiserver.v1.DashboardService/GetCurrentPeriodUsage"
   const PLAN_URL = BASE_URL + "/aiserver.v1.DashboardService/GetPlanInfo"
@@ -10,11 +8,16 @@
   const CLIENT_ID = "KbZUR41cY7W6zRSdpSUJ7I7mLYBKOCmB"
   const REFRESH_BUFFER_MS = 5 * 60 * 1000 // refresh 5 minutes before expiration
 
+  function stateDbPath(ctx) {
+    return ctx.host.ide.stateDbPath("Cursor")
+  }
+
   function readStateValue(ctx, key) {
+    const stateDb = stateDbPath(ctx)
+    if (!stateDb) return null
     try {
-      const sql =
-        "SELECT value FROM ItemTable WHERE key = '" + key + "' LIMIT 1;"
-      const json = ctx.host.sqlite.query(STATE_DB, sql)
+      const sql = "SELECT value FROM ItemTable WHERE key = ? LIMIT 1"
+      const json = ctx.host.sqlite.query(stateDb, sql, [key])
       const rows = ctx.util.tryParseJson(json)
       if (!Array.isArray(rows)) {
         throw new Error("sqlite returned invalid json")
@@ -29,16 +32,11 @@
   }
 
   function writeStateValue(ctx, key, value) {
+    const stateDb = stateDbPath(ctx)
+    if (!stateDb) return false
     try {
-      // Escape single quotes in value for SQL
-      const escaped = String(value).replace(/'/g, "''")
-      const sql =
-        "INSERT OR REPLACE INTO ItemTable (key, value) VALUES ('" +
-        key +
-        "', '" +
-        escaped +
-        "');"
-      ctx.host.sqlite.exec(STATE_DB, sql)
+      const sql = "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?, ?)"
+      ctx.host.sqlite.exec(stateDb, sql, [key, String(value)])
       return true
     } catch (e) {
       ctx.host.log.warn("sqlite write failed for " + key + ": " + String(e))
@@ -199,7 +197,7 @@
           label: "Included requests",
           used: used,
           limit: limit,
-          format: { kind: "count", suffix: "requests" },
+          format: { kind: "requests" },
           resetsAt: ctx.util.toIso(cycleEndMs),
           periodDurationMs: billingPeriodMs,
         }))
@@ -29,6 +29,21 @@ describe("cursor plugin", () => {
     expect(ctx.host.log.warn).toHaveBeenCalled()
   })
 
+  it("passes state keys as bound parameters", async () => {
+    const ctx = makeCtx()
+    ctx.host.sqlite.query.mockReturnValue(JSON.stringify([]))
+    const plugin = await loadPlugin()
+    expect(() => plugin.probe(ctx)).toThrow("Not logged in")
+
+    const [db, sql, params] = ctx.host.sqlite.query.mock.calls[0]
+    expect(db).toContain("Cursor")
+    expect(sql).toBe("SELECT value FROM ItemTable WHERE key = ? LIMIT 1")
+    expect(params).toEqual(["cursorAuth/accessToken"])
+    for (const call of ctx.host.sqlite.query.mock.calls) {
+      expect(call[1]).not.toContain("cursorAuth")
+    }
+  })
+
   it("throws on disabled usage", async () => {
     const ctx = makeCtx()
     ctx.host.sqlite.query.mockReturnValue(JSON.stringify([{ value: "token" }]))
@@ -252,7 +267,7 @@ describe("cursor plugin", () => {
     expect(reqLine).toBeTruthy()
     expect(reqLine.used).toBe(422)
     expect(reqLine.limit).toBe(500)
-    expect(reqLine.format).toEqual({ kind: "count", suffix: "requests" })
+    expect(reqLine.format).toEqual({ kind: "requests" })
   })
 
   it("throws when enterprise REST usage API fails", async () => {
@@ -412,11 +427,11 @@ describe("cursor plugin", () => {
       .replace(/=+$/g, "")
     const accessToken = `a.${expiredPayload}.c`
 
-    ctx.host.sqlite.query.mockImplementation((db, sql) => {
-      if (String(sql).includes("cursorAuth/accessToken")) {
+    ctx.host.sqlite.query.mockImplementation((db, sql, params) => {
+      if (String(db).includes("Cursor") && params && params[0] === "cursorAuth/accessToken") {
         return JSON.stringify([{ value: accessToken }])
       }
-      if (String(sql).includes("cursorAuth/refreshToken")) {
+      if (String(db).includes("Cursor") && params && params[0] === "cursorAuth/refreshToken") {
         return JSON.stringify([{ value: "refresh" }])
       }
       return JSON.stringify([])
@@ -445,11 +460,11 @@ describe("cursor plugin", () => {
 
   it("throws session expired when refresh requires logout and no access token exists", async () => {
     const ctx = makeCtx()
-    ctx.host.sqlite.query.mockImplementation((db, sql) => {
-      if (String(sql).includes("cursorAuth/accessToken")) {
+    ctx.host.sqlite.query.mockImplementation((db, sql, params) => {
+      if (String(db).includes("Cursor") && params && params[0] === "cursorAuth/accessToken") {
         return JSON.stringify([])
       }
-      if (String(sql).includes("cursorAuth/refreshToken")) {
+      if (String(db).includes("Cursor") && params && params[0] === "cursorAuth/refreshToken") {
         return JSON.stringify([{ value: "refresh" }])
       }
       return JSON.stringify([])
@@ -473,11 +488,11 @@ describe("cursor plugin", () => {
       .replace(/=+$/g, "")
     const accessToken = `a.${payload}.c`
 
-    ctx.host.sqlite.query.mockImplementation((db, sql) => {
-      if (String(sql).includes("cursorAuth/accessToken")) {
+    ctx.host.sqlite.query.mockImplementation((db, sql, params) => {
+      if (String(db).includes("Cursor") && params && params[0] === "cursorAuth/accessToken") {
         return JSON.stringify([{ value: accessToken }])
       }
-      if (String(sql).includes("cursorAuth/refreshToken")) {
+      if (String(db).includes("Cursor") && params && params[0] === "cursorAuth/refreshToken") {
         return JSON.stringify([{ value: "refresh" }])
       }
       return JSON.stringify([])
@@ -62,7 +62,12 @@
 
   function saveOauthCreds(ctx, creds) {
     try {
-      ctx.host.fs.writeText(CREDS_PATH, JSON.stringify(creds, null, 2))
+      ctx.host.fs.mergeJson(CREDS_PATH, {
+        access_token: creds.access_token,
+        id_token: creds.id_token,
+        refresh_token: creds.refresh_token,
+        expiry_date: creds.expiry_date,
+      })
     } catch (e) {
       ctx.host.log.warn("failed persisting creds: " + String(e))
     }
@@ -60,7 +60,7 @@
 
   function saveCredentials(ctx, creds) {
     try {
-      ctx.host.fs.writeText(CRED_PATH, JSON.stringify(creds))
+      ctx.host.fs.writeTextAtomic(CRED_PATH, JSON.stringify(creds))
     } catch (e) {
       ctx.host.log.warn("failed to persist credentials: " + String(e))
     }
@@ -36,9 +36,11 @@
         lineProgress({ label: "Behind pace", used: 65, limit: 100, format: { kind: "percent" }, resetsAt: _resets, periodDurationMs: _30d }),
         // Edge cases
         lineProgress({ label: "Empty bar", used: 0, limit: 500, format: { kind: "dollars" } }),
-        lineProgress({ label: "Exactly full", used: 1000, limit: 1000, format: { kind: "count", suffix: "tokens" } }),
-        lineProgress({ label: "Over limit!", used: 1337, limit: 1000, format: { kind: "count", suffix: "requests" } }),
-        lineProgress({ label: "Huge numbers", used: 8429301, limit: 10000000, format: { kind: "count", suffix: "tokens" } }),
+        lineProgress({ label: "Exactly full", used: 1000, limit: 1000, format: { kind: "tokens" } }),
+        lineProgress({ label: "Over limit!", used: 1337, limit: 1000, format: { kind: "requests" } }),
+        lineProgress({ label: "Huge numbers", used: 8429301, limit: 10000000, format: { kind: "tokens" } }),
+        lineProgress({ label: "Agent time", used: 130, limit: 300, format: { kind: "duration", unit: "minutes" } }),
+        lineProgress({ label: "Storage", used: 1610612736, limit: 5368709120, format: { kind: "bytes" } }),
         lineProgress({ label: "Tiny sliver", used: 1, limit: 10000, format: { kind: "percent" } }),
         lineProgress({ label: "Almost full", used: 9999, limit: 10000, format: { kind: "percent" } }),
         lineProgress({ label: "Expired reset", used: 42, limit: 100, format: { kind: "percent" }, resetsAt: _pastReset, periodDurationMs: _30d }),
@@ -51,6 +53,17 @@
         lineBadge({ label: "Alert", text: "Rate limited", color: "#ef4444" }),
         lineBadge({ label: "Region", text: "us-east-1" }),
       ],
+      sections: [
+        {
+          title: "Model A",
+          lines: [lineProgress({ label: "Requests", used: 20, limit: 100, format: { kind: "percent" } })],
+        },
+        {
+          title: "Model B",
+          collapsed: true,
+          lines: [lineProgress({ label: "Requests", used: 80, limit: 100, format: { kind: "percent" } })],
+        },
+      ],
     }
   }
 
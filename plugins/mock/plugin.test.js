@@ -34,6 +34,13 @@ describe("mock plugin", () => {
     expect(progressLabels).toContain("Expired reset")
   })
 
+  it("groups per-model lines into sections", async () => {
+    const plugin = await loadPlugin()
+    const result = plugin.probe(createCtx())
+    expect(result.sections.map((s) => s.title)).toEqual(["Model A", "Model B"])
+    expect(result.sections[1].collapsed).toBe(true)
+  })
+
   it("includes text and badge lines", async () => {
     const plugin = await loadPlugin()
     const result = plugin.probe(createCtx())
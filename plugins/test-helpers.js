@@ -16,6 +16,32 @@ export const makeCtx = () => {
         exists: (path) => files.has(path),
         readText: (path) => files.get(path),
         writeText: vi.fn((path, text) => files.set(path, text)),
+        stat: vi.fn((path) => (files.has(path) ? { kind: "file", size: String(files.get(path)).length, modifiedMs: null, readonly: false } : null)),
+        listDir: vi.fn(() => []),
+        glob: vi.fn(() => []),
+        readLines: vi.fn((path, opts) => {
+          const lines = String(files.get(path) ?? "").split(/\r?\n/)
+          const offset = (opts && opts.offset) || 0
+          return lines.slice(offset, opts && opts.limit != null ? offset + opts.limit : undefined)
+        }),
+        readBytes: vi.fn(() => ""),
+        remove: vi.fn((path) => files.delete(path)),
+        writeTextAtomic: vi.fn((path, text) => files.set(path, text)),
+        withLock: vi.fn((path, fn) => fn()),
+        mergeJson: vi.fn((path, patch) => {
+          const merge = (target, value) => {
+            if (!value || typeof value !== "object" || Array.isArray(value)) return value
+            const out = target && typeof target === "object" && !Array.isArray(target) ? { ...target } : {}
+            for (const [key, v] of Object.entries(value)) {
+              if (v === null) delete out[key]
+              else out[key] = merge(out[key], v)
+            }
+            return out
+          }
+          const merged = merge(files.has(path) ? JSON.parse(files.get(path)) : null, patch)
+          files.set(path, JSON.stringify(merged, null, 2))
+          return merged
+        }),
       },
       env: {
         get: vi.fn(() => null),
@@ -23,17 +49,36 @@ export const makeCtx = () => {
       keychain: {
         readGenericPassword: vi.fn(),
         writeGenericPassword: vi.fn(),
-        deleteGenericPassword: vi.fn(),
+      },
+      secrets: {
+        get: vi.fn(() => null),
+        set: vi.fn(),
+        delete: vi.fn(() => false),
+        list: vi.fn(() => []),
       },
       sqlite: {
         query: vi.fn(() => "[]"),
         exec: vi.fn(),
       },
+      ide: {
+        stateDbPath: vi.fn((name, opts) => {
+          const suffix = { insiders: " - Insiders", next: " - Next" }[opts && opts.variant] || ""
+          return "~/Library/Application Support/" + name + suffix + "/User/globalStorage/state.vscdb"
+        }),
+        getItem: vi.fn(() => null),
+        getJson: vi.fn(() => null),
+        getProto: vi.fn(() => null),
+        setItem: vi.fn(),
+      },
       http: {
         request: vi.fn(),
       },
       ls: {
         discover: vi.fn(() => null),
+        discoverAll: vi.fn(() => []),
+      },
+      logs: {
+        scanJsonl: vi.fn(() => ({ records: [], filesScanned: 0, bytesRead: 0, rewritten: [], skipped: 0, more: false })),
       },
       log: {
         trace: vi.fn(),
@@ -48,23 +93,47 @@ export const makeCtx = () => {
   ctx.line = {
     text: (opts) => {
       const line = { type: "text", label: opts.label, value: opts.value }
+      if (opts.id) line.id = opts.id
       if (opts.color) line.color = opts.color
       if (opts.subtitle) line.subtitle = opts.subtitle
       return line
     },
     progress: (opts) => {
       const line = { type: "progress", label: opts.label, used: opts.used, limit: opts.limit, format: opts.format }
+      if (opts.id) line.id = opts.id
       if (opts.resetsAt) line.resetsAt = opts.resetsAt
       if (opts.periodDurationMs) line.periodDurationMs = opts.periodDurationMs
       if (opts.color) line.color = opts.color
+      if (opts.segments) line.segments = opts.segments
+      if (opts.softLimit != null) line.softLimit = opts.softLimit
+      if (opts.hardLimit != null) line.hardLimit = opts.hardLimit
       return line
     },
     badge: (opts) => {
       const line = { type: "badge", label: opts.label, text: opts.text }
+      if (opts.id) line.id = opts.id
       if (opts.color) line.color = opts.color
       if (opts.subtitle) line.subtitle = opts.subtitle
       return line
     },
+    sparkline: (opts) => {
+      const line = { type: "sparkline", label: opts.label, points: opts.points, unit: opts.unit }
+      if (opts.id) line.id = opts.id
+      if (opts.color) line.color = opts.color
+      if (opts.subtitle) line.subtitle = opts.subtitle
+      return line
+    },
+    table: (opts) => {
+      const line = { type: "table", label: opts.label, columns: opts.columns, rows: opts.rows }
+      if (opts.id) line.id = opts.id
+      return line
+    },
+    link: (opts) => {
+      const line = { type: "link", label: opts.label, url: opts.url }
+      if (opts.id) line.id = opts.id
+      if (opts.subtitle) line.subtitle = opts.subtitle
+      return line
+    },
   }
 
   ctx.fmt = {
@@ -148,6 +217,12 @@ export const makeCtx = () => {
     },
   }
 
+  ctx.pricing = {
+    catalogVersion: 3,
+    cost: vi.fn(() => null),
+    price: vi.fn(() => null),
+  }
+
   ctx.util = {
     tryParseJson: (text) => {
       if (text === null || text === undefined) return null
@@ -7,20 +7,21 @@
     {
       marker: "windsurf",
       ideName: "windsurf",
-      stateDb: "~/Library/Application Support/Windsurf/User/globalStorage/state.vscdb",
+      ideVariant: "stable",
     },
     {
       marker: "windsurf-next",
       ideName: "windsurf-next",
-      stateDb: "~/Library/Application Support/Windsurf - Next/User/globalStorage/state.vscdb",
+      ideVariant: "next",
     },
   ]
 
   // --- LS discovery ---
 
+  // Every running language server of the variant (one per window); the binary name differs per OS.
   function discoverLs(ctx, variant) {
-    return ctx.host.ls.discover({
-      processName: "language_server_macos",
+    return ctx.host.ls.discoverAll({
+      processName: "language_server",
       markers: [variant.marker],
       csrfFlag: "--csrf_token",
       portFlag: "--extension_server_port",
@@ -29,10 +30,13 @@
   }
 
   function loadApiKey(ctx, variant) {
+    var stateDb = ctx.host.ide.stateDbPath("Windsurf", { variant: variant.ideVariant })
+    if (!stateDb) return null
     try {
       var rows = ctx.host.sqlite.query(
-        variant.stateDb,
-        "SELECT value FROM ItemTable WHERE key = 'windsurfAuthStatus' LIMIT 1"
+        stateDb,
+        "SELECT value FROM ItemTable WHERE key = ? LIMIT 1",
+        ["windsurfAuthStatus"]
       )
       var parsed = ctx.util.tryParseJson(rows)
       if (!parsed || !parsed.length || !parsed[0].value) return null
@@ -123,10 +127,13 @@
   // --- LS probe for a specific variant ---
 
   function probeVariant(ctx, variant) {
-    var discovery = discoverLs(ctx, variant)
-    if (!discovery) return null
-
-    var found = findWorkingPort(ctx, discovery, variant.ideName)
+    var servers = discoverLs(ctx, variant) || []
+    var discovery = null
+    var found = null
+    for (var i = 0; i < servers.length && !found; i++) {
+      discovery = servers[i]
+      found = findWorkingPort(ctx, discovery, variant.ideName)
+    }
     if (!found) return null
 
     var apiKey = loadApiKey(ctx, variant)
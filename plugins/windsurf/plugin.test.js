@@ -43,15 +43,16 @@ function makeLsResponse(overrides) {
 
 function setupLsMock(ctx, discovery, apiKey, responseBody, opts) {
   var stateDb = (opts && opts.stateDb) || "Windsurf"
-  ctx.host.ls.discover.mockImplementation((discoverOpts) => {
+  ctx.host.ls.discoverAll.mockImplementation((discoverOpts) => {
     // Match the right variant by marker
     var marker = discoverOpts.markers[0]
-    if (marker === "windsurf" && stateDb === "Windsurf") return discovery
-    if (marker === "windsurf-next" && stateDb === "Windsurf - Next") return discovery
-    return null
+    var servers = discovery ? [discovery] : []
+    if (marker === "windsurf" && stateDb === "Windsurf") return servers
+    if (marker === "windsurf-next" && stateDb === "Windsurf - Next") return servers
+    return []
   })
-  ctx.host.sqlite.query.mockImplementation((db, sql) => {
-    if (String(sql).includes("windsurfAuthStatus") && String(db).includes(stateDb)) {
+  ctx.host.sqlite.query.mockImplementation((db, sql, params) => {
+    if (params && params[0] === "windsurfAuthStatus" && String(db).includes(stateDb)) {
       return makeAuthStatus(apiKey)
     }
     return "[]"
@@ -74,7 +75,7 @@ describe("windsurf plugin", () => {
 
   it("throws when LS not found and no cache", async () => {
     const ctx = makeCtx()
-    ctx.host.ls.discover.mockReturnValue(null)
+    ctx.host.ls.discoverAll.mockReturnValue([])
     ctx.host.sqlite.query.mockReturnValue("[]")
     const plugin = await loadPlugin()
     expect(() => plugin.probe(ctx)).toThrow("Start Windsurf and try again.")
@@ -107,6 +108,20 @@ describe("windsurf plugin", () => {
     expect(flex.periodDurationMs).toBeUndefined()
   })
 
+  it("passes the auth key as a bound parameter", async () => {
+    const ctx = makeCtx()
+    setupLsMock(ctx, makeDiscovery(), "sk-ws-01-test", makeLsResponse())
+
+    const plugin = await loadPlugin()
+    plugin.probe(ctx)
+
+    const [db, sql, params] = ctx.host.sqlite.query.mock.calls[0]
+    expect(db).toContain("Windsurf")
+    expect(sql).toBe("SELECT value FROM ItemTable WHERE key = ? LIMIT 1")
+    expect(sql).not.toContain("windsurfAuthStatus")
+    expect(params).toEqual(["windsurfAuthStatus"])
+  })
+
   it("flex credits have no billing cycle (non-renewing)", async () => {
     const ctx = makeCtx()
     setupLsMock(ctx, makeDiscovery(), "sk-ws-01-test", makeLsResponse())
@@ -164,7 +179,7 @@ describe("windsurf plugin", () => {
 
   it("returns null from LS when no API key", async () => {
     const ctx = makeCtx()
-    ctx.host.ls.discover.mockReturnValue(makeDiscovery())
+    ctx.host.ls.discoverAll.mockReturnValue([makeDiscovery()])
     ctx.host.sqlite.query.mockReturnValue("[]")
     ctx.host.http.request.mockImplementation((reqOpts) => {
       if (String(reqOpts.url).includes("GetUnleashData")) {
@@ -235,8 +250,8 @@ describe("windsurf plugin", () => {
     setupLsMock(ctx, makeDiscovery(), "sk-ws-01-next", makeLsResponse(), { stateDb: "Windsurf - Next" })
 
     let queriedDb = null
-    ctx.host.sqlite.query.mockImplementation((db, sql) => {
-      if (String(sql).includes("windsurfAuthStatus")) {
+    ctx.host.sqlite.query.mockImplementation((db, sql, params) => {
+      if (params && params[0] === "windsurfAuthStatus") {
         queriedDb = db
         return makeAuthStatus("sk-ws-01-next")
       }
@@ -252,11 +267,11 @@ describe("windsurf plugin", () => {
   it("prefers Windsurf over Windsurf Next when both available", async () => {
     const ctx = makeCtx()
     // Both variants return valid discoveries
-    ctx.host.ls.discover.mockImplementation((discoverOpts) => {
-      return makeDiscovery()
+    ctx.host.ls.discoverAll.mockImplementation((discoverOpts) => {
+      return [makeDiscovery()]
     })
-    ctx.host.sqlite.query.mockImplementation((db, sql) => {
-      if (String(sql).includes("windsurfAuthStatus")) {
+    ctx.host.sqlite.query.mockImplementation((db, sql, params) => {
+      if (params && params[0] === "windsurfAuthStatus") {
         return makeAuthStatus("sk-ws-01-both")
       }
       return "[]"
@@ -288,4 +303,28 @@ describe("windsurf plugin", () => {
     expect(parsed.metadata.ideName).toBe("windsurf")
   })
 
+  it("tries every discovered instance of a variant", async () => {
+    const ctx = makeCtx()
+    setupLsMock(ctx, makeDiscovery(), "sk-ws-01-test", makeLsResponse())
+    ctx.host.ls.discoverAll.mockImplementation((discoverOpts) =>
+      discoverOpts.markers[0] === "windsurf"
+        ? [makeDiscovery({ csrf: "stale", ports: [30001] }), makeDiscovery({ csrf: "live", ports: [30002] })]
+        : []
+    )
+    const csrfs = []
+    ctx.host.http.request.mockImplementation((reqOpts) => {
+      const url = String(reqOpts.url)
+      if (url.includes("GetUnleashData")) {
+        if (url.includes(":30002/")) return { status: 200, bodyText: "{}" }
+        throw new Error("refused")
+      }
+      csrfs.push(reqOpts.headers["x-codeium-csrf-token"])
+      return { status: 200, bodyText: JSON.stringify(makeLsResponse()) }
+    })
+
+    const plugin = await loadPlugin()
+    const result = plugin.probe(ctx)
+    expect(result.plan).toBe("Teams")
+    expect(csrfs).toEqual(["live"])
+  })
 })
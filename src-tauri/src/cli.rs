@@ -0,0 +1,118 @@
+//! Command-line entry points, run instead of the app when the first argument names one.
+//!
+//! `openusage validate [PATH...]` checks plugins without starting the app. Each path is a
+//! plugin directory (with a `plugin.json`) or a directory of plugins; the default is
+//! `./plugins`. Exits with 1 when any plugin has errors.
+
+use crate::plugin_engine::diagnostics::{PluginReport, Severity};
+use crate::plugin_engine::manifest::{self, MANIFEST_FILE};
+use std::path::Path;
+
+const USAGE: &str = "usage: openusage validate [PATH...]";
+
+/// Runs the subcommand in `args` (without the program name), or `None` to start the app.
+pub fn run(args: &[String]) -> Option<i32> {
+    match args.first().map(String::as_str) {
+        Some("validate") => {
+            attach_console();
+            Some(validate(&args[1..]))
+        }
+        _ => None,
+    }
+}
+
+fn validate(args: &[String]) -> i32 {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return 0;
+    }
+    let paths: Vec<&str> = if args.is_empty() {
+        vec!["plugins"]
+    } else {
+        args.iter().map(String::as_str).collect()
+    };
+
+    let mut reports = Vec::new();
+    for path in paths {
+        let path = Path::new(path);
+        if path.join(MANIFEST_FILE).is_file() {
+            reports.push(manifest::load_single_plugin(path).1);
+        } else if path.is_dir() {
+            reports.extend(manifest::load_plugins_from_dir(path).1);
+        } else {
+            eprintln!("{}: not a directory\n{}", path.display(), USAGE);
+            return 2;
+        }
+    }
+
+    for report in &reports {
+        print!("{}", format_report(report));
+    }
+    let count = |severity| {
+        reports
+            .iter()
+            .flat_map(|report| &report.diagnostics)
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    };
+    let errors = count(Severity::Error);
+    println!(
+        "{} plugins checked, {} errors, {} warnings",
+        reports.len(),
+        errors,
+        count(Severity::Warning)
+    );
+    if errors > 0 { 1 } else { 0 }
+}
+
+/// `path/plugin.json: ok`, or one line per diagnostic.
+fn format_report(report: &PluginReport) -> String {
+    if report.diagnostics.is_empty() {
+        return format!("{}: ok\n", report.file);
+    }
+    report
+        .diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let severity = match diagnostic.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            format!("{}: {} {}\n", diagnostic.file, severity, diagnostic)
+        })
+        .collect()
+}
+
+/// Release builds on Windows have no console of their own; write to the one we were run from.
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // Fails harmlessly when there is no parent console or one is already attached.
+    unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_list_one_line_per_diagnostic() {
+        let mut report = PluginReport::new(Path::new("plugins/x/plugin.json"));
+        assert_eq!(format_report(&report), "plugins/x/plugin.json: ok\n");
+        report
+            .error("/schemaVersion", "unsupported schemaVersion 3")
+            .suggest("this version of OpenUsage supports schemaVersion 1");
+        report.warning("/brandColor", "brandColor 'orange' is not a #rrggbb color; using the default");
+        assert_eq!(
+            format_report(&report),
+            "plugins/x/plugin.json: error at /schemaVersion: unsupported schemaVersion 3 \
+             (this version of OpenUsage supports schemaVersion 1)\n\
+             plugins/x/plugin.json: warning at /brandColor: brandColor 'orange' is not a #rrggbb \
+             color; using the default\n"
+        );
+        assert_eq!(run(&["--version".to_string()]), None);
+    }
+}
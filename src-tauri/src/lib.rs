@@ -1,7 +1,9 @@
 #[cfg(target_os = "macos")]
 mod app_nap;
+pub mod cli;
 mod plugin_engine;
 mod tray;
+mod tray_render;
 #[cfg(target_os = "macos")]
 mod webkit_config;
 mod window;
@@ -11,6 +13,7 @@ use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
 
+use plugin_engine::provider::{ProbeContext, Provider, ProviderKind};
 use serde::Serialize;
 use tauri::Emitter;
 use tauri_plugin_log::{Target, TargetKind};
@@ -20,6 +23,9 @@ use uuid::Uuid;
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
 
 const GLOBAL_SHORTCUT_STORE_KEY: &str = "globalShortcut";
+const PLUGIN_ACCOUNTS_STORE_KEY: &str = "pluginAccounts";
+const PLUGIN_SETTING_VALUES_STORE_KEY: &str = "pluginSettingValues";
+const DISPLAY_CURRENCY_STORE_KEY: &str = "displayCurrency";
 const APP_STARTED_TRACKED_DAY_KEY_PREFIX: &str = "analytics.app_started_day.";
 
 fn app_started_day_key(version: &str) -> String {
@@ -95,9 +101,17 @@ fn handle_global_shortcut(app: &tauri::AppHandle, event: tauri_plugin_global_sho
 }
 
 pub struct AppState {
-    pub plugins: Vec<plugin_engine::manifest::LoadedPlugin>,
+    /// JS plugins and built-in providers, in panel order.
+    pub providers: Vec<Arc<dyn Provider>>,
     pub app_data_dir: PathBuf,
     pub app_version: String,
+    /// Most recent probe outputs per plugin id (one per account), used by the tray menu.
+    pub latest_outputs: HashMap<String, Vec<plugin_engine::runtime::PluginOutput>>,
+    /// Validation results of the plugin directories found at startup.
+    pub plugin_reports: Vec<plugin_engine::diagnostics::PluginReport>,
+    /// Local time zone offset, read once at startup: the `time` crate cannot read it once
+    /// the process has more than one thread on Linux.
+    pub local_offset: time::UtcOffset,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -109,15 +123,25 @@ pub struct PluginMeta {
     pub brand_color: Option<String>,
     pub lines: Vec<ManifestLineDto>,
     pub primary_candidates: Vec<String>,
+    pub links: Vec<PluginLinkDto>,
+    pub settings: Vec<plugin_engine::manifest::SettingField>,
+    /// Minimum minutes between automatic refreshes, when the plugin sets one.
+    pub refresh_interval: Option<u32>,
+    /// False when the plugin cannot run on this OS; it is listed but not probed.
+    pub supported: bool,
+    pub unsupported_reason: Option<String>,
+    /// Built into the app rather than loaded from a JS plugin.
+    pub native: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ManifestLineDto {
+    pub id: String,
     #[serde(rename = "type")]
     pub line_type: String,
     pub label: String,
-    pub scope: String,
+    pub scope: plugin_engine::manifest::LineScope,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -165,6 +189,7 @@ async fn start_probe_batch(
     state: tauri::State<'_, Mutex<AppState>>,
     batch_id: Option<String>,
     plugin_ids: Option<Vec<String>>,
+    locale: Option<String>,
 ) -> Result<ProbeBatchStarted, String> {
     let batch_id = batch_id
         .and_then(|id| {
@@ -177,20 +202,29 @@ async fn start_probe_batch(
         })
         .unwrap_or_else(|| Uuid::new_v4().to_string());
 
-    let (plugins, app_data_dir, app_version) = {
-        let locked = state.lock().map_err(|e| e.to_string())?;
+    let disabled = tray::disabled_plugin_ids(&app_handle);
+    let (providers, app_data_dir, app_version, local_offset) = {
+        let mut locked = state.lock().map_err(|e| e.to_string())?;
+        // Outputs of disabled providers would otherwise keep counting in aggregate views
+        for id in &disabled {
+            locked.latest_outputs.remove(id);
+        }
         (
-            locked.plugins.clone(),
+            locked.providers.clone(),
             locked.app_data_dir.clone(),
             locked.app_version.clone(),
+            locked.local_offset,
         )
     };
 
-    let selected_plugins = match plugin_ids {
+    let user_accounts = stored_plugin_accounts(&app_handle);
+    let user_settings = stored_plugin_setting_values(&app_handle);
+
+    let selected: Vec<Arc<dyn Provider>> = match plugin_ids {
         Some(ids) => {
-            let mut by_id: HashMap<String, plugin_engine::manifest::LoadedPlugin> = plugins
+            let mut by_id: HashMap<String, Arc<dyn Provider>> = providers
                 .into_iter()
-                .map(|plugin| (plugin.manifest.id.clone(), plugin))
+                .map(|provider| (provider.id().to_string(), provider))
                 .collect();
             let mut seen = HashSet::new();
             ids.into_iter()
@@ -202,12 +236,12 @@ async fn start_probe_batch(
                 })
                 .collect()
         }
-        None => plugins,
+        None => providers,
     };
 
-    let response_plugin_ids: Vec<String> = selected_plugins
+    let response_plugin_ids: Vec<String> = selected
         .iter()
-        .map(|plugin| plugin.manifest.id.clone())
+        .map(|provider| provider.id().to_string())
         .collect();
 
     log::info!(
@@ -216,7 +250,7 @@ async fn start_probe_batch(
         response_plugin_ids
     );
 
-    if selected_plugins.is_empty() {
+    if selected.is_empty() {
         let _ = app_handle.emit(
             "probe:batch-complete",
             ProbeBatchComplete {
@@ -229,47 +263,63 @@ async fn start_probe_batch(
         });
     }
 
-    let remaining = Arc::new(AtomicUsize::new(selected_plugins.len()));
-    for plugin in selected_plugins {
+    let secret_backend = plugin_engine::secrets::default_backend(&app_data_dir);
+    let batch = Arc::new(ProbeBatch {
+        batch_id: batch_id.clone(),
+        rates: plugin_engine::currency::RateTable::load(&app_data_dir),
+        display_currency: stored_display_currency(&app_handle),
+        locale: locale
+            .filter(|locale| !locale.trim().is_empty())
+            .unwrap_or_else(|| plugin_engine::humanize::DEFAULT_LOCALE.to_string()),
+        app_data_dir,
+        app_version,
+        local_offset,
+    });
+    let (aggregates, jobs): (Vec<ProbeJob>, Vec<ProbeJob>) = selected
+        .into_iter()
+        .map(|provider| {
+            let fields = &provider.manifest().settings;
+            let mut settings =
+                plugin_engine::settings::resolve(fields, user_settings.get(provider.id()));
+            plugin_engine::settings::reveal_secrets(
+                fields,
+                &mut settings,
+                &plugin_engine::settings::secrets_scope(provider.id()),
+                secret_backend.as_ref(),
+            );
+            let accounts = if provider.capabilities().accounts {
+                user_accounts.get(provider.id()).cloned().unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+            ProbeJob {
+                settings,
+                accounts,
+                provider,
+            }
+        })
+        .partition(|job| job.provider.capabilities().aggregate);
+    let aggregates = Arc::new(aggregates);
+
+    if jobs.is_empty() {
+        let handle = app_handle.clone();
+        tauri::async_runtime::spawn_blocking(move || finish_probe_batch(&handle, &batch, &aggregates));
+        return Ok(ProbeBatchStarted {
+            batch_id,
+            plugin_ids: response_plugin_ids,
+        });
+    }
+    let remaining = Arc::new(AtomicUsize::new(jobs.len()));
+    for job in jobs {
         let handle = app_handle.clone();
-        let completion_handle = app_handle.clone();
-        let bid = batch_id.clone();
-        let completion_bid = batch_id.clone();
-        let data_dir = app_data_dir.clone();
-        let version = app_version.clone();
+        let batch = Arc::clone(&batch);
+        let aggregates = Arc::clone(&aggregates);
         let counter = Arc::clone(&remaining);
 
         tauri::async_runtime::spawn_blocking(move || {
-            let plugin_id = plugin.manifest.id.clone();
-            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                plugin_engine::runtime::run_probe(&plugin, &data_dir, &version)
-            }));
-
-            match result {
-                Ok(output) => {
-                    let has_error = output.lines.iter().any(|line| {
-                        matches!(line, plugin_engine::runtime::MetricLine::Badge { label, .. } if label == "Error")
-                    });
-                    if has_error {
-                        log::warn!("probe {} completed with error", plugin_id);
-                    } else {
-                        log::info!("probe {} completed ok ({} lines)", plugin_id, output.lines.len());
-                    }
-                    let _ = handle.emit("probe:result", ProbeResult { batch_id: bid, output });
-                }
-                Err(_) => {
-                    log::error!("probe {} panicked", plugin_id);
-                }
-            }
-
+            run_probe_job(&handle, &batch, &job);
             if counter.fetch_sub(1, Ordering::SeqCst) == 1 {
-                log::info!("probe batch {} complete", completion_bid);
-                let _ = completion_handle.emit(
-                    "probe:batch-complete",
-                    ProbeBatchComplete {
-                        batch_id: completion_bid,
-                    },
-                );
+                finish_probe_batch(&handle, &batch, &aggregates);
             }
         });
     }
@@ -280,6 +330,257 @@ async fn start_probe_batch(
     })
 }
 
+/// Inputs shared by every probe of a batch.
+struct ProbeBatch {
+    batch_id: String,
+    rates: plugin_engine::currency::RateTable,
+    display_currency: String,
+    /// UI locale (BCP 47) used for humanized progress labels.
+    locale: String,
+    app_data_dir: PathBuf,
+    app_version: String,
+    local_offset: time::UtcOffset,
+}
+
+/// A provider with its resolved settings and account profiles.
+struct ProbeJob {
+    provider: Arc<dyn Provider>,
+    settings: plugin_engine::runtime::SettingValues,
+    accounts: Vec<plugin_engine::manifest::AccountProfile>,
+}
+
+/// Probe one provider, convert money lines to the display currency, cache the outputs and
+/// emit them. Aggregate providers see the outputs cached so far.
+fn run_probe_job(handle: &tauri::AppHandle, batch: &ProbeBatch, job: &ProbeJob) {
+    use tauri::Manager;
+
+    let plugin_id = job.provider.id().to_string();
+    let (cached, providers) = if job.provider.capabilities().aggregate {
+        handle
+            .state::<Mutex<AppState>>()
+            .lock()
+            .map(|locked| (locked.latest_outputs.clone(), locked.providers.clone()))
+            .unwrap_or_default()
+    } else {
+        (HashMap::new(), Vec::new())
+    };
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        job.provider.probe(&ProbeContext {
+            app_data_dir: &batch.app_data_dir,
+            app_version: &batch.app_version,
+            settings: &job.settings,
+            accounts: &job.accounts,
+            display_currency: &batch.display_currency,
+            rates: &batch.rates,
+            outputs: &cached,
+            providers: &providers,
+            local_offset: batch.local_offset,
+        })
+    }));
+
+    let mut outputs = match result {
+        Ok(outputs) => outputs,
+        Err(_) => {
+            log::error!("probe {} panicked", plugin_id);
+            return;
+        }
+    };
+    plugin_engine::currency::convert_outputs(&mut outputs, &batch.rates, &batch.display_currency);
+    plugin_engine::humanize::humanize_outputs(&mut outputs, &batch.locale);
+    for output in &outputs {
+        let has_error = output.all_lines().any(|line| {
+            matches!(line, plugin_engine::runtime::MetricLine::Badge { label, .. } if label == "Error")
+        });
+        let account = output.account.as_deref().unwrap_or("default");
+        if has_error {
+            log::warn!("probe {} ({}) completed with error", plugin_id, account);
+        } else {
+            log::info!(
+                "probe {} ({}) completed ok ({} lines)",
+                plugin_id,
+                account,
+                output.all_lines().count()
+            );
+        }
+    }
+    if let Ok(mut locked) = handle.state::<Mutex<AppState>>().lock() {
+        locked.latest_outputs.insert(plugin_id.clone(), outputs.clone());
+    }
+    for output in outputs {
+        let _ = handle.emit(
+            "probe:result",
+            ProbeResult {
+                batch_id: batch.batch_id.clone(),
+                output,
+            },
+        );
+    }
+}
+
+/// Run the aggregate providers once the rest of the batch is cached, then report completion.
+fn finish_probe_batch(handle: &tauri::AppHandle, batch: &ProbeBatch, aggregates: &[ProbeJob]) {
+    for job in aggregates {
+        run_probe_job(handle, batch, job);
+    }
+    log::info!("probe batch {} complete", batch.batch_id);
+    tray::update_provider_metrics(handle);
+    let _ = handle.emit(
+        "probe:batch-complete",
+        ProbeBatchComplete {
+            batch_id: batch.batch_id.clone(),
+        },
+    );
+}
+
+/// User-defined account profiles per plugin id, from the settings store.
+fn stored_plugin_accounts(
+    app_handle: &tauri::AppHandle,
+) -> HashMap<String, Vec<plugin_engine::manifest::AccountProfile>> {
+    use tauri_plugin_store::StoreExt;
+
+    let Ok(store) = app_handle.store("settings.json") else {
+        return HashMap::new();
+    };
+    match store.get(PLUGIN_ACCOUNTS_STORE_KEY) {
+        Some(value) => serde_json::from_value(value).unwrap_or_else(|e| {
+            log::warn!("invalid {} setting, ignoring: {}", PLUGIN_ACCOUNTS_STORE_KEY, e);
+            HashMap::new()
+        }),
+        None => HashMap::new(),
+    }
+}
+
+/// Stored setting values per plugin id, from the settings store. Not yet validated.
+fn stored_plugin_setting_values(
+    app_handle: &tauri::AppHandle,
+) -> HashMap<String, plugin_engine::runtime::SettingValues> {
+    use tauri_plugin_store::StoreExt;
+
+    let Ok(store) = app_handle.store("settings.json") else {
+        return HashMap::new();
+    };
+    match store.get(PLUGIN_SETTING_VALUES_STORE_KEY) {
+        Some(value) => serde_json::from_value(value).unwrap_or_else(|e| {
+            log::warn!(
+                "invalid {} setting, ignoring: {}",
+                PLUGIN_SETTING_VALUES_STORE_KEY,
+                e
+            );
+            HashMap::new()
+        }),
+        None => HashMap::new(),
+    }
+}
+
+/// ISO 4217 code money lines are converted to, from the settings store.
+fn stored_display_currency(app_handle: &tauri::AppHandle) -> String {
+    use tauri_plugin_store::StoreExt;
+
+    let stored = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(DISPLAY_CURRENCY_STORE_KEY))
+        .and_then(|value| value.as_str().map(str::to_string));
+    match stored {
+        Some(code) if plugin_engine::currency::valid_code(&code) => code,
+        Some(code) => {
+            log::warn!("invalid {} setting {:?}, using USD", DISPLAY_CURRENCY_STORE_KEY, code);
+            plugin_engine::currency::DEFAULT_CURRENCY.to_string()
+        }
+        None => plugin_engine::currency::DEFAULT_CURRENCY.to_string(),
+    }
+}
+
+/// Exchange-rate table used for display-currency conversion, for the settings UI.
+#[tauri::command]
+fn get_exchange_rates(
+    state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<plugin_engine::currency::RateTable, String> {
+    let app_data_dir = state
+        .lock()
+        .map_err(|_| "plugin state poisoned".to_string())?
+        .app_data_dir
+        .clone();
+    Ok(plugin_engine::currency::RateTable::load(&app_data_dir))
+}
+
+/// Problems found in the plugin directories at startup, including plugins that failed to load.
+#[tauri::command]
+fn get_plugin_diagnostics(
+    state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<Vec<plugin_engine::diagnostics::PluginReport>, String> {
+    Ok(state
+        .lock()
+        .map_err(|_| "plugin state poisoned".to_string())?
+        .plugin_reports
+        .clone())
+}
+
+fn find_plugin_settings_schema(
+    state: &tauri::State<'_, Mutex<AppState>>,
+    plugin_id: &str,
+) -> Result<Vec<plugin_engine::manifest::SettingField>, String> {
+    let locked = state.lock().map_err(|_| "plugin state poisoned".to_string())?;
+    locked
+        .providers
+        .iter()
+        .find(|provider| provider.id() == plugin_id)
+        .map(|provider| provider.manifest().settings.clone())
+        .ok_or_else(|| format!("unknown plugin: {}", plugin_id))
+}
+
+/// Current setting values for a plugin, with defaults filled in. Secret values are never
+/// returned, only `true` when one is set.
+#[tauri::command]
+fn get_plugin_settings(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<AppState>>,
+    plugin_id: String,
+) -> Result<plugin_engine::runtime::SettingValues, String> {
+    let schema = find_plugin_settings_schema(&state, &plugin_id)?;
+    let stored = stored_plugin_setting_values(&app_handle);
+    Ok(plugin_engine::settings::resolve(&schema, stored.get(&plugin_id)))
+}
+
+/// Validate and persist setting values for a plugin. Rejects the whole submission on any error.
+/// Secret values go to the secrets backend; the settings store only records that one is set.
+#[tauri::command]
+fn set_plugin_settings(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<AppState>>,
+    plugin_id: String,
+    values: plugin_engine::runtime::SettingValues,
+) -> Result<(), String> {
+    use tauri_plugin_store::StoreExt;
+
+    let schema = find_plugin_settings_schema(&state, &plugin_id)?;
+    let mut values = plugin_engine::settings::validate_submission(&schema, &values)
+        .map_err(|errors| errors.join("; "))?;
+    let app_data_dir = state
+        .lock()
+        .map_err(|_| "plugin state poisoned".to_string())?
+        .app_data_dir
+        .clone();
+    plugin_engine::settings::store_secrets(
+        &schema,
+        &mut values,
+        &plugin_engine::settings::secrets_scope(&plugin_id),
+        plugin_engine::secrets::default_backend(&app_data_dir).as_ref(),
+    )?;
+
+    let mut stored = stored_plugin_setting_values(&app_handle);
+    stored.insert(plugin_id, values);
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("failed to access settings store: {}", e))?;
+    let value = serde_json::to_value(&stored).map_err(|e| e.to_string())?;
+    store.set(PLUGIN_SETTING_VALUES_STORE_KEY, value);
+    store
+        .save()
+        .map_err(|e| format!("failed to save settings store: {}", e))
+}
+
 #[tauri::command]
 fn get_log_path(app_handle: tauri::AppHandle) -> Result<String, String> {
     use tauri::Manager;
@@ -340,55 +641,75 @@ fn update_global_shortcut(app_handle: tauri::AppHandle, shortcut: Option<String>
     Ok(())
 }
 
+#[tauri::command]
+fn get_pinned_providers(app_handle: tauri::AppHandle) -> Vec<String> {
+    tray::get_stored_pinned_providers(&app_handle)
+}
+
+#[tauri::command]
+fn set_pinned_providers(app_handle: tauri::AppHandle, plugin_ids: Vec<String>) {
+    tray::set_pinned_providers(&app_handle, plugin_ids);
+}
+
 #[tauri::command]
 fn list_plugins(state: tauri::State<'_, Mutex<AppState>>) -> Vec<PluginMeta> {
-    let plugins = {
+    let providers = {
         let locked = state.lock().expect("plugin state poisoned");
-        locked.plugins.clone()
+        locked.providers.clone()
     };
-    log::debug!("list_plugins: {} plugins", plugins.len());
+    log::debug!("list_plugins: {} plugins", providers.len());
 
-    plugins
-        .into_iter()
-        .map(|plugin| {
-            let mut candidates: Vec<_> = plugin
-                .manifest
-                .lines
-                .iter()
-                .filter(|line| line.line_type == "progress" && line.primary_order.is_some())
-                .collect();
-            candidates.sort_by_key(|line| line.primary_order.unwrap());
-            let primary_candidates: Vec<String> =
-                candidates.iter().map(|line| line.label.clone()).collect();
-
-            PluginMeta {
-                id: plugin.manifest.id,
-                name: plugin.manifest.name,
-                icon_url: plugin.icon_data_url,
-                brand_color: plugin.manifest.brand_color,
-                lines: plugin
-                    .manifest
-                    .lines
-                    .iter()
-                    .map(|line| ManifestLineDto {
-                        line_type: line.line_type.clone(),
-                        label: line.label.clone(),
-                        scope: line.scope.clone(),
-                    })
-                    .collect(),
-                primary_candidates,
-            }
-        })
+    providers
+        .iter()
+        .map(|provider| plugin_meta(provider.as_ref()))
         .collect()
 }
 
+fn plugin_meta(provider: &dyn Provider) -> PluginMeta {
+    let manifest = provider.manifest();
+    let support = provider.support();
+    PluginMeta {
+        id: manifest.id.clone(),
+        name: manifest.name.clone(),
+        icon_url: provider.icon_data_url().to_string(),
+        brand_color: manifest.brand_color.clone(),
+        lines: manifest
+            .lines
+            .iter()
+            .map(|line| ManifestLineDto {
+                id: line.id.clone(),
+                line_type: line.line_type.clone(),
+                label: line.label.clone(),
+                scope: line.scope,
+            })
+            .collect(),
+        primary_candidates: manifest.primary_candidates(),
+        links: manifest
+            .links
+            .iter()
+            .map(|link| PluginLinkDto {
+                label: link.label.clone(),
+                url: link.url.clone(),
+            })
+            .collect(),
+        settings: manifest.settings.clone(),
+        refresh_interval: manifest.refresh_interval,
+        supported: *support == plugin_engine::manifest::PluginSupport::Supported,
+        unsupported_reason: support.unsupported_reason().map(str::to_string),
+        native: provider.capabilities().kind == ProviderKind::Native,
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Must run before the Tokio runtime starts its worker threads
+    let local_offset = time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC);
     let runtime = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
     let _guard = runtime.enter();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(
             tauri_plugin_log::Builder::new()
@@ -412,9 +733,15 @@ pub fn run() {
             start_probe_batch,
             list_plugins,
             get_log_path,
-            update_global_shortcut
+            update_global_shortcut,
+            get_pinned_providers,
+            set_pinned_providers,
+            get_plugin_settings,
+            set_plugin_settings,
+            get_exchange_rates,
+            get_plugin_diagnostics
         ])
-        .setup(|app| {
+        .setup(move |app| {
             #[cfg(target_os = "macos")]
             app.set_activation_policy(tauri::ActivationPolicy::Accessory);
 
@@ -445,11 +772,15 @@ pub fn run() {
                 }
             };
 
-            let (_, plugins) = plugin_engine::initialize_plugins(&app_data_dir, &resource_dir);
+            let (_, plugins, plugin_reports) =
+                plugin_engine::initialize_plugins(&app_data_dir, &resource_dir);
             app.manage(Mutex::new(AppState {
-                plugins,
+                providers: plugin_engine::provider::all_providers(plugins),
                 app_data_dir,
                 app_version: app.package_info().version.to_string(),
+                latest_outputs: HashMap::new(),
+                plugin_reports,
+                local_offset,
             }));
 
             if let Err(e) = tray::create(app.handle()) {
@@ -0,0 +1,593 @@
+//! Built-in "All providers" view: adds up the billable money lines (manifest `spend`) of
+//! every other provider's latest output plus fixed subscription costs for the billing
+//! period, and compares the total with a monthly budget. Runs after the other providers
+//! of a probe batch, from their cached outputs; the daily total is kept in the history
+//! store to project the period's spend.
+
+use crate::plugin_engine::currency::{self, RateTable};
+use crate::plugin_engine::history::{self, History};
+use crate::plugin_engine::manifest::{
+    LineScope, ManifestLine, PluginManifest, PluginSupport, SettingField,
+    CURRENT_SCHEMA_VERSION,
+};
+use crate::plugin_engine::provider::{Capabilities, ProbeContext, Provider, ProviderKind};
+use crate::plugin_engine::runtime::{MetricLine, PluginOutput, ProgressFormat, SettingValues};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use time::format_description::well_known::Rfc3339;
+use time::{Date, Duration, Month, OffsetDateTime, UtcOffset};
+
+pub const ID: &str = "budget";
+pub const NAME: &str = "All providers";
+
+/// Providers listed individually in the detail view.
+const MAX_PROVIDER_LINES: usize = 8;
+const OVER_BUDGET_COLOR: &str = "#ef4444";
+
+const ICON_SVG: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><rect x="2" y="5" width="20" height="14" rx="2"/><path d="M2 10h20"/><path d="M6 15h4"/></svg>"##;
+
+/// `Name=amount [CUR]` entries separated by commas, or nothing.
+const SUBSCRIPTIONS_PATTERN: &str =
+    r"^\s*$|^\s*[^=,]+=\s*\d+(\.\d+)?(\s+[A-Za-z]{3})?\s*(,\s*[^=,]+=\s*\d+(\.\d+)?(\s+[A-Za-z]{3})?\s*)*$";
+
+fn manifest_lines() -> Vec<ManifestLine> {
+    let line = |id: &str, line_type: &str, label: &str, scope: LineScope, primary_order: Option<u32>| ManifestLine {
+        id: id.to_string(),
+        line_type: line_type.to_string(),
+        label: label.to_string(),
+        scope,
+        primary_order,
+        segment_colors: BTreeMap::new(),
+        spend: false,
+    };
+    vec![
+        line("spend", "progress", "Spend", LineScope::Overview, Some(1)),
+        line("projected", "text", "Projected", LineScope::Overview, None),
+        line("subscriptions", "text", "Subscriptions", LineScope::Detail, None),
+    ]
+}
+
+fn settings_schema() -> Vec<SettingField> {
+    let days: Vec<String> = (1..=28).map(|day| day.to_string()).collect();
+    serde_json::from_value(serde_json::json!([
+        {
+            "key": "monthlyBudget",
+            "label": "Monthly budget",
+            "type": "string",
+            "pattern": r"^\s*$|^\s*\d+(\.\d+)?\s*$",
+            "description": "In your display currency. Leave empty to only track spend."
+        },
+        {
+            "key": "periodStartDay",
+            "label": "Billing period starts on day",
+            "type": "enum",
+            "options": days,
+            "default": "1"
+        },
+        {
+            "key": "subscriptions",
+            "label": "Fixed subscriptions",
+            "type": "string",
+            "pattern": SUBSCRIPTIONS_PATTERN,
+            "description": "Monthly seats not reported by a provider, e.g. \"Claude Max=100, Copilot=10 USD\". Amounts without a currency are in your display currency."
+        }
+    ]))
+    .expect("budget settings schema")
+}
+
+/// The built-in provider; see the module docs.
+pub struct Budget {
+    manifest: PluginManifest,
+    icon_data_url: String,
+    support: PluginSupport,
+}
+
+impl Budget {
+    pub fn new() -> Self {
+        Self {
+            manifest: PluginManifest {
+                schema_version: CURRENT_SCHEMA_VERSION,
+                id: ID.to_string(),
+                name: NAME.to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                entry: String::new(),
+                icon: String::new(),
+                brand_color: None,
+                lines: manifest_lines(),
+                strict_lines: false,
+                links: Vec::new(),
+                accounts: Vec::new(),
+                settings: settings_schema(),
+                platforms: Vec::new(),
+                permissions: Vec::new(),
+                refresh_interval: None,
+                min_app_version: None,
+            },
+            icon_data_url: format!("data:image/svg+xml;base64,{}", STANDARD.encode(ICON_SVG)),
+            support: PluginSupport::Supported,
+        }
+    }
+}
+
+impl Provider for Budget {
+    fn manifest(&self) -> &PluginManifest {
+        &self.manifest
+    }
+
+    fn icon_data_url(&self) -> &str {
+        &self.icon_data_url
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            kind: ProviderKind::Native,
+            accounts: false,
+            aggregate: true,
+        }
+    }
+
+    fn support(&self) -> &PluginSupport {
+        &self.support
+    }
+
+    fn probe(&self, ctx: &ProbeContext<'_>) -> Vec<PluginOutput> {
+        let now = OffsetDateTime::now_utc();
+        let now = now.to_offset(ctx.local_offset);
+        let mut history = History::load(ctx.app_data_dir);
+        let summary = summarize(
+            ctx.outputs,
+            &spend_lines(ctx.providers),
+            &BudgetSettings::from_values(ctx.settings),
+            ctx.rates,
+            ctx.display_currency,
+            &history,
+            now,
+        );
+
+        history.record(&ymd(now.date()), &summary.series, summary.variable);
+        history.prune(&ymd(now.date() - Duration::days(history::RETENTION_DAYS)));
+        if let Err(e) = history.save(ctx.app_data_dir) {
+            log::warn!("[plugin:{}] failed to save history: {}", ID, e);
+        }
+
+        vec![PluginOutput {
+            provider_id: ID.to_string(),
+            display_name: NAME.to_string(),
+            plan: None,
+            lines: summary_lines(&summary),
+            icon_url: self.icon_data_url.clone(),
+            account: None,
+            account_id: None,
+            sections: Vec::new(),
+        }]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Subscription {
+    name: String,
+    amount: f64,
+    /// Display currency when `None`.
+    currency: Option<String>,
+}
+
+/// `Name=amount [CUR], ...`; malformed entries are logged and skipped.
+fn parse_subscriptions(text: &str) -> Vec<Subscription> {
+    let mut subscriptions = Vec::new();
+    for entry in text.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let parsed = entry.split_once('=').and_then(|(name, value)| {
+            let mut parts = value.split_whitespace();
+            let amount = parts.next()?.parse::<f64>().ok().filter(|a| a.is_finite() && *a >= 0.0)?;
+            let currency = parts.next().map(str::to_ascii_uppercase);
+            if parts.next().is_some() || name.trim().is_empty() {
+                return None;
+            }
+            Some(Subscription {
+                name: name.trim().to_string(),
+                amount,
+                currency,
+            })
+        });
+        match parsed {
+            Some(subscription) => subscriptions.push(subscription),
+            None => log::warn!("[plugin:{}] ignoring subscription entry {:?}", ID, entry),
+        }
+    }
+    subscriptions
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct BudgetSettings {
+    monthly_budget: Option<f64>,
+    period_start_day: u8,
+    subscriptions: Vec<Subscription>,
+}
+
+impl BudgetSettings {
+    fn from_values(values: &SettingValues) -> Self {
+        let text = |key: &str| values.get(key).and_then(|v| v.as_str()).map(str::trim).unwrap_or("");
+        Self {
+            monthly_budget: text("monthlyBudget").parse::<f64>().ok().filter(|b| *b > 0.0),
+            period_start_day: text("periodStartDay").parse::<u8>().unwrap_or(1).clamp(1, 28),
+            subscriptions: parse_subscriptions(text("subscriptions")),
+        }
+    }
+}
+
+fn ymd(date: Date) -> String {
+    format!("{:04}-{:02}-{:02}", date.year(), date.month() as u8, date.day())
+}
+
+fn shift_month(date: Date, delta: i32) -> Date {
+    let index = date.year() * 12 + date.month() as i32 - 1 + delta;
+    let month = Month::try_from((index.rem_euclid(12) + 1) as u8).expect("month in range");
+    Date::from_calendar_date(index.div_euclid(12), month, date.day()).expect("day <= 28")
+}
+
+/// `[start, end)` of the billing period containing `today`.
+fn billing_period(today: Date, start_day: u8) -> (Date, Date) {
+    let this_month =
+        Date::from_calendar_date(today.year(), today.month(), start_day).expect("day <= 28");
+    let start = if today >= this_month {
+        this_month
+    } else {
+        shift_month(this_month, -1)
+    };
+    (start, shift_month(start, 1))
+}
+
+/// Ids of the lines each provider marks as billable spend.
+fn spend_lines(providers: &[Arc<dyn Provider>]) -> HashMap<String, HashSet<String>> {
+    providers
+        .iter()
+        .map(|provider| {
+            let manifest = provider.manifest();
+            let ids = manifest.lines.iter().filter(|line| line.spend).map(|line| line.id.clone());
+            (manifest.id.clone(), ids.collect())
+        })
+        .collect()
+}
+
+/// Amount of a money line in `display`, or `None` when it is in another currency that
+/// could not be converted.
+fn amount_in(line: &MetricLine, display: &str) -> Option<Option<f64>> {
+    let MetricLine::Progress {
+        used,
+        format,
+        converted,
+        ..
+    } = line
+    else {
+        return None;
+    };
+    let code = currency::source_currency(format)?;
+    Some(match converted {
+        Some(converted) if converted.code == display => Some(converted.used),
+        _ if code == display => Some(*used),
+        _ => None,
+    })
+}
+
+fn is_error_output(output: &PluginOutput) -> bool {
+    output.sections.is_empty()
+        && matches!(output.lines.as_slice(), [MetricLine::Badge { label, .. }] if label == "Error")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Summary {
+    code: String,
+    /// History series of the variable spend, per currency.
+    series: String,
+    /// Provider name and spend, highest first.
+    providers: Vec<(String, f64)>,
+    subscriptions: Vec<(String, f64)>,
+    /// Money lines and subscriptions left out for lack of an exchange rate.
+    unconverted: usize,
+    variable: f64,
+    fixed: f64,
+    budget: Option<f64>,
+    projected: f64,
+    period_start: OffsetDateTime,
+    period_end: OffsetDateTime,
+}
+
+fn summarize(
+    outputs: &HashMap<String, Vec<PluginOutput>>,
+    spend_lines: &HashMap<String, HashSet<String>>,
+    settings: &BudgetSettings,
+    rates: &RateTable,
+    display: &str,
+    history: &History,
+    now: OffsetDateTime,
+) -> Summary {
+    let mut providers = Vec::new();
+    let mut unconverted = 0;
+    for (provider_id, provider_outputs) in outputs {
+        if provider_id == ID {
+            continue;
+        }
+        let Some(billable) = spend_lines.get(provider_id) else {
+            continue;
+        };
+        let is_billable = |line: &&MetricLine| line.id().is_some_and(|id| billable.contains(id));
+        for output in provider_outputs.iter().filter(|output| !is_error_output(output)) {
+            let mut spend = None;
+            for amount in output.all_lines().filter(is_billable).filter_map(|line| amount_in(line, display)) {
+                match amount {
+                    Some(amount) => *spend.get_or_insert(0.0) += amount,
+                    None => unconverted += 1,
+                }
+            }
+            if let Some(spend) = spend {
+                let name = match &output.account {
+                    Some(account) => format!("{} ({})", output.display_name, account),
+                    None => output.display_name.clone(),
+                };
+                providers.push((name, spend));
+            }
+        }
+    }
+    providers.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut subscriptions = Vec::new();
+    for subscription in &settings.subscriptions {
+        let code = subscription.currency.as_deref().unwrap_or(display);
+        match rates.rate(code, display) {
+            Some(rate) => subscriptions.push((subscription.name.clone(), subscription.amount * rate)),
+            None => unconverted += 1,
+        }
+    }
+
+    let variable: f64 = providers.iter().map(|(_, amount)| amount).sum();
+    let fixed: f64 = subscriptions.iter().map(|(_, amount)| amount).sum();
+    let (start, end) = billing_period(now.date(), settings.period_start_day);
+    let period_start = start.midnight().assume_offset(now.offset());
+    let period_end = end.midnight().assume_offset(now.offset());
+
+    // Daily rate from growth since the first snapshot of the period; from the period start
+    // when there is no earlier snapshot (first day, or history started mid-period).
+    let series = format!("spend:{}", display);
+    let today = now.date();
+    let rate = match history.first_since(&series, &ymd(start)) {
+        Some((date, first)) if date < ymd(today).as_str() => {
+            let days = Date::parse(date, &time::format_description::well_known::Iso8601::DATE)
+                .map(|date| (today - date).whole_days().max(1) as f64)
+                .unwrap_or(1.0);
+            (variable - first).max(0.0) / days
+        }
+        _ => variable / ((now - period_start).as_seconds_f64() / 86_400.0).max(1.0),
+    };
+    let remaining_days = ((period_end - now).as_seconds_f64() / 86_400.0).max(0.0);
+
+    Summary {
+        code: display.to_string(),
+        series,
+        providers,
+        subscriptions,
+        unconverted,
+        variable,
+        fixed,
+        budget: settings.monthly_budget,
+        projected: variable + rate * remaining_days + fixed,
+        period_start,
+        period_end,
+    }
+}
+
+fn summary_lines(summary: &Summary) -> Vec<MetricLine> {
+    let money = |amount: f64| currency::format_amount(amount, &summary.code);
+    // Per-provider lines are not declared in the manifest and have no id.
+    let text = |id: Option<&str>, label: &str, value: String, color: Option<&str>, subtitle: Option<String>| MetricLine::Text {
+        id: id.map(str::to_string),
+        label: label.to_string(),
+        value,
+        color: color.map(str::to_string),
+        subtitle,
+    };
+    let total = summary.variable + summary.fixed;
+    let end_date = summary.period_end.date();
+    let by_end = format!("by {} {}", &end_date.month().to_string()[..3], end_date.day());
+
+    let mut lines = vec![match summary.budget {
+        Some(budget) => MetricLine::Progress {
+            id: Some("spend".to_string()),
+            label: "Spend".to_string(),
+            used: total,
+            limit: budget,
+            format: ProgressFormat::Currency {
+                code: summary.code.clone(),
+            },
+            resets_at: summary.period_end.to_offset(UtcOffset::UTC).format(&Rfc3339).ok(),
+            period_duration_ms: Some(
+                (summary.period_end - summary.period_start).whole_milliseconds().max(0) as u64,
+            ),
+            color: None,
+            segments: Vec::new(),
+            soft_limit: None,
+            hard_limit: None,
+            converted: None,
+            humanized: None,
+        },
+        None => text(Some("spend"), "Spend", money(total), None, Some("No monthly budget set".to_string())),
+    }];
+
+    let over = summary.budget.map(|budget| summary.projected - budget).filter(|over| *over > 0.0);
+    lines.push(match over {
+        Some(over) => text(
+            Some("projected"),
+            "Projected",
+            money(summary.projected),
+            Some(OVER_BUDGET_COLOR),
+            Some(format!("{} over budget {}", money(over), by_end)),
+        ),
+        None => text(Some("projected"), "Projected", money(summary.projected), None, Some(by_end)),
+    });
+
+    if !summary.subscriptions.is_empty() {
+        let names: Vec<&str> = summary.subscriptions.iter().map(|(name, _)| name.as_str()).collect();
+        lines.push(text(Some("subscriptions"), "Subscriptions", money(summary.fixed), None, Some(names.join(", "))));
+    }
+    for (name, amount) in summary.providers.iter().take(MAX_PROVIDER_LINES) {
+        lines.push(text(None, name, money(*amount), None, None));
+    }
+    if summary.unconverted > 0 {
+        lines.push(text(
+            None,
+            "Not included",
+            format!("{} amounts", summary.unconverted),
+            None,
+            Some(format!("No exchange rate to {}", summary.code)),
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_engine::currency::ConvertedAmount;
+
+    fn at(text: &str) -> OffsetDateTime {
+        OffsetDateTime::parse(text, &Rfc3339).expect("timestamp")
+    }
+
+    fn money(id: &str, used: f64, format: ProgressFormat, converted: Option<ConvertedAmount>) -> MetricLine {
+        MetricLine::Progress {
+            id: Some(id.to_string()),
+            label: "Spend".to_string(),
+            used,
+            limit: 100.0,
+            format,
+            resets_at: None,
+            period_duration_ms: None,
+            color: None,
+            segments: Vec::new(),
+            soft_limit: None,
+            hard_limit: None,
+            converted,
+            humanized: None,
+        }
+    }
+
+    fn output(id: &str, lines: Vec<MetricLine>) -> PluginOutput {
+        PluginOutput {
+            provider_id: id.to_string(),
+            display_name: id.to_string(),
+            plan: None,
+            lines,
+            icon_url: String::new(),
+            account: None,
+            account_id: None,
+            sections: Vec::new(),
+        }
+    }
+
+    fn settings(values: serde_json::Value) -> BudgetSettings {
+        BudgetSettings::from_values(values.as_object().expect("object"))
+    }
+
+    #[test]
+    fn billing_periods_wrap_months_and_years() {
+        let date = |y: i32, m: Month, d: u8| Date::from_calendar_date(y, m, d).unwrap();
+        assert_eq!(
+            billing_period(date(2026, Month::October, 18), 1),
+            (date(2026, Month::October, 1), date(2026, Month::November, 1))
+        );
+        assert_eq!(
+            billing_period(date(2026, Month::January, 3), 15),
+            (date(2025, Month::December, 15), date(2026, Month::January, 15))
+        );
+    }
+
+    #[test]
+    fn parses_subscription_settings() {
+        let parsed = settings(serde_json::json!({
+            "monthlyBudget": " 250 ",
+            "periodStartDay": "40",
+            "subscriptions": "Claude Max=100, Copilot=10 usd, broken, =5",
+        }));
+        assert_eq!(parsed.monthly_budget, Some(250.0));
+        assert_eq!(parsed.period_start_day, 28);
+        assert_eq!(
+            parsed.subscriptions,
+            vec![
+                Subscription { name: "Claude Max".to_string(), amount: 100.0, currency: None },
+                Subscription { name: "Copilot".to_string(), amount: 10.0, currency: Some("USD".to_string()) },
+            ]
+        );
+        assert!(regex_lite::Regex::new(SUBSCRIPTIONS_PATTERN).unwrap().is_match("A=1, B c=2.5 EUR"));
+        assert!(!regex_lite::Regex::new(SUBSCRIPTIONS_PATTERN).unwrap().is_match("A=1 euros"));
+    }
+
+    #[test]
+    fn sums_billable_money_lines_and_projects_from_history() {
+        let rates = RateTable::parse(r#"{ "base": "USD", "date": "2026-10-01", "rates": { "EUR": 0.5 } }"#)
+            .expect("rates");
+        let eur = |used: f64| ConvertedAmount {
+            code: "EUR".to_string(),
+            used,
+            limit: 50.0,
+            rate: 0.5,
+            rates_date: "2026-10-01".to_string(),
+        };
+        let mut outputs = HashMap::new();
+        outputs.insert(
+            "cursor".to_string(),
+            vec![output(
+                "cursor",
+                vec![
+                    money("on-demand", 40.0, ProgressFormat::Dollars, Some(eur(20.0))),
+                    money("plan-usage", 20.0, ProgressFormat::Dollars, Some(eur(10.0))),
+                ],
+            )],
+        );
+        outputs.insert(
+            "mistral".to_string(),
+            vec![output(
+                "mistral",
+                vec![
+                    money("spend", 10.0, ProgressFormat::Currency { code: "EUR".to_string() }, None),
+                    money("spend", 50.0, ProgressFormat::Percent, None),
+                    money("spend", 3.0, ProgressFormat::Currency { code: "CHF".to_string() }, None),
+                ],
+            )],
+        );
+        outputs.insert(ID.to_string(), vec![output(ID, vec![money("spend", 999.0, ProgressFormat::Dollars, None)])]);
+        outputs.insert(
+            "amp".to_string(),
+            vec![output("amp", vec![money("free", 5.0, ProgressFormat::Dollars, None)])],
+        );
+        let spend_lines: HashMap<String, HashSet<String>> = [
+            ("cursor", vec!["on-demand"]),
+            ("mistral", vec!["spend"]),
+            ("amp", vec![]),
+            (ID, vec!["spend"]),
+        ]
+        .into_iter()
+        .map(|(id, lines)| (id.to_string(), lines.into_iter().map(str::to_string).collect()))
+        .collect();
+        let settings = settings(serde_json::json!({
+            "monthlyBudget": "100",
+            "periodStartDay": "1",
+            "subscriptions": "Claude Max=100 USD",
+        }));
+        let mut history = History::default();
+        history.record("2026-10-05", "spend:EUR", 10.0);
+
+        let summary = summarize(&outputs, &spend_lines, &settings, &rates, "EUR", &history, at("2026-10-15T12:00:00Z"));
+        assert_eq!(summary.providers, vec![("cursor".to_string(), 20.0), ("mistral".to_string(), 10.0)]);
+        assert_eq!((summary.variable, summary.fixed, summary.unconverted), (30.0, 50.0, 1));
+        // 20 EUR over 10 days, 16.5 days left
+        assert!((summary.projected - (30.0 + 2.0 * 16.5 + 50.0)).abs() < 1e-9);
+
+        let lines = summary_lines(&summary);
+        let json = serde_json::to_value(&lines).expect("serialize");
+        assert_eq!(json[0]["used"], 80.0);
+        assert_eq!(json[0]["format"], serde_json::json!({ "kind": "currency", "code": "EUR" }));
+        assert_eq!(json[0]["resetsAt"], "2026-11-01T00:00:00Z");
+        assert_eq!(json[1]["color"], OVER_BUDGET_COLOR);
+        assert_eq!(json[2]["subtitle"], "Claude Max");
+        assert_eq!(json[lines.len() - 1]["label"], "Not included");
+    }
+}
@@ -0,0 +1,259 @@
+use crate::plugin_engine::pricing;
+use crate::plugin_engine::runtime::{MetricLine, PluginOutput, ProgressFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// User exchange-rate table in the app data dir; replaces the built-in one when valid.
+pub const RATES_FILE: &str = "exchange-rates.json";
+
+/// Display currency when the user has not picked one.
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+const BUILTIN_RATES: &str = include_str!("exchange_rates.json");
+
+/// ISO 4217 code: three ASCII capital letters.
+pub fn valid_code(code: &str) -> bool {
+    code.len() == 3 && code.bytes().all(|b| b.is_ascii_uppercase())
+}
+
+/// Units of each currency per one unit of `base`, as of `date` (`YYYY-MM-DD`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateTable {
+    pub base: String,
+    pub date: String,
+    pub rates: BTreeMap<String, f64>,
+}
+
+impl RateTable {
+    pub fn builtin() -> &'static RateTable {
+        static BUILTIN: OnceLock<RateTable> = OnceLock::new();
+        BUILTIN.get_or_init(|| RateTable::parse(BUILTIN_RATES).expect("built-in exchange rates"))
+    }
+
+    /// `<app_data_dir>/exchange-rates.json` when present and valid, else the built-in table.
+    pub fn load(app_data_dir: &Path) -> RateTable {
+        let path = app_data_dir.join(RATES_FILE);
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return RateTable::builtin().clone();
+        };
+        RateTable::parse(&text).unwrap_or_else(|e| {
+            log::warn!("ignoring invalid exchange rates in {}: {}", path.display(), e);
+            RateTable::builtin().clone()
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<RateTable, String> {
+        let mut table: RateTable = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        table.base = table.base.trim().to_ascii_uppercase();
+        if !valid_code(&table.base) {
+            return Err(format!("invalid base currency: {}", table.base));
+        }
+        if !pricing::valid_date(&table.date) {
+            return Err(format!("invalid date {} (expected YYYY-MM-DD)", table.date));
+        }
+        let mut rates = BTreeMap::new();
+        for (code, rate) in table.rates {
+            let code = code.trim().to_ascii_uppercase();
+            if !valid_code(&code) {
+                return Err(format!("invalid currency code: {}", code));
+            }
+            if !rate.is_finite() || rate <= 0.0 {
+                return Err(format!("{}: rate must be a positive number", code));
+            }
+            rates.insert(code, rate);
+        }
+        rates.insert(table.base.clone(), 1.0);
+        table.rates = rates;
+        Ok(table)
+    }
+
+    /// Multiplier from an amount in `from` to `to`, or `None` if either is unknown.
+    pub fn rate(&self, from: &str, to: &str) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        Some(self.rates.get(to)? / self.rates.get(from)?)
+    }
+}
+
+/// A progress line's amounts in the user's display currency.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertedAmount {
+    pub code: String,
+    pub used: f64,
+    pub limit: f64,
+    pub rate: f64,
+    pub rates_date: String,
+}
+
+/// Currency of a money-formatted progress line. `dollars` is USD.
+pub fn source_currency(format: &ProgressFormat) -> Option<&str> {
+    match format {
+        ProgressFormat::Dollars => Some("USD"),
+        ProgressFormat::Currency { code } => Some(code),
+        _ => None,
+    }
+}
+
+/// Fills `converted` on money progress lines whose currency differs from `display`. Lines in
+/// a currency missing from the table keep only their original amounts.
+pub fn convert_outputs(outputs: &mut [PluginOutput], rates: &RateTable, display: &str) {
+    for output in outputs.iter_mut() {
+        let provider_id = output.provider_id.clone();
+        for line in output.all_lines_mut() {
+            let MetricLine::Progress {
+                used,
+                limit,
+                format,
+                converted,
+                ..
+            } = line
+            else {
+                continue;
+            };
+            *converted = None;
+            let Some(code) = source_currency(format) else {
+                continue;
+            };
+            if code == display {
+                continue;
+            }
+            match rates.rate(code, display) {
+                Some(rate) => {
+                    *converted = Some(ConvertedAmount {
+                        code: display.to_string(),
+                        used: *used * rate,
+                        limit: *limit * rate,
+                        rate,
+                        rates_date: rates.date.clone(),
+                    });
+                }
+                None => log::warn!(
+                    "no exchange rate from {} to {} for {}, showing original amount",
+                    code,
+                    display,
+                    provider_id
+                ),
+            }
+        }
+    }
+}
+
+fn symbol(code: &str) -> Option<&'static str> {
+    match code {
+        "USD" => Some("$"),
+        "EUR" => Some("€"),
+        "GBP" => Some("£"),
+        "JPY" => Some("JP¥"),
+        "CNY" => Some("CN¥"),
+        "INR" => Some("₹"),
+        "KRW" => Some("₩"),
+        _ => None,
+    }
+}
+
+/// `$12.34`, `€5.00` or `3.00 CHF` for the tray. Yen and won have no minor unit.
+pub fn format_amount(amount: f64, code: &str) -> String {
+    let text = match code {
+        "JPY" | "KRW" | "IDR" => format!("{:.0}", amount),
+        _ => format!("{:.2}", amount),
+    };
+    match symbol(code) {
+        Some(symbol) => format!("{}{}", symbol, text),
+        None => format!("{} {}", text, code),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money_line(used: f64, limit: f64, format: ProgressFormat) -> MetricLine {
+        MetricLine::Progress {
+            id: None,
+            label: "Spend".to_string(),
+            used,
+            limit,
+            format,
+            resets_at: None,
+            period_duration_ms: None,
+            color: None,
+            segments: Vec::new(),
+            soft_limit: None,
+            hard_limit: None,
+            converted: None,
+            humanized: None,
+        }
+    }
+
+    fn output(lines: Vec<MetricLine>) -> PluginOutput {
+        PluginOutput {
+            provider_id: "test".to_string(),
+            display_name: "Test".to_string(),
+            plan: None,
+            lines,
+            icon_url: String::new(),
+            account: None,
+            account_id: None,
+            sections: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn converts_money_lines_and_keeps_originals() {
+        let rates = RateTable::parse(
+            r#"{ "base": "USD", "date": "2026-09-30", "rates": { "eur": 0.8, "GBP": 0.5 } }"#,
+        )
+        .expect("rates");
+        let mut outputs = vec![output(vec![
+            money_line(10.0, 100.0, ProgressFormat::Dollars),
+            money_line(8.0, 40.0, ProgressFormat::Currency { code: "EUR".to_string() }),
+            money_line(5.0, 10.0, ProgressFormat::Currency { code: "CHF".to_string() }),
+            money_line(50.0, 100.0, ProgressFormat::Percent),
+        ])];
+
+        convert_outputs(&mut outputs, &rates, "EUR");
+
+        let converted: Vec<Option<(f64, f64)>> = outputs[0]
+            .lines
+            .iter()
+            .map(|line| match line {
+                MetricLine::Progress { converted, .. } => converted.as_ref().map(|c| (c.used, c.limit)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(converted, vec![Some((8.0, 80.0)), None, None, None]);
+
+        let json = serde_json::to_value(&outputs[0].lines[0]).expect("serialize");
+        assert_eq!(json["used"], 10.0);
+        assert_eq!(json["format"]["kind"], "dollars");
+        assert_eq!(json["converted"]["code"], "EUR");
+        assert_eq!(json["converted"]["ratesDate"], "2026-09-30");
+
+        convert_outputs(&mut outputs, &rates, "GBP");
+        let json = serde_json::to_value(&outputs[0].lines[1]).expect("serialize");
+        assert_eq!(json["format"], serde_json::json!({ "kind": "currency", "code": "EUR" }));
+        assert_eq!(json["converted"]["used"], 5.0);
+    }
+
+    #[test]
+    fn rate_tables_are_validated() {
+        assert!(RateTable::builtin().rate("USD", "EUR").is_some());
+        assert!(RateTable::parse(r#"{ "base": "USD", "date": "2026-10-01", "rates": { "EUR": 0 } }"#).is_err());
+        assert!(RateTable::parse(r#"{ "base": "USD", "date": "Oct 1", "rates": {} }"#).is_err());
+        assert!(RateTable::parse(r#"{ "base": "US", "date": "2026-10-01", "rates": {} }"#).is_err());
+
+        let eur = RateTable::parse(r#"{ "base": "EUR", "date": "2026-10-01", "rates": { "USD": 1.25 } }"#)
+            .expect("rates");
+        assert_eq!(eur.rate("USD", "EUR"), Some(0.8));
+        assert_eq!(eur.rate("USD", "XYZ"), None);
+        assert_eq!(format_amount(12.345, "EUR"), "€12.35");
+        assert_eq!(format_amount(1234.4, "JPY"), "JP¥1234");
+        assert_eq!(format_amount(12.5, "CNY"), "CN¥12.50");
+        assert_eq!(format_amount(3.0, "CHF"), "3.00 CHF");
+    }
+}
@@ -0,0 +1,137 @@
+//! Problems found while loading a plugin directory, reported by the `get_plugin_diagnostics`
+//! command and `openusage validate` instead of only being logged.
+
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The plugin is not loaded.
+    Error,
+    /// The plugin is loaded without the part the diagnostic points at.
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// File the problem is in.
+    pub file: String,
+    /// JSON pointer into `file` (e.g. `/lines/2/scope`); empty for the whole file.
+    pub pointer: String,
+    pub message: String,
+    /// How to fix it, when there is an obvious fix.
+    pub suggestion: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn suggest(&mut self, suggestion: impl Into<String>) {
+        self.suggestion = Some(suggestion.into());
+    }
+}
+
+/// Result of validating one plugin directory.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginReport {
+    /// The plugin's `plugin.json`.
+    pub file: String,
+    /// Manifest id, when it could be read.
+    pub id: Option<String>,
+    pub loaded: bool,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl PluginReport {
+    pub fn new(manifest_path: &Path) -> PluginReport {
+        PluginReport {
+            file: manifest_path.display().to_string(),
+            id: None,
+            loaded: false,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn error(&mut self, pointer: impl Into<String>, message: impl Into<String>) -> &mut Diagnostic {
+        self.push(Severity::Error, pointer.into(), message.into())
+    }
+
+    pub fn warning(&mut self, pointer: impl Into<String>, message: impl Into<String>) -> &mut Diagnostic {
+        self.push(Severity::Warning, pointer.into(), message.into())
+    }
+
+    fn push(&mut self, severity: Severity, pointer: String, message: String) -> &mut Diagnostic {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            file: self.file.clone(),
+            pointer,
+            message,
+            suggestion: None,
+        });
+        self.diagnostics.last_mut().expect("just pushed")
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    /// Writes every diagnostic to the app log.
+    pub fn log(&self) {
+        let name = self.id.as_deref().unwrap_or(&self.file);
+        for diagnostic in &self.diagnostics {
+            let level = match diagnostic.severity {
+                Severity::Error => log::Level::Error,
+                Severity::Warning => log::Level::Warn,
+            };
+            log::log!(level, "plugin {}: {}", name, diagnostic);
+        }
+    }
+}
+
+/// `at /lines/2/scope: message (suggestion)`, the format used in logs and the CLI.
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.pointer.is_empty() {
+            write!(f, "at {}: ", self.pointer)?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " ({})", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// A map key as a JSON pointer segment (RFC 6901).
+pub fn pointer_segment(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagnostics_render_pointer_and_suggestion() {
+        let mut report = PluginReport::new(Path::new("plugins/x/plugin.json"));
+        report.warning("", "plugin.json is empty");
+        report
+            .error("/lines/0/scope", "unknown scope \"sidebar\"")
+            .suggest("use \"overview\" or \"detail\"");
+        assert!(report.has_errors());
+        let lines: Vec<String> = report.diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "plugin.json is empty",
+                "at /lines/0/scope: unknown scope \"sidebar\" (use \"overview\" or \"detail\")",
+            ]
+        );
+        assert_eq!(report.diagnostics[1].file, "plugins/x/plugin.json");
+        assert_eq!(pointer_segment("a/b~c"), "a~1b~0c");
+    }
+}
@@ -0,0 +1,592 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::thread::ThreadId;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+/// Most paths one `glob` call returns, whatever `limit` the plugin asks for.
+pub const MAX_GLOB_RESULTS: usize = 1000;
+
+/// Most matches one `glob` call looks at before giving up. Results are sorted, so every
+/// match has to be seen; past this a pattern like `**/*` in the home directory fails
+/// instead of walking the whole tree.
+pub const MAX_GLOB_VISITED: usize = 100_000;
+
+/// How long `lock` waits for another process before giving up.
+pub const DEFAULT_LOCK_TIMEOUT_MS: u64 = 5000;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStat {
+    /// `file`, `dir`, `symlink` or `other`
+    pub kind: &'static str,
+    pub size: u64,
+    pub modified_ms: Option<u64>,
+    pub readonly: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirEntry {
+    pub name: String,
+    pub path: String,
+    #[serde(flatten)]
+    pub stat: FileStat,
+}
+
+fn file_stat(meta: &std::fs::Metadata) -> FileStat {
+    let file_type = meta.file_type();
+    let kind = if file_type.is_symlink() {
+        "symlink"
+    } else if file_type.is_dir() {
+        "dir"
+    } else if file_type.is_file() {
+        "file"
+    } else {
+        "other"
+    };
+    FileStat {
+        kind,
+        size: meta.len(),
+        modified_ms: meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64),
+        readonly: meta.permissions().readonly(),
+    }
+}
+
+/// Metadata of `path` (following symlinks), or `None` when it does not exist.
+pub fn stat(path: &str) -> Result<Option<FileStat>, String> {
+    match std::fs::metadata(path) {
+        Ok(meta) => Ok(Some(file_stat(&meta))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Entries of a directory sorted by name. Symlinks are reported as `symlink`, not followed.
+pub fn list_dir(path: &str) -> Result<Vec<DirEntry>, String> {
+    let mut entries: Vec<DirEntry> = std::fs::read_dir(path)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.path().symlink_metadata().ok()?;
+            Some(DirEntry {
+                name: entry.file_name().to_string_lossy().to_string(),
+                path: entry.path().to_string_lossy().to_string(),
+                stat: file_stat(&meta),
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+/// The first `limit` paths, in sorted order, under `dir` matching the relative `pattern`
+/// (`*`, `?`, `[...]`, `**`). The pattern cannot be absolute or contain `..`, and matches that
+/// resolve outside `dir` through symlinks are dropped. Fails when more than
+/// `MAX_GLOB_VISITED` paths match.
+pub fn glob(dir: &str, pattern: &str, limit: usize) -> Result<Vec<String>, String> {
+    glob_visiting(dir, pattern, limit, MAX_GLOB_VISITED)
+}
+
+fn glob_visiting(dir: &str, pattern: &str, limit: usize, max_visited: usize) -> Result<Vec<String>, String> {
+    let relative = Path::new(pattern);
+    if relative.has_root()
+        || relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err("glob pattern must be relative and stay inside the directory".to_string());
+    }
+    let root = Path::new(dir)
+        .canonicalize()
+        .map_err(|e| format!("cannot read {}: {}", dir, e))?;
+    // Match against `dir` as given: canonical Windows paths (`\\?\C:\...`) do not glob
+    let full = format!("{}/{}", glob::Pattern::escape(dir), pattern);
+    let paths = glob::glob(&full).map_err(|e| format!("invalid glob pattern: {}", e))?;
+
+    let limit = limit.min(MAX_GLOB_RESULTS);
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+    // Walk every match and keep the smallest `limit`, so the result does not depend on the
+    // order the directories are read in
+    let mut out = BTreeSet::new();
+    for (visited, path) in paths.flatten().enumerate() {
+        if visited == max_visited {
+            return Err(format!(
+                "glob pattern matches more than {} paths; use a narrower pattern",
+                max_visited
+            ));
+        }
+        let inside = path
+            .canonicalize()
+            .map(|resolved| resolved.starts_with(&root))
+            .unwrap_or(false);
+        if !inside {
+            continue;
+        }
+        out.insert(path.to_string_lossy().to_string());
+        if out.len() > limit {
+            out.pop_last();
+        }
+    }
+    Ok(out.into_iter().collect())
+}
+
+/// Up to `limit` lines starting at zero-based line `offset`, without line endings.
+pub fn read_lines(path: &str, offset: usize, limit: Option<usize>) -> Result<Vec<String>, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    BufReader::new(file)
+        .lines()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|line| line.map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// File contents (or `length` bytes from `offset`) as base64.
+pub fn read_bytes(path: &str, offset: u64, length: Option<u64>) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+    let mut bytes = Vec::new();
+    match length {
+        Some(length) => file.take(length).read_to_end(&mut bytes),
+        None => file.read_to_end(&mut bytes),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(STANDARD.encode(bytes))
+}
+
+/// Delete a file or directory tree inside `data_dir` (the plugin's own data dir). Returns
+/// `false` when nothing exists at `path`.
+pub fn remove(data_dir: &Path, path: &str) -> Result<bool, String> {
+    const OUTSIDE: &str = "remove is limited to the plugin data directory";
+    let target = PathBuf::from(path);
+    let root = data_dir
+        .canonicalize()
+        .map_err(|e| format!("plugin data dir unavailable: {}", e))?;
+    // Resolve the parent so a symlink itself can be removed without following it. The sandbox
+    // is checked before existence, so a missing path outside it is still an error.
+    let parent = target
+        .parent()
+        .and_then(resolve_existing_ancestor)
+        .ok_or(OUTSIDE)?;
+    let resolved = parent.join(target.file_name().ok_or("invalid path")?);
+    if !resolved.starts_with(&root) || resolved == root {
+        return Err(OUTSIDE.to_string());
+    }
+    let Ok(meta) = resolved.symlink_metadata() else {
+        return Ok(false);
+    };
+    if meta.is_dir() {
+        std::fs::remove_dir_all(&resolved)
+    } else {
+        std::fs::remove_file(&resolved)
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// `path` with its nearest existing ancestor resolved through symlinks and the missing
+/// components appended. `None` when a missing component is `..`.
+fn resolve_existing_ancestor(path: &Path) -> Option<PathBuf> {
+    let mut missing = Vec::new();
+    let mut current = path;
+    loop {
+        if let Ok(resolved) = current.canonicalize() {
+            return Some(missing.iter().rev().fold(resolved, |acc, name| acc.join(name)));
+        }
+        missing.push(current.file_name()?);
+        current = current.parent()?;
+    }
+}
+
+/// Replace `path` with `content` without readers ever seeing a partial file: write a temp file
+/// in the same directory, fsync it, copy the old file's permissions and rename it over the
+/// target. A symlinked target is replaced at its resolved location, keeping the link.
+pub fn write_text_atomic(path: &str, content: &str) -> Result<(), String> {
+    let target = match std::fs::canonicalize(path) {
+        Ok(resolved) => resolved,
+        Err(_) => PathBuf::from(path),
+    };
+    let dir = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let name = target
+        .file_name()
+        .ok_or_else(|| format!("invalid path: {}", path))?
+        .to_string_lossy()
+        .to_string();
+    let nanos = std::time::SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let temp = dir.join(format!(".{}.openusage-{}-{}.tmp", name, std::process::id(), nanos));
+
+    let result = write_temp(&temp, content, &target).and_then(|_| rename_with_retry(&temp, &target));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+fn write_temp(temp: &Path, content: &str, target: &Path) -> Result<(), String> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    // Credential files are private; never expose the new contents through a wider default mode
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(temp).map_err(|e| e.to_string())?;
+    file.write_all(content.as_bytes()).map_err(|e| e.to_string())?;
+    file.sync_all().map_err(|e| e.to_string())?;
+    drop(file);
+    if let Ok(meta) = std::fs::metadata(target) {
+        std::fs::set_permissions(temp, meta.permissions()).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Windows refuses to replace a file another process has open without share-delete, which
+/// CLIs briefly do while reading their credentials; retry for a short while before failing.
+fn rename_with_retry(from: &Path, to: &Path) -> Result<(), String> {
+    let mut attempt = 0;
+    loop {
+        match std::fs::rename(from, to) {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < 5 && e.kind() == std::io::ErrorKind::PermissionDenied => {
+                attempt += 1;
+                std::thread::sleep(Duration::from_millis(20 * attempt));
+            }
+            Err(e) => return Err(format!("cannot replace {}: {}", to.display(), e)),
+        }
+    }
+}
+
+/// Lock files held by this process, with the holding thread and how many guards it has out.
+/// The file stays open, and locked, until the last guard is dropped.
+static HELD_LOCKS: Mutex<BTreeMap<PathBuf, HeldLock>> = Mutex::new(BTreeMap::new());
+
+struct HeldLock {
+    thread: ThreadId,
+    guards: usize,
+    _file: File,
+}
+
+/// Advisory exclusive lock for `path`, released on drop. The lock file lives in `lock_dir`,
+/// named after the resolved target, so nothing is left behind in directories owned by other
+/// programs. Other OpenUsage instances wait for it; it does not stop plain writers.
+pub struct FileLock {
+    lock_path: PathBuf,
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let Ok(mut held) = HELD_LOCKS.lock() else {
+            return;
+        };
+        if let Some(entry) = held.get_mut(&self.lock_path) {
+            entry.guards -= 1;
+            if entry.guards == 0 {
+                held.remove(&self.lock_path);
+            }
+        }
+    }
+}
+
+/// Takes the lock for `path`. The thread holding it can take it again without waiting, so
+/// `merge_json` works inside a `lock` on the same file.
+pub fn lock(lock_dir: &Path, path: &str, timeout: Duration) -> Result<FileLock, String> {
+    let target = resolve_existing_ancestor(Path::new(path))
+        .ok_or_else(|| format!("cannot resolve {}", path))?;
+    let digest = ring::digest::digest(&ring::digest::SHA256, target.to_string_lossy().as_bytes());
+    let name: String = digest.as_ref()[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    let lock_path = lock_dir.join(format!("{}.lock", name));
+    let thread = std::thread::current().id();
+    {
+        let mut held = HELD_LOCKS.lock().map_err(|_| "lock registry poisoned".to_string())?;
+        if let Some(entry) = held.get_mut(&lock_path).filter(|entry| entry.thread == thread) {
+            entry.guards += 1;
+            return Ok(FileLock { lock_path });
+        }
+    }
+
+    std::fs::create_dir_all(lock_dir).map_err(|e| format!("cannot create {}: {}", lock_dir.display(), e))?;
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&lock_path)
+        .map_err(|e| format!("cannot open {}: {}", lock_path.display(), e))?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        match file.try_lock() {
+            Ok(()) => {
+                let mut held = HELD_LOCKS.lock().map_err(|_| "lock registry poisoned".to_string())?;
+                held.insert(lock_path.clone(), HeldLock { thread, guards: 1, _file: file });
+                return Ok(FileLock { lock_path });
+            }
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(25));
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(format!("timed out waiting for lock on {}", path));
+            }
+            Err(TryLockError::Error(e)) => return Err(format!("cannot lock {}: {}", path, e)),
+        }
+    }
+}
+
+/// Apply a JSON merge patch (RFC 7386): objects merge recursively, `null` deletes a key and
+/// anything else replaces the target value.
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    let target = target.as_object_mut().expect("object");
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// Merge `patch` into the JSON file at `path` under its lock and write it back atomically, so
+/// fields written concurrently by the owning CLI survive. Key order, pretty vs. compact
+/// formatting and a trailing newline are kept. A missing file starts empty; a file that is not
+/// valid JSON is left untouched and reported.
+pub fn merge_json(lock_dir: &Path, path: &str, patch: &Value, timeout: Duration) -> Result<Value, String> {
+    let _lock = lock(lock_dir, path, timeout)?;
+    let existing = match std::fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut value = match existing.as_deref() {
+        Some(text) if !text.trim().is_empty() => serde_json::from_str(text)
+            .map_err(|e| format!("{} is not valid JSON, refusing to overwrite: {}", path, e))?,
+        _ => Value::Null,
+    };
+    merge_patch(&mut value, patch);
+
+    let original = existing.unwrap_or_default();
+    let pretty = original.is_empty() || original.trim_end().contains('\n');
+    let mut text = if pretty {
+        serde_json::to_string_pretty(&value)
+    } else {
+        serde_json::to_string(&value)
+    }
+    .map_err(|e| e.to_string())?;
+    if original.ends_with('\n') {
+        text.push('\n');
+    }
+    write_text_atomic(path, &text)?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_engine::test_dir;
+    use serde_json::json;
+
+    fn s(path: &Path) -> String {
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn list_dir_and_stat_report_kinds() {
+        let dir = test_dir("list");
+        std::fs::write(dir.join("b.json"), "{}").unwrap();
+        std::fs::create_dir(dir.join("a")).unwrap();
+
+        let entries = list_dir(&s(&dir)).expect("list");
+        let kinds: Vec<(&str, &str)> = entries.iter().map(|e| (e.name.as_str(), e.stat.kind)).collect();
+        assert_eq!(kinds, vec![("a", "dir"), ("b.json", "file")]);
+        assert_eq!(entries[1].stat.size, 2);
+
+        assert_eq!(stat(&s(&dir.join("b.json"))).unwrap().map(|st| st.kind), Some("file"));
+        assert_eq!(stat(&s(&dir.join("missing"))).unwrap(), None);
+    }
+
+    #[test]
+    fn glob_matches_inside_directory_only() {
+        let dir = test_dir("glob");
+        std::fs::create_dir_all(dir.join("projects/app")).unwrap();
+        std::fs::write(dir.join("projects/app/1.jsonl"), "").unwrap();
+        std::fs::write(dir.join("projects/app/2.jsonl"), "").unwrap();
+        std::fs::write(dir.join("projects/notes.txt"), "").unwrap();
+
+        let found = glob(&s(&dir), "projects/**/*.jsonl", 100).expect("glob");
+        assert_eq!(found.len(), 2);
+        assert!(found[0].ends_with("1.jsonl"));
+        let first = glob(&s(&dir), "projects/**/*.jsonl", 1).unwrap();
+        assert_eq!(first.len(), 1);
+        assert!(first[0].ends_with("1.jsonl"));
+        assert!(glob(&s(&dir), "projects/**/*.jsonl", 0).unwrap().is_empty());
+
+        assert!(glob(&s(&dir), "../*", 100).is_err());
+        assert!(glob(&s(&dir), "/etc/*", 100).is_err());
+    }
+
+    #[test]
+    fn glob_fails_past_the_visited_cap() {
+        let dir = test_dir("glob-cap");
+        for name in ["a", "b", "c"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        assert_eq!(glob_visiting(&s(&dir), "*", 1, 3).unwrap().len(), 1);
+        let err = glob_visiting(&s(&dir), "*", 1, 2).unwrap_err();
+        assert!(err.contains("more than 2 paths"), "{}", err);
+    }
+
+    #[test]
+    fn read_lines_and_bytes_support_ranges() {
+        let dir = test_dir("read");
+        let file = dir.join("log.txt");
+        std::fs::write(&file, "one\ntwo\r\nthree\nfour").unwrap();
+
+        assert_eq!(read_lines(&s(&file), 1, Some(2)).unwrap(), vec!["two", "three"]);
+        assert_eq!(read_lines(&s(&file), 3, None).unwrap(), vec!["four"]);
+        assert_eq!(read_bytes(&s(&file), 0, Some(3)).unwrap(), "b25l");
+        assert_eq!(read_bytes(&s(&file), 15, None).unwrap(), STANDARD.encode("four"));
+    }
+
+    #[test]
+    fn remove_is_limited_to_data_dir() {
+        let data_dir = test_dir("remove");
+        let outside = test_dir("outside");
+        std::fs::create_dir_all(data_dir.join("cache/nested")).unwrap();
+        std::fs::write(data_dir.join("cache/nested/x"), "").unwrap();
+        std::fs::write(outside.join("keep"), "").unwrap();
+
+        assert_eq!(remove(&data_dir, &s(&data_dir.join("cache"))), Ok(true));
+        assert!(!data_dir.join("cache").exists());
+        assert_eq!(remove(&data_dir, &s(&data_dir.join("cache"))), Ok(false));
+
+        assert!(remove(&data_dir, &s(&outside.join("keep"))).is_err());
+        assert!(remove(&data_dir, &s(&data_dir.join("../").join(outside.file_name().unwrap()).join("keep"))).is_err());
+        assert!(remove(&data_dir, &s(&data_dir)).is_err());
+        assert!(outside.join("keep").exists());
+
+        assert!(remove(&data_dir, &s(&outside.join("missing"))).is_err());
+        assert!(remove(&data_dir, &s(&outside.join("gone/missing"))).is_err());
+        assert_eq!(remove(&data_dir, &s(&data_dir.join("gone/missing"))), Ok(false));
+    }
+
+    #[test]
+    fn write_text_atomic_replaces_and_keeps_permissions() {
+        let dir = test_dir("atomic");
+        let file = dir.join("auth.json");
+        std::fs::write(&file, "old").unwrap();
+        #[cfg(unix)]
+        {
+            let mut perms = std::fs::metadata(&file).unwrap().permissions();
+            std::os::unix::fs::PermissionsExt::set_mode(&mut perms, 0o640);
+            std::fs::set_permissions(&file, perms).unwrap();
+        }
+
+        write_text_atomic(&s(&file), "new").expect("write");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "new");
+        #[cfg(unix)]
+        assert_eq!(
+            std::os::unix::fs::PermissionsExt::mode(&std::fs::metadata(&file).unwrap().permissions()) & 0o777,
+            0o640
+        );
+
+        write_text_atomic(&s(&dir.join("fresh.json")), "{}").expect("create");
+        let names: Vec<String> = list_dir(&s(&dir)).unwrap().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["auth.json", "fresh.json"]);
+    }
+
+    #[test]
+    fn lock_waits_for_holder_and_times_out() {
+        let dir = test_dir("lock");
+        let locks = dir.join("locks");
+        let path = s(&dir.join("creds.json"));
+        let try_elsewhere = |path: String| {
+            let locks = locks.clone();
+            std::thread::spawn(move || lock(&locks, &path, Duration::from_millis(50)).is_ok())
+                .join()
+                .unwrap()
+        };
+
+        let held = lock(&locks, &path, Duration::from_millis(100)).expect("lock");
+        assert!(!try_elsewhere(s(&dir.join("./creds.json"))));
+        assert!(try_elsewhere(s(&dir.join("other.json"))));
+        // The holding thread can take the lock again; others wait until every guard is dropped
+        let nested = lock(&locks, &path, Duration::from_millis(50)).expect("nested lock");
+        drop(held);
+        assert!(!try_elsewhere(path.clone()));
+        drop(nested);
+        assert!(try_elsewhere(path.clone()));
+
+        let names: Vec<String> = list_dir(&s(&dir)).unwrap().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["locks"]);
+    }
+
+    #[test]
+    fn merge_patch_follows_rfc_7386() {
+        let mut doc = json!({ "a": "b", "c": { "d": "e", "f": "g" } });
+        merge_patch(&mut doc, &json!({ "a": "z", "c": { "f": null } }));
+        assert_eq!(doc, json!({ "a": "z", "c": { "d": "e" } }));
+
+        let mut scalar = json!("x");
+        merge_patch(&mut scalar, &json!({ "k": [1] }));
+        assert_eq!(scalar, json!({ "k": [1] }));
+    }
+
+    #[test]
+    fn merge_json_updates_only_patched_fields() {
+        let dir = test_dir("merge");
+        let file = dir.join("auth.json");
+        std::fs::write(
+            &file,
+            "{\n  \"OPENAI_API_KEY\": null,\n  \"tokens\": {\"access_token\": \"old\", \"id_token\": \"id\"},\n  \"extra\": 1\n}\n",
+        )
+        .unwrap();
+
+        let locks = test_dir("merge-locks");
+        let merged = merge_json(
+            &locks,
+            &s(&file),
+            &json!({ "tokens": { "access_token": "new" }, "last_refresh": "now" }),
+            Duration::from_secs(1),
+        )
+        .expect("merge");
+        assert_eq!(merged["tokens"], json!({ "access_token": "new", "id_token": "id" }));
+        let text = std::fs::read_to_string(&file).unwrap();
+        assert!(text.starts_with("{\n  \"OPENAI_API_KEY\": null,\n  \"tokens\""));
+        assert!(text.ends_with("\"last_refresh\": \"now\"\n}\n"));
+
+        let compact = dir.join("compact.json");
+        std::fs::write(&compact, r#"{"a":1}"#).unwrap();
+        merge_json(&locks, &s(&compact), &json!({ "b": 2 }), Duration::from_secs(1)).unwrap();
+        assert_eq!(std::fs::read_to_string(&compact).unwrap(), r#"{"a":1,"b":2}"#);
+
+        let broken = dir.join("broken.json");
+        std::fs::write(&broken, "{not json").unwrap();
+        assert!(merge_json(&locks, &s(&broken), &json!({ "a": 1 }), Duration::from_secs(1)).is_err());
+        assert_eq!(std::fs::read_to_string(&broken).unwrap(), "{not json");
+
+        let names: Vec<String> = list_dir(&s(&dir)).unwrap().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["auth.json", "broken.json", "compact.json"]);
+    }
+}
@@ -0,0 +1,84 @@
+//! Daily snapshots of numeric series (e.g. total spend in a currency), kept across restarts
+//! in `<app_data_dir>/history.json` so totals can be compared with earlier days.
+
+use crate::plugin_engine::files;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const HISTORY_FILE: &str = "history.json";
+
+/// Days kept; a little over a year so the same period last year stays available.
+pub const RETENTION_DAYS: i64 = 400;
+
+/// Last value of each series per local calendar day (`YYYY-MM-DD`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct History {
+    pub days: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+impl History {
+    /// The stored history, or an empty one when missing or unreadable.
+    pub fn load(app_data_dir: &Path) -> History {
+        let path = app_data_dir.join(HISTORY_FILE);
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return History::default();
+        };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("ignoring unreadable history in {}: {}", path.display(), e);
+            History::default()
+        })
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(app_data_dir).map_err(|e| e.to_string())?;
+        let text = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        files::write_text_atomic(&app_data_dir.join(HISTORY_FILE).to_string_lossy(), &text)
+    }
+
+    /// Sets the value of `series` on `date`, replacing an earlier value from the same day.
+    pub fn record(&mut self, date: &str, series: &str, value: f64) {
+        self.days
+            .entry(date.to_string())
+            .or_default()
+            .insert(series.to_string(), value);
+    }
+
+    /// First recorded `(date, value)` of `series` on or after `from`.
+    pub fn first_since(&self, series: &str, from: &str) -> Option<(&str, f64)> {
+        self.days
+            .range(from.to_string()..)
+            .find_map(|(date, values)| values.get(series).map(|value| (date.as_str(), *value)))
+    }
+
+    /// Drops days before `oldest` (`YYYY-MM-DD`).
+    pub fn prune(&mut self, oldest: &str) {
+        self.days = self.days.split_off(oldest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_engine::test_dir;
+
+    #[test]
+    fn records_daily_values_and_prunes() {
+        let app_data_dir = test_dir("history");
+        let mut history = History::load(&app_data_dir);
+        history.record("2026-09-30", "spend:USD", 4.0);
+        history.record("2026-10-02", "spend:USD", 10.0);
+        history.record("2026-10-02", "spend:USD", 12.5);
+        history.record("2026-10-03", "spend:EUR", 1.0);
+        history.save(&app_data_dir).expect("save");
+
+        let mut history = History::load(&app_data_dir);
+        assert_eq!(history.first_since("spend:USD", "2026-10-01"), Some(("2026-10-02", 12.5)));
+        assert_eq!(history.first_since("spend:EUR", "2026-10-04"), None);
+
+        history.prune("2026-10-01");
+        assert_eq!(history.days.keys().collect::<Vec<_>>(), vec!["2026-10-02", "2026-10-03"]);
+        let _ = std::fs::remove_dir_all(&app_data_dir);
+    }
+}
@@ -1,5 +1,16 @@
-use rquickjs::{Ctx, Exception, Function, Object};
-use std::path::PathBuf;
+use crate::plugin_engine::files;
+use crate::plugin_engine::ide;
+use crate::plugin_engine::logs::{self, ScanOpts};
+use crate::plugin_engine::ls_discovery::{self, LsDiscoverOpts};
+use crate::plugin_engine::manifest::AccountProfile;
+use crate::plugin_engine::paths::expand_path;
+use crate::plugin_engine::pricing::{self, Catalog, TokenUsage};
+use crate::plugin_engine::secrets::{self, SecretBackend};
+use crate::plugin_engine::sqlite;
+use rquickjs::{prelude::Opt, Ctx, Exception, Function, Object};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 const WHITELISTED_ENV_VARS: [&str; 1] = ["CODEX_HOME"];
 
@@ -100,8 +111,9 @@ fn redact_log_message(msg: &str) -> String {
 pub fn inject_host_api<'js>(
     ctx: &Ctx<'js>,
     plugin_id: &str,
-    app_data_dir: &PathBuf,
+    app_data_dir: &Path,
     app_version: &str,
+    account: Option<&AccountProfile>,
 ) -> rquickjs::Result<()> {
     let globals = ctx.globals();
     let probe_ctx = Object::new(ctx.clone())?;
@@ -112,7 +124,11 @@ pub fn inject_host_api<'js>(
     app_obj.set("version", app_version)?;
     app_obj.set("platform", std::env::consts::OS)?;
     app_obj.set("appDataDir", app_data_dir.to_string_lossy().to_string())?;
-    let plugin_data_dir = app_data_dir.join("plugins_data").join(plugin_id);
+    let mut plugin_data_dir = app_data_dir.join("plugins_data").join(plugin_id);
+    if let Some(account) = account {
+        // Each account gets its own namespace so cached tokens/state never mix.
+        plugin_data_dir = plugin_data_dir.join("accounts").join(&account.id);
+    }
     if let Err(err) = std::fs::create_dir_all(&plugin_data_dir) {
         log::warn!(
             "[plugin:{}] failed to create plugin data dir: {}",
@@ -126,16 +142,44 @@ pub fn inject_host_api<'js>(
     )?;
     probe_ctx.set("app", app_obj)?;
 
+    if let Some(account) = account {
+        let account_obj = Object::new(ctx.clone())?;
+        account_obj.set("id", account.id.as_str())?;
+        account_obj.set("label", account.label.as_str())?;
+        let paths_obj = Object::new(ctx.clone())?;
+        for (name, path) in &account.paths {
+            paths_obj.set(name.as_str(), path.as_str())?;
+        }
+        account_obj.set("paths", paths_obj)?;
+        probe_ctx.set("account", account_obj)?;
+    }
+
+    let env_overrides = account.map(|a| a.env.clone()).unwrap_or_default();
+    // Secrets follow the same per-account namespacing as pluginDataDir.
+    let secrets_scope = match account {
+        Some(account) => format!("{}/accounts/{}", plugin_id, account.id),
+        None => plugin_id.to_string(),
+    };
+
     let host = Object::new(ctx.clone())?;
     inject_log(ctx, &host, plugin_id)?;
-    inject_fs(ctx, &host)?;
-    inject_env(ctx, &host)?;
+    inject_fs(ctx, &host, plugin_data_dir.clone(), app_data_dir.join("locks"))?;
+    inject_env(ctx, &host, env_overrides)?;
     inject_http(ctx, &host, plugin_id)?;
     inject_keychain(ctx, &host)?;
+    inject_secrets(
+        ctx,
+        &host,
+        secrets_scope,
+        secrets::default_backend(app_data_dir),
+    )?;
     inject_sqlite(ctx, &host)?;
+    inject_ide(ctx, &host)?;
     inject_ls(ctx, &host, plugin_id)?;
+    inject_logs(ctx, &host, plugin_data_dir.clone())?;
 
     probe_ctx.set("host", host)?;
+    inject_pricing(ctx, &probe_ctx, app_data_dir)?;
     globals.set("__openusage_ctx", probe_ctx)?;
 
     Ok(())
@@ -176,7 +220,12 @@ fn inject_log<'js>(
     Ok(())
 }
 
-fn inject_fs<'js>(ctx: &Ctx<'js>, host: &Object<'js>) -> rquickjs::Result<()> {
+fn inject_fs<'js>(
+    ctx: &Ctx<'js>,
+    host: &Object<'js>,
+    plugin_data_dir: PathBuf,
+    lock_dir: PathBuf,
+) -> rquickjs::Result<()> {
     let fs_obj = Object::new(ctx.clone())?;
 
     fs_obj.set(
@@ -213,16 +262,182 @@ fn inject_fs<'js>(ctx: &Ctx<'js>, host: &Object<'js>) -> rquickjs::Result<()> {
         )?,
     )?;
 
+    fs_obj.set(
+        "stat",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>, path: String| -> rquickjs::Result<rquickjs::Value<'js>> {
+                let stat = files::stat(&expand_path(&path))
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))?;
+                to_js_value(&ctx_inner, &stat)
+            },
+        )?,
+    )?;
+
+    fs_obj.set(
+        "listDir",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>, path: String| -> rquickjs::Result<rquickjs::Value<'js>> {
+                let entries = files::list_dir(&expand_path(&path))
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))?;
+                to_js_value(&ctx_inner, &entries)
+            },
+        )?,
+    )?;
+
+    fs_obj.set(
+        "glob",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>,
+                  dir: String,
+                  pattern: String,
+                  opts: Opt<Object<'js>>|
+                  -> rquickjs::Result<Vec<String>> {
+                let limit = opt_count(&opts, "limit")?.map_or(files::MAX_GLOB_RESULTS, |n| n as usize);
+                files::glob(&expand_path(&dir), &pattern, limit)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))
+            },
+        )?,
+    )?;
+
+    fs_obj.set(
+        "readLines",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>, path: String, opts: Opt<Object<'js>>| -> rquickjs::Result<Vec<String>> {
+                let offset = opt_count(&opts, "offset")?.unwrap_or(0) as usize;
+                let limit = opt_count(&opts, "limit")?.map(|n| n as usize);
+                files::read_lines(&expand_path(&path), offset, limit)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))
+            },
+        )?,
+    )?;
+
+    fs_obj.set(
+        "readBytes",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>, path: String, opts: Opt<Object<'js>>| -> rquickjs::Result<String> {
+                let offset = opt_count(&opts, "offset")?.unwrap_or(0);
+                let length = opt_count(&opts, "length")?;
+                files::read_bytes(&expand_path(&path), offset, length)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))
+            },
+        )?,
+    )?;
+
+    fs_obj.set(
+        "remove",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'_>, path: String| -> rquickjs::Result<bool> {
+                files::remove(&plugin_data_dir, &expand_path(&path))
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))
+            },
+        )?,
+    )?;
+
+    fs_obj.set(
+        "writeTextAtomic",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'_>, path: String, content: String| -> rquickjs::Result<()> {
+                files::write_text_atomic(&expand_path(&path), &content)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))
+            },
+        )?,
+    )?;
+
+    let merge_lock_dir = lock_dir.clone();
+    fs_obj.set(
+        "withLock",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>,
+                  path: String,
+                  callback: Function<'js>,
+                  opts: Opt<Object<'js>>|
+                  -> rquickjs::Result<rquickjs::Value<'js>> {
+                let timeout = opt_count(&opts, "timeoutMs")?.unwrap_or(files::DEFAULT_LOCK_TIMEOUT_MS);
+                let _lock = files::lock(&lock_dir, &expand_path(&path), Duration::from_millis(timeout))
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))?;
+                callback.call(())
+            },
+        )?,
+    )?;
+
+    fs_obj.set(
+        "mergeJson",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>,
+                  path: String,
+                  patch: rquickjs::Value<'js>,
+                  opts: Opt<Object<'js>>|
+                  -> rquickjs::Result<rquickjs::Value<'js>> {
+                let timeout = opt_count(&opts, "timeoutMs")?.unwrap_or(files::DEFAULT_LOCK_TIMEOUT_MS);
+                let patch_json = ctx_inner
+                    .json_stringify(patch)?
+                    .map(|s| s.to_string())
+                    .transpose()?
+                    .unwrap_or_else(|| "null".to_string());
+                let patch: serde_json::Value = serde_json::from_str(&patch_json)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &format!("invalid patch: {}", e)))?;
+                let merged = files::merge_json(
+                    &merge_lock_dir,
+                    &expand_path(&path),
+                    &patch,
+                    Duration::from_millis(timeout),
+                )
+                .map_err(|e| Exception::throw_message(&ctx_inner, &e))?;
+                to_js_value(&ctx_inner, &merged)
+            },
+        )?,
+    )?;
+
     host.set("fs", fs_obj)?;
     Ok(())
 }
 
-fn inject_env<'js>(ctx: &Ctx<'js>, host: &Object<'js>) -> rquickjs::Result<()> {
+/// Non-negative integer option (`offset`, `limit`, ...) from an optional options object.
+fn opt_count<'js>(opts: &Opt<Object<'js>>, key: &str) -> rquickjs::Result<Option<u64>> {
+    let Some(opts) = opts.0.as_ref() else {
+        return Ok(None);
+    };
+    match opts.get::<_, Option<f64>>(key)? {
+        Some(n) if n.is_finite() && n >= 0.0 => Ok(Some(n as u64)),
+        Some(_) => Err(Exception::throw_message(
+            opts.ctx(),
+            &format!("{} must be a non-negative number", key),
+        )),
+        None => Ok(None),
+    }
+}
+
+fn to_js_value<'js, T: serde::Serialize>(
+    ctx: &Ctx<'js>,
+    value: &T,
+) -> rquickjs::Result<rquickjs::Value<'js>> {
+    let json = serde_json::to_string(value)
+        .map_err(|e| Exception::throw_message(ctx, &format!("serialize failed: {}", e)))?;
+    ctx.json_parse(json)
+}
+
+fn inject_env<'js>(
+    ctx: &Ctx<'js>,
+    host: &Object<'js>,
+    overrides: HashMap<String, String>,
+) -> rquickjs::Result<()> {
     let env_obj = Object::new(ctx.clone())?;
     env_obj.set(
         "get",
         Function::new(ctx.clone(), move |name: String| -> Option<String> {
-            if WHITELISTED_ENV_VARS.contains(&name.as_str()) {
+            // Account overrides are user-configured, so they bypass the allowlist.
+            if let Some(value) = overrides.get(&name) {
+                Some(value.clone())
+            } else if WHITELISTED_ENV_VARS.contains(&name.as_str()) {
                 std::env::var(&name).ok()
             } else {
                 None
@@ -397,22 +612,46 @@ pub fn inject_utils(ctx: &rquickjs::Ctx<'_>) -> rquickjs::Result<()> {
             ctx.line = {
                 text: function(opts) {
                     var line = { type: "text", label: opts.label, value: opts.value };
+                    if (opts.id) line.id = opts.id;
                     if (opts.color) line.color = opts.color;
                     if (opts.subtitle) line.subtitle = opts.subtitle;
                     return line;
                 },
                 progress: function(opts) {
                     var line = { type: "progress", label: opts.label, used: opts.used, limit: opts.limit, format: opts.format };
+                    if (opts.id) line.id = opts.id;
                     if (opts.resetsAt) line.resetsAt = opts.resetsAt;
                     if (opts.periodDurationMs) line.periodDurationMs = opts.periodDurationMs;
                     if (opts.color) line.color = opts.color;
+                    if (opts.segments) line.segments = opts.segments;
+                    if (opts.softLimit != null) line.softLimit = opts.softLimit;
+                    if (opts.hardLimit != null) line.hardLimit = opts.hardLimit;
                     return line;
                 },
                 badge: function(opts) {
                     var line = { type: "badge", label: opts.label, text: opts.text };
+                    if (opts.id) line.id = opts.id;
                     if (opts.color) line.color = opts.color;
                     if (opts.subtitle) line.subtitle = opts.subtitle;
                     return line;
+                },
+                sparkline: function(opts) {
+                    var line = { type: "sparkline", label: opts.label, points: opts.points, unit: opts.unit };
+                    if (opts.id) line.id = opts.id;
+                    if (opts.color) line.color = opts.color;
+                    if (opts.subtitle) line.subtitle = opts.subtitle;
+                    return line;
+                },
+                table: function(opts) {
+                    var line = { type: "table", label: opts.label, columns: opts.columns, rows: opts.rows };
+                    if (opts.id) line.id = opts.id;
+                    return line;
+                },
+                link: function(opts) {
+                    var line = { type: "link", label: opts.label, url: opts.url };
+                    if (opts.id) line.id = opts.id;
+                    if (opts.subtitle) line.subtitle = opts.subtitle;
+                    return line;
                 }
             };
 
@@ -690,26 +929,6 @@ struct HttpRespParams {
 
 // --- Language Server Discovery ---
 
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct LsDiscoverOpts {
-    process_name: String,
-    markers: Vec<String>,
-    csrf_flag: String,
-    port_flag: Option<String>,
-    extra_flags: Option<Vec<String>>,
-}
-
-#[derive(serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-struct LsDiscoverResult {
-    pid: i32,
-    csrf: String,
-    ports: Vec<i32>,
-    extra: std::collections::HashMap<String, String>,
-    extension_port: Option<i32>,
-}
-
 fn inject_ls<'js>(
     ctx: &Ctx<'js>,
     host: &Object<'js>,
@@ -719,7 +938,7 @@ fn inject_ls<'js>(
     let pid = plugin_id.to_string();
 
     ls_obj.set(
-        "_discoverRaw",
+        "_discoverAllRaw",
         Function::new(
             ctx.clone(),
             move |ctx_inner: Ctx<'_>, opts_json: String| -> rquickjs::Result<String> {
@@ -737,189 +956,13 @@ fn inject_ls<'js>(
                     opts.markers
                 );
 
-                let ps_output = match std::process::Command::new("/bin/ps")
-                    .args(["-ax", "-o", "pid=,command="])
-                    .output()
-                {
-                    Ok(o) => o,
-                    Err(e) => {
-                        log::warn!("[plugin:{}] ps failed: {}", pid, e);
-                        return Ok("null".to_string());
-                    }
-                };
-
-                if !ps_output.status.success() {
-                    log::warn!("[plugin:{}] ps returned non-zero", pid);
-                    return Ok("null".to_string());
-                }
-
-                let ps_stdout = String::from_utf8_lossy(&ps_output.stdout);
-                let process_name_lower = opts.process_name.to_lowercase();
-                let markers_lower: Vec<String> =
-                    opts.markers.iter().map(|m| m.to_lowercase()).collect();
-
-                // Find the target process. Marker patterns are Codeium-derived.
-                // Matching priority:
-                //   1. Exact --ide_name / --app_data_dir flag value (prevents
-                //      "windsurf" matching "windsurf-next")
-                //   2. Path substring (/<marker>/) as fallback when no flags found
-                let mut found: Option<(i32, String)> = None;
-
-                for line in ps_stdout.lines() {
-                    let trimmed = line.trim();
-                    if trimmed.is_empty() {
-                        continue;
-                    }
-
-                    let mut parts = trimmed.splitn(2, char::is_whitespace);
-                    let pid_str = match parts.next() {
-                        Some(s) => s.trim(),
-                        None => continue,
-                    };
-                    let command = match parts.next() {
-                        Some(s) => s.trim(),
-                        None => continue,
-                    };
-
-                    let command_lower = command.to_lowercase();
-
-                    if !command_lower.contains(&process_name_lower) {
-                        continue;
-                    }
-
-                    let ide_name = ls_extract_flag(command, "--ide_name")
-                        .map(|v| v.to_lowercase());
-                    let app_data = ls_extract_flag(command, "--app_data_dir")
-                        .map(|v| v.to_lowercase());
-
-                    let has_marker = markers_lower.iter().any(|m| {
-                        // Prefer exact flag match; skip path fallback when
-                        // a distinguishing flag exists.
-                        if let Some(ref name) = ide_name {
-                            return *name == *m;
-                        }
-                        if let Some(ref dir) = app_data {
-                            return *dir == *m;
-                        }
-                        // Fallback: path substring
-                        command_lower.contains(&format!("/{}/", m))
-                    });
-                    if !has_marker {
-                        continue;
-                    }
-
-                    if let Ok(p) = pid_str.parse::<i32>() {
-                        found = Some((p, command.to_string()));
-                        break;
-                    }
-                }
-
-                let (process_pid, command) = match found {
-                    Some(pair) => pair,
-                    None => {
-                        log::info!("[plugin:{}] LS process not found", pid);
-                        return Ok("null".to_string());
-                    }
-                };
-
-                // Extract CSRF token
-                let csrf = match ls_extract_flag(&command, &opts.csrf_flag) {
-                    Some(c) => c,
-                    None => {
-                        log::warn!(
-                            "[plugin:{}] CSRF token not found in process args",
-                            pid
-                        );
-                        return Ok("null".to_string());
-                    }
-                };
-
-                // Extract extension port (optional)
-                let extension_port = opts
-                    .port_flag
-                    .as_ref()
-                    .and_then(|flag| {
-                        ls_extract_flag(&command, flag)
-                            .and_then(|v| v.parse::<i32>().ok())
-                    });
-
-                // Extract extra flags (optional)
-                let mut extra = std::collections::HashMap::new();
-                if let Some(ref flags) = opts.extra_flags {
-                    for flag in flags {
-                        if let Some(val) = ls_extract_flag(&command, flag) {
-                            // Use flag name without leading dashes as key
-                            let key = flag.trim_start_matches('-').to_string();
-                            extra.insert(key, val);
-                        }
-                    }
+                let inspector = ls_discovery::native_inspector();
+                let results = ls_discovery::discover(inspector.as_ref(), &opts, &pid);
+                if results.is_empty() {
+                    log::info!("[plugin:{}] LS process not found", pid);
                 }
 
-                // Find lsof binary
-                let lsof_path = ["/usr/sbin/lsof", "/usr/bin/lsof"]
-                    .iter()
-                    .find(|p| std::path::Path::new(p).exists())
-                    .copied();
-
-                let ports = if let Some(lsof) = lsof_path {
-                    match std::process::Command::new(lsof)
-                        .args([
-                            "-nP",
-                            "-iTCP",
-                            "-sTCP:LISTEN",
-                            "-a",
-                            "-p",
-                            &process_pid.to_string(),
-                        ])
-                        .output()
-                    {
-                        Ok(o) if o.status.success() => {
-                            ls_parse_listening_ports(
-                                &String::from_utf8_lossy(&o.stdout),
-                            )
-                        }
-                        Ok(_) => {
-                            log::warn!(
-                                "[plugin:{}] lsof returned non-zero",
-                                pid
-                            );
-                            Vec::new()
-                        }
-                        Err(e) => {
-                            log::warn!("[plugin:{}] lsof failed: {}", pid, e);
-                            Vec::new()
-                        }
-                    }
-                } else {
-                    log::warn!("[plugin:{}] lsof not found", pid);
-                    Vec::new()
-                };
-
-                if ports.is_empty() && extension_port.is_none() {
-                    log::warn!(
-                        "[plugin:{}] no listening ports found for pid {}",
-                        pid,
-                        process_pid
-                    );
-                    return Ok("null".to_string());
-                }
-
-                log::info!(
-                    "[plugin:{}] LS found: pid={}, ports={:?}, csrf=[REDACTED]",
-                    pid,
-                    process_pid,
-                    ports
-                );
-
-                let result = LsDiscoverResult {
-                    pid: process_pid,
-                    csrf,
-                    ports,
-                    extra,
-                    extension_port,
-                };
-
-                serde_json::to_string(&result).map_err(|e| {
+                serde_json::to_string(&results).map_err(|e| {
                     Exception::throw_message(
                         &ctx_inner,
                         &format!("serialize failed: {}", e),
@@ -937,13 +980,16 @@ pub fn patch_ls_wrapper(ctx: &rquickjs::Ctx<'_>) -> rquickjs::Result<()> {
     ctx.eval::<(), _>(
         r#"
         (function() {
-            var rawFn = __openusage_ctx.host.ls._discoverRaw;
-            __openusage_ctx.host.ls.discover = function(opts) {
+            var rawFn = __openusage_ctx.host.ls._discoverAllRaw;
+            var discoverAll = function(opts) {
                 var optsJson;
-                try { optsJson = JSON.stringify(opts); } catch (e) { return null; }
-                var json = rawFn(optsJson);
-                if (json === "null") return null;
-                return JSON.parse(json);
+                try { optsJson = JSON.stringify(opts); } catch (e) { return []; }
+                return JSON.parse(rawFn(optsJson));
+            };
+            __openusage_ctx.host.ls.discoverAll = discoverAll;
+            __openusage_ctx.host.ls.discover = function(opts) {
+                var all = discoverAll(opts);
+                return all.length > 0 ? all[0] : null;
             };
         })();
         "#
@@ -951,45 +997,91 @@ pub fn patch_ls_wrapper(ctx: &rquickjs::Ctx<'_>) -> rquickjs::Result<()> {
     )
 }
 
-/// Extract value of a CLI flag from a command string.
-/// Handles both `--flag value` and `--flag=value` forms.
-fn ls_extract_flag(command: &str, flag: &str) -> Option<String> {
-    let parts: Vec<&str> = command.split_whitespace().collect();
-    let flag_eq = format!("{}=", flag);
-    for (i, part) in parts.iter().enumerate() {
-        if *part == flag {
-            if i + 1 < parts.len() {
-                return Some(parts[i + 1].to_string());
-            }
-        } else if part.starts_with(&flag_eq) {
-            return Some(part[flag_eq.len()..].to_string());
-        }
-    }
-    None
+/// Expose resolved plugin settings (see `settings::resolve`) as `ctx.settings`.
+pub fn inject_settings(
+    ctx: &rquickjs::Ctx<'_>,
+    settings: &serde_json::Map<String, serde_json::Value>,
+) -> rquickjs::Result<()> {
+    let probe_ctx: Object = ctx.globals().get("__openusage_ctx")?;
+    let json = serde_json::to_string(settings).unwrap_or_else(|_| "{}".to_string());
+    probe_ctx.set("settings", ctx.json_parse(json)?)?;
+    Ok(())
 }
 
-/// Parse listening port numbers from `lsof -nP -iTCP -sTCP:LISTEN` output.
-fn ls_parse_listening_ports(output: &str) -> Vec<i32> {
-    let mut ports = std::collections::BTreeSet::new();
-    for line in output.lines() {
-        if !line.contains("LISTEN") {
-            continue;
-        }
-        // lsof -nP output: ... TCP 127.0.0.1:PORT (LISTEN)  or  ... TCP *:PORT
-        // Scan tokens in reverse to find the address:port token.
-        for token in line.split_whitespace().rev() {
-            if let Some(colon_pos) = token.rfind(':') {
-                let port_str = &token[colon_pos + 1..];
-                if let Ok(port) = port_str.parse::<i32>() {
-                    if port > 0 && port < 65536 {
-                        ports.insert(port);
-                        break;
-                    }
-                }
-            }
+fn inject_secrets<'js>(
+    ctx: &Ctx<'js>,
+    host: &Object<'js>,
+    scope: String,
+    backend: std::sync::Arc<dyn SecretBackend>,
+) -> rquickjs::Result<()> {
+    let secrets_obj = Object::new(ctx.clone())?;
+
+    fn check_key(ctx: &Ctx<'_>, key: &str) -> rquickjs::Result<()> {
+        if secrets::is_valid_secret_key(key) {
+            Ok(())
+        } else {
+            Err(Exception::throw_message(
+                ctx,
+                "invalid secret key (use A-Z, a-z, 0-9, '.', '-', '_'; max 128 chars)",
+            ))
         }
     }
-    ports.into_iter().collect()
+
+    let (b, s) = (backend.clone(), scope.clone());
+    secrets_obj.set(
+        "get",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'_>, key: String| -> rquickjs::Result<Option<String>> {
+                check_key(&ctx_inner, &key)?;
+                b.get(&s, &key)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))
+            },
+        )?,
+    )?;
+
+    let (b, s) = (backend.clone(), scope.clone());
+    secrets_obj.set(
+        "set",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'_>, key: String, value: String| -> rquickjs::Result<()> {
+                check_key(&ctx_inner, &key)?;
+                b.set(&s, &key, &value)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))
+            },
+        )?,
+    )?;
+
+    let (b, s) = (backend.clone(), scope.clone());
+    secrets_obj.set(
+        "delete",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'_>, key: String| -> rquickjs::Result<bool> {
+                check_key(&ctx_inner, &key)?;
+                b.delete(&s, &key)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))
+            },
+        )?,
+    )?;
+
+    let name = backend.name();
+    secrets_obj.set(
+        "list",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'_>| -> rquickjs::Result<Vec<String>> {
+                backend
+                    .list(&scope)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))
+            },
+        )?,
+    )?;
+    secrets_obj.set("backend", name)?;
+
+    host.set("secrets", secrets_obj)?;
+    Ok(())
 }
 
 fn inject_keychain<'js>(ctx: &Ctx<'js>, host: &Object<'js>) -> rquickjs::Result<()> {
@@ -1115,6 +1207,137 @@ fn inject_keychain<'js>(ctx: &Ctx<'js>, host: &Object<'js>) -> rquickjs::Result<
     Ok(())
 }
 
+fn inject_logs<'js>(
+    ctx: &Ctx<'js>,
+    host: &Object<'js>,
+    plugin_data_dir: PathBuf,
+) -> rquickjs::Result<()> {
+    let logs_obj = Object::new(ctx.clone())?;
+    let state_path = plugin_data_dir.join(logs::STATE_FILE);
+
+    logs_obj.set(
+        "scanJsonl",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>,
+                  pattern: String,
+                  opts: Opt<rquickjs::Value<'js>>|
+                  -> rquickjs::Result<rquickjs::Value<'js>> {
+                let opts: ScanOpts = match opts.0.filter(|o| !o.is_undefined() && !o.is_null()) {
+                    Some(opts) => {
+                        let json = ctx_inner
+                            .json_stringify(opts)?
+                            .map(|s| s.to_string())
+                            .transpose()?
+                            .unwrap_or_default();
+                        serde_json::from_str(&json).map_err(|e| {
+                            Exception::throw_message(&ctx_inner, &format!("invalid scan opts: {}", e))
+                        })?
+                    }
+                    None => ScanOpts::default(),
+                };
+                let result = logs::scan_jsonl(&expand_path(&pattern), &opts, &state_path)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))?;
+                to_js_value(&ctx_inner, &result)
+            },
+        )?,
+    )?;
+
+    host.set("logs", logs_obj)?;
+    Ok(())
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CostRequest {
+    model: String,
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_read_tokens: u64,
+    #[serde(default)]
+    cache_write_tokens: u64,
+}
+
+/// The `at` argument of `price()` and `cost()` as JSON, `Null` when omitted. NaN, Infinity
+/// and invalid Dates stringify to `null` and functions to nothing, so those throw rather than
+/// silently pricing today.
+fn pricing_date<'js>(ctx: &Ctx<'js>, at: Option<rquickjs::Value<'js>>) -> rquickjs::Result<serde_json::Value> {
+    let Some(at) = at.filter(|v| !v.is_undefined() && !v.is_null()) else {
+        return Ok(serde_json::Value::Null);
+    };
+    let json = ctx.json_stringify(at)?.map(|s| s.to_string()).transpose()?;
+    match json.and_then(|json| serde_json::from_str(&json).ok()) {
+        Some(serde_json::Value::Null) | None => Err(Exception::throw_message(
+            ctx,
+            "invalid date: expected YYYY-MM-DD, ISO timestamp or unix ms",
+        )),
+        Some(at) => Ok(at),
+    }
+}
+
+fn inject_pricing<'js>(
+    ctx: &Ctx<'js>,
+    probe_ctx: &Object<'js>,
+    app_data_dir: &Path,
+) -> rquickjs::Result<()> {
+    let pricing_obj = Object::new(ctx.clone())?;
+    let catalog = std::rc::Rc::new(Catalog::load(app_data_dir));
+    pricing_obj.set("catalogVersion", catalog.version)?;
+
+    let cost_catalog = catalog.clone();
+    pricing_obj.set(
+        "cost",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>, request: rquickjs::Value<'js>| -> rquickjs::Result<Option<f64>> {
+                let at = match request.as_object() {
+                    Some(request) => pricing_date(&ctx_inner, request.get("at")?)?,
+                    None => serde_json::Value::Null,
+                };
+                let json = ctx_inner
+                    .json_stringify(request)?
+                    .map(|s| s.to_string())
+                    .transpose()?
+                    .unwrap_or_default();
+                let request: CostRequest = serde_json::from_str(&json).map_err(|e| {
+                    Exception::throw_message(&ctx_inner, &format!("invalid cost request: {}", e))
+                })?;
+                let date = pricing::date_from_value(&at)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))?;
+                let usage = TokenUsage {
+                    input: request.input_tokens,
+                    output: request.output_tokens,
+                    cache_write: request.cache_write_tokens,
+                    cache_read: request.cache_read_tokens,
+                };
+                Ok(cost_catalog.cost(&request.model, &usage, date.as_deref()))
+            },
+        )?,
+    )?;
+
+    pricing_obj.set(
+        "price",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>,
+                  model: String,
+                  at: Opt<rquickjs::Value<'js>>|
+                  -> rquickjs::Result<rquickjs::Value<'js>> {
+                let at = pricing_date(&ctx_inner, at.0)?;
+                let date = pricing::date_from_value(&at)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))?;
+                to_js_value(&ctx_inner, &catalog.price_for(&model, date.as_deref()))
+            },
+        )?,
+    )?;
+
+    probe_ctx.set("pricing", pricing_obj)?;
+    Ok(())
+}
+
 fn inject_sqlite<'js>(ctx: &Ctx<'js>, host: &Object<'js>) -> rquickjs::Result<()> {
     let sqlite_obj = Object::new(ctx.clone())?;
 
@@ -1122,42 +1345,16 @@ fn inject_sqlite<'js>(ctx: &Ctx<'js>, host: &Object<'js>) -> rquickjs::Result<()
         "query",
         Function::new(
             ctx.clone(),
-            move |ctx_inner: Ctx<'_>, db_path: String, sql: String| -> rquickjs::Result<String> {
-                if sql.lines().any(|line| line.trim_start().starts_with('.')) {
-                    return Err(Exception::throw_message(
-                        &ctx_inner,
-                        "sqlite3 dot-commands are not allowed",
-                    ));
-                }
+            move |ctx_inner: Ctx<'js>,
+                  db_path: String,
+                  sql: String,
+                  params: Opt<rquickjs::Value<'js>>|
+                  -> rquickjs::Result<String> {
+                let params = sqlite_params(&ctx_inner, params)?;
                 let expanded = expand_path(&db_path);
-                // Use immutable=1 to bypass WAL/SHM file access issues
-                // (WAL databases can fail with -readonly when shm is locked after macOS sleep)
-                // Percent-encode special chars for valid URI (% must be first!)
-                let encoded = expanded
-                    .replace('%', "%25")
-                    .replace(' ', "%20")
-                    .replace('#', "%23")
-                    .replace('?', "%3F");
-                let uri_path = format!("file:{}?immutable=1", encoded);
-                let output = std::process::Command::new("sqlite3")
-                    .args(["-readonly", "-json", &uri_path, &sql])
-                    .output()
-                    .map_err(|e| {
-                        Exception::throw_message(
-                            &ctx_inner,
-                            &format!("sqlite3 exec failed: {}", e),
-                        )
-                    })?;
-
-                if !output.status.success() {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    return Err(Exception::throw_message(
-                        &ctx_inner,
-                        &format!("sqlite3 error: {}", stderr.trim()),
-                    ));
-                }
-
-                Ok(String::from_utf8_lossy(&output.stdout).to_string())
+                let rows = sqlite::query(&expanded, &sql, params.as_ref())
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))?;
+                Ok(rows.to_string())
             },
         )?,
     )?;
@@ -1166,41 +1363,134 @@ fn inject_sqlite<'js>(ctx: &Ctx<'js>, host: &Object<'js>) -> rquickjs::Result<()
         "exec",
         Function::new(
             ctx.clone(),
-            move |ctx_inner: Ctx<'_>, db_path: String, sql: String| -> rquickjs::Result<()> {
-                if sql.lines().any(|line| line.trim_start().starts_with('.')) {
-                    return Err(Exception::throw_message(
-                        &ctx_inner,
-                        "sqlite3 dot-commands are not allowed",
-                    ));
-                }
+            move |ctx_inner: Ctx<'js>,
+                  db_path: String,
+                  sql: String,
+                  params: Opt<rquickjs::Value<'js>>|
+                  -> rquickjs::Result<usize> {
+                let params = sqlite_params(&ctx_inner, params)?;
                 let expanded = expand_path(&db_path);
-                let output = std::process::Command::new("sqlite3")
-                    .args([&expanded, &sql])
-                    .output()
-                    .map_err(|e| {
-                        Exception::throw_message(
-                            &ctx_inner,
-                            &format!("sqlite3 exec failed: {}", e),
-                        )
-                    })?;
+                sqlite::exec(&expanded, &sql, params.as_ref())
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))
+            },
+        )?,
+    )?;
 
-                if !output.status.success() {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    return Err(Exception::throw_message(
-                        &ctx_inner,
-                        &format!("sqlite3 error: {}", stderr.trim()),
-                    ));
-                }
+    host.set("sqlite", sqlite_obj)?;
+    Ok(())
+}
 
-                Ok(())
+#[derive(Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IdeStateDbOpts {
+    variant: Option<String>,
+    portable_dir: Option<String>,
+}
+
+fn inject_ide<'js>(ctx: &Ctx<'js>, host: &Object<'js>) -> rquickjs::Result<()> {
+    let ide_obj = Object::new(ctx.clone())?;
+
+    ide_obj.set(
+        "stateDbPath",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>,
+                  name: String,
+                  opts: Opt<rquickjs::Value<'js>>|
+                  -> rquickjs::Result<Option<String>> {
+                let opts: IdeStateDbOpts = match opts.0.filter(|o| !o.is_undefined() && !o.is_null()) {
+                    Some(opts) => {
+                        let json = ctx_inner
+                            .json_stringify(opts)?
+                            .map(|s| s.to_string())
+                            .transpose()?
+                            .unwrap_or_default();
+                        serde_json::from_str(&json).map_err(|e| {
+                            Exception::throw_message(&ctx_inner, &format!("invalid ide opts: {}", e))
+                        })?
+                    }
+                    None => IdeStateDbOpts::default(),
+                };
+                let variant = match opts.variant.as_deref() {
+                    Some(variant) => ide::Variant::parse(variant)
+                        .map_err(|e| Exception::throw_message(&ctx_inner, &e))?,
+                    None => ide::Variant::Stable,
+                };
+                let portable = opts.portable_dir.map(|dir| PathBuf::from(expand_path(&dir)));
+                Ok(ide::resolve_state_db(&name, variant, portable.as_deref())
+                    .map(|path| path.to_string_lossy().to_string()))
             },
         )?,
     )?;
 
-    host.set("sqlite", sqlite_obj)?;
+    ide_obj.set(
+        "getItem",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>, db_path: String, key: String| -> rquickjs::Result<Option<String>> {
+                ide::get_item(&expand_path(&db_path), &key)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))
+            },
+        )?,
+    )?;
+
+    ide_obj.set(
+        "getJson",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>, db_path: String, key: String| -> rquickjs::Result<rquickjs::Value<'js>> {
+                let value = ide::get_json(&expand_path(&db_path), &key)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))?;
+                ctx_inner.json_parse(value.unwrap_or_default().to_string())
+            },
+        )?,
+    )?;
+
+    ide_obj.set(
+        "getProto",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>, db_path: String, key: String| -> rquickjs::Result<rquickjs::Value<'js>> {
+                let value = ide::get_proto(&expand_path(&db_path), &key)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))?;
+                ctx_inner.json_parse(value.unwrap_or_default().to_string())
+            },
+        )?,
+    )?;
+
+    ide_obj.set(
+        "setItem",
+        Function::new(
+            ctx.clone(),
+            move |ctx_inner: Ctx<'js>, db_path: String, key: String, value: String| -> rquickjs::Result<()> {
+                ide::set_item(&expand_path(&db_path), &key, &value)
+                    .map_err(|e| Exception::throw_message(&ctx_inner, &e))
+            },
+        )?,
+    )?;
+
+    host.set("ide", ide_obj)?;
     Ok(())
 }
 
+/// Convert the optional JS `params` argument (array or object) to JSON for binding.
+fn sqlite_params<'js>(
+    ctx: &Ctx<'js>,
+    params: Opt<rquickjs::Value<'js>>,
+) -> rquickjs::Result<Option<serde_json::Value>> {
+    let Some(params) = params.0.filter(|p| !p.is_undefined() && !p.is_null()) else {
+        return Ok(None);
+    };
+    let json = ctx
+        .json_stringify(params)?
+        .map(|s| s.to_string())
+        .transpose()?
+        .unwrap_or_default();
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|_| Exception::throw_message(ctx, "sqlite params must be JSON-serializable"))
+}
+
 fn iso_now() -> String {
     time::OffsetDateTime::now_utc()
         .format(&time::format_description::well_known::Rfc3339)
@@ -1210,23 +1500,10 @@ fn iso_now() -> String {
         })
 }
 
-fn expand_path(path: &str) -> String {
-    if path == "~" {
-        if let Some(home) = dirs::home_dir() {
-            return home.to_string_lossy().to_string();
-        }
-    }
-    if path.starts_with("~/") {
-        if let Some(home) = dirs::home_dir() {
-            return home.join(&path[2..]).to_string_lossy().to_string();
-        }
-    }
-    path.to_string()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::plugin_engine::test_dir;
     use rquickjs::{Context, Function, Object, Runtime};
 
     #[test]
@@ -1235,7 +1512,7 @@ mod tests {
         let ctx = Context::full(&rt).expect("context");
         ctx.with(|ctx| {
             let app_data = std::env::temp_dir();
-            inject_host_api(&ctx, "test", &app_data, "0.0.0").expect("inject host api");
+            inject_host_api(&ctx, "test", &app_data, "0.0.0", None).expect("inject host api");
             let globals = ctx.globals();
             let probe_ctx: Object = globals.get("__openusage_ctx").expect("probe ctx");
             let host: Object = probe_ctx.get("host").expect("host");
@@ -1255,7 +1532,7 @@ mod tests {
         let ctx = Context::full(&rt).expect("context");
         ctx.with(|ctx| {
             let app_data = std::env::temp_dir();
-            inject_host_api(&ctx, "test", &app_data, "0.0.0").expect("inject host api");
+            inject_host_api(&ctx, "test", &app_data, "0.0.0", None).expect("inject host api");
             let globals = ctx.globals();
             let probe_ctx: Object = globals.get("__openusage_ctx").expect("probe ctx");
             let host: Object = probe_ctx.get("host").expect("host");
@@ -1364,4 +1641,324 @@ mod tests {
         // "Robin Ebers" is 11 chars (<=12) so becomes [REDACTED]
         assert!(redacted.contains("\"name\": \"[REDACTED]\""), "name should show [REDACTED], got: {}", redacted);
     }
+
+    #[test]
+    fn account_scopes_env_and_plugin_data_dir() {
+        let rt = Runtime::new().expect("runtime");
+        let ctx = Context::full(&rt).expect("context");
+        ctx.with(|ctx| {
+            let app_data = std::env::temp_dir().join("openusage-account-test");
+            let account = AccountProfile {
+                id: "work".to_string(),
+                label: "Work".to_string(),
+                env: HashMap::from([("CLAUDE_CONFIG_DIR".to_string(), "~/.claude-work".to_string())]),
+                paths: HashMap::from([("credentials".to_string(), "~/.claude-work/.credentials.json".to_string())]),
+            };
+            inject_host_api(&ctx, "test", &app_data, "0.0.0", Some(&account)).expect("inject host api");
+
+            let env_value: Option<String> = ctx
+                .eval(r#"__openusage_ctx.host.env.get("CLAUDE_CONFIG_DIR")"#)
+                .expect("env get");
+            assert_eq!(env_value.as_deref(), Some("~/.claude-work"));
+
+            let label: String = ctx.eval("__openusage_ctx.account.label").expect("account label");
+            assert_eq!(label, "Work");
+            let credentials: String = ctx
+                .eval("__openusage_ctx.account.paths.credentials")
+                .expect("account path");
+            assert_eq!(credentials, "~/.claude-work/.credentials.json");
+
+            let data_dir: String = ctx.eval("__openusage_ctx.app.pluginDataDir").expect("data dir");
+            let expected = app_data.join("plugins_data").join("test").join("accounts").join("work");
+            assert_eq!(data_dir, expected.to_string_lossy());
+        });
+    }
+
+    #[test]
+    fn secrets_api_is_scoped_to_plugin() {
+        let rt = Runtime::new().expect("runtime");
+        let ctx = Context::full(&rt).expect("context");
+        ctx.with(|ctx| {
+            let dir = test_dir("secrets-api");
+            let backend: std::sync::Arc<dyn SecretBackend> =
+                std::sync::Arc::new(secrets::EncryptedFileBackend::new(dir.clone()));
+            backend.set("other", "token", "not-yours").expect("seed other scope");
+
+            let host = Object::new(ctx.clone()).expect("host");
+            inject_secrets(&ctx, &host, "test".to_string(), backend).expect("inject secrets");
+            ctx.globals().set("host", host).expect("set host");
+
+            let result: String = ctx
+                .eval(
+                    r#"
+                    host.secrets.set("token", "abc");
+                    [
+                        host.secrets.get("token"),
+                        String(host.secrets.get("missing")),
+                        host.secrets.list().join(","),
+                        String(host.secrets.delete("token")),
+                        String(host.secrets.delete("token")),
+                    ].join("|")
+                    "#,
+                )
+                .expect("secrets calls");
+            assert_eq!(result, "abc|undefined|token|true|false");
+
+            let invalid: String = ctx
+                .eval(r#"try { host.secrets.get("../x"); "ok" } catch (e) { String(e) }"#)
+                .expect("invalid key");
+            assert!(invalid.contains("invalid secret key"), "{}", invalid);
+            let _ = std::fs::remove_dir_all(dir);
+        });
+    }
+
+    #[test]
+    fn sqlite_api_binds_js_params() {
+        let rt = Runtime::new().expect("runtime");
+        let ctx = Context::full(&rt).expect("context");
+        ctx.with(|ctx| {
+            let dir = test_dir("sqlite-api");
+            let db = dir.join("state.db");
+            rusqlite::Connection::open(&db)
+                .and_then(|conn| conn.execute_batch("CREATE TABLE ItemTable (key TEXT, value TEXT);"))
+                .expect("create db");
+
+            let host = Object::new(ctx.clone()).expect("host");
+            inject_sqlite(&ctx, &host).expect("inject sqlite");
+            ctx.globals().set("host", host).expect("set host");
+            ctx.globals()
+                .set("dbPath", db.to_string_lossy().to_string())
+                .expect("set db path");
+
+            let value: String = ctx
+                .eval(
+                    r#"
+                    host.sqlite.exec(dbPath, "INSERT INTO ItemTable VALUES (:key, :value)", { key: "k", value: "it's" });
+                    JSON.parse(host.sqlite.query(dbPath, "SELECT value FROM ItemTable WHERE key = ?", ["k"]))[0].value
+                    "#,
+                )
+                .expect("sqlite calls");
+            assert_eq!(value, "it's");
+
+            let err: String = ctx
+                .eval(r#"try { host.sqlite.query(dbPath, "SELECT ?"); "ok" } catch (e) { String(e) }"#)
+                .expect("missing param");
+            assert!(err.contains("expects 1 parameter"), "{}", err);
+            let _ = std::fs::remove_dir_all(&dir);
+        });
+    }
+
+    #[test]
+    fn ide_api_returns_parsed_items() {
+        let rt = Runtime::new().expect("runtime");
+        let ctx = Context::full(&rt).expect("context");
+        ctx.with(|ctx| {
+            let dir = test_dir("ide-api");
+            let db = dir.join("state.vscdb");
+            rusqlite::Connection::open(&db)
+                .and_then(|conn| conn.execute_batch("CREATE TABLE ItemTable (key TEXT UNIQUE ON CONFLICT REPLACE, value BLOB);"))
+                .expect("create db");
+
+            let host = Object::new(ctx.clone()).expect("host");
+            inject_ide(&ctx, &host).expect("inject ide");
+            ctx.globals().set("host", host).expect("set host");
+            ctx.globals()
+                .set("dbPath", db.to_string_lossy().to_string())
+                .expect("set db path");
+
+            let result: String = ctx
+                .eval(
+                    r#"
+                    host.ide.setItem(dbPath, "windsurfAuthStatus", JSON.stringify({ apiKey: "sk-1" }));
+                    [
+                        host.ide.getJson(dbPath, "windsurfAuthStatus").apiKey,
+                        host.ide.getJson(dbPath, "missing"),
+                        host.ide.getItem(dbPath, "missing"),
+                    ].join("|")
+                    "#,
+                )
+                .expect("ide calls");
+            assert_eq!(result, "sk-1||");
+
+            let err: String = ctx
+                .eval(r#"try { host.ide.stateDbPath("Code", { variant: "beta" }); "ok" } catch (e) { String(e) }"#)
+                .expect("bad variant");
+            assert!(err.contains("unknown IDE variant"), "{}", err);
+            let _ = std::fs::remove_dir_all(&dir);
+        });
+    }
+
+    #[test]
+    fn fs_api_reads_ranges_and_removes_only_plugin_data() {
+        let rt = Runtime::new().expect("runtime");
+        let ctx = Context::full(&rt).expect("context");
+        ctx.with(|ctx| {
+            let root = test_dir("fs-api");
+            let data_dir = root.join("plugin");
+            std::fs::create_dir_all(data_dir.join("cache")).expect("create data dir");
+            std::fs::write(root.join("log.jsonl"), "a\nb\nc\n").expect("write log");
+
+            let host = Object::new(ctx.clone()).expect("host");
+            inject_fs(&ctx, &host, data_dir.clone(), root.join("locks")).expect("inject fs");
+            ctx.globals().set("host", host).expect("set host");
+            ctx.globals()
+                .set("root", root.to_string_lossy().to_string())
+                .expect("set root");
+
+            let result: String = ctx
+                .eval(
+                    r#"
+                    [
+                        host.fs.readLines(root + "/log.jsonl", { offset: 1, limit: 1 }).join(","),
+                        host.fs.readBytes(root + "/log.jsonl", { length: 1 }),
+                        host.fs.stat(root + "/log.jsonl").kind,
+                        host.fs.stat(root + "/missing"),
+                        host.fs.listDir(root).map(function (e) { return e.name + ":" + e.kind }).join(","),
+                        host.fs.glob(root, "*.jsonl").length,
+                        host.fs.remove(root + "/plugin/cache"),
+                    ].join("|")
+                    "#,
+                )
+                .expect("fs calls");
+            assert_eq!(result, "b|YQ==|file||log.jsonl:file,plugin:dir|1|true");
+
+            let err: String = ctx
+                .eval(r#"try { host.fs.remove(root + "/log.jsonl"); "ok" } catch (e) { String(e) }"#)
+                .expect("remove outside");
+            assert!(err.contains("plugin data directory"), "{}", err);
+            assert!(root.join("log.jsonl").exists());
+            let _ = std::fs::remove_dir_all(&root);
+        });
+    }
+
+    #[test]
+    fn fs_api_merges_json_under_lock() {
+        let rt = Runtime::new().expect("runtime");
+        let ctx = Context::full(&rt).expect("context");
+        ctx.with(|ctx| {
+            let root = test_dir("fs-merge");
+            std::fs::write(root.join("auth.json"), r#"{"tokens":{"access_token":"old","id_token":"id"}}"#)
+                .expect("write auth");
+
+            let host = Object::new(ctx.clone()).expect("host");
+            inject_fs(&ctx, &host, root.join("plugin"), root.join("locks")).expect("inject fs");
+            ctx.globals().set("host", host).expect("set host");
+            ctx.globals()
+                .set("root", root.to_string_lossy().to_string())
+                .expect("set root");
+
+            let result: String = ctx
+                .eval(
+                    r#"
+                    var path = root + "/auth.json";
+                    var merged = host.fs.withLock(path, function () {
+                        return host.fs.mergeJson(path, { tokens: { access_token: "new" } });
+                    }, { timeoutMs: 100 });
+                    host.fs.writeTextAtomic(root + "/note.txt", "hi");
+                    [merged.tokens.access_token, merged.tokens.id_token, host.fs.readText(root + "/note.txt")].join("|")
+                    "#,
+                )
+                .expect("merge calls");
+            assert_eq!(result, "new|id|hi");
+            assert_eq!(
+                std::fs::read_to_string(root.join("auth.json")).unwrap(),
+                r#"{"tokens":{"access_token":"new","id_token":"id"}}"#
+            );
+            assert!(!root.join("auth.json.lock").exists());
+            let _ = std::fs::remove_dir_all(&root);
+        });
+    }
+
+    #[test]
+    fn logs_api_scans_incrementally() {
+        let rt = Runtime::new().expect("runtime");
+        let ctx = Context::full(&rt).expect("context");
+        ctx.with(|ctx| {
+            let root = test_dir("logs-api");
+            std::fs::create_dir_all(root.join("projects")).expect("create dir");
+            std::fs::write(
+                root.join("projects/s.jsonl"),
+                "{\"message\":{\"model\":\"m\",\"id\":\"x\"},\"timestamp\":\"2026-01-01T00:00:00Z\"}\n",
+            )
+            .expect("write log");
+
+            let host = Object::new(ctx.clone()).expect("host");
+            inject_logs(&ctx, &host, root.join("plugin")).expect("inject logs");
+            ctx.globals().set("host", host).expect("set host");
+            ctx.globals()
+                .set("root", root.to_string_lossy().to_string())
+                .expect("set root");
+
+            let result: String = ctx
+                .eval(
+                    r#"
+                    var pattern = root + "/projects/**/*.jsonl";
+                    var first = host.logs.scanJsonl(pattern, { fields: ["message.model"] });
+                    var second = host.logs.scanJsonl(pattern, { fields: ["message.model"] });
+                    [first.records[0].message.model, first.records[0].message.id, second.records.length].join("|")
+                    "#,
+                )
+                .expect("scan");
+            assert_eq!(result, "m||0");
+            assert!(root.join("plugin").join(logs::STATE_FILE).exists());
+
+            let err: String = ctx
+                .eval(r#"try { host.logs.scanJsonl(root + "/*", { since: "soon" }); "ok" } catch (e) { String(e) }"#)
+                .expect("bad since");
+            assert!(err.contains("invalid since"), "{}", err);
+            let _ = std::fs::remove_dir_all(&root);
+        });
+    }
+
+    #[test]
+    fn pricing_api_costs_tokens_with_overrides() {
+        let rt = Runtime::new().expect("runtime");
+        let ctx = Context::full(&rt).expect("context");
+        ctx.with(|ctx| {
+            let app_data_dir = test_dir("pricing-api");
+            std::fs::write(
+                app_data_dir.join(pricing::OVERRIDES_FILE),
+                r#"{ "models": [{ "match": "team-model", "prices": [{ "input": 2, "output": 4 }] }] }"#,
+            )
+            .expect("write overrides");
+
+            let probe_ctx = Object::new(ctx.clone()).expect("ctx");
+            inject_pricing(&ctx, &probe_ctx, &app_data_dir).expect("inject pricing");
+            ctx.globals().set("ctx", probe_ctx).expect("set ctx");
+
+            let result: String = ctx
+                .eval(
+                    r#"
+                    [
+                        ctx.pricing.cost({ model: "claude-sonnet-4-5", inputTokens: 1000000, cacheReadTokens: 1000000 }),
+                        ctx.pricing.cost({ model: "o3", outputTokens: 1000000, at: "2025-05-01" }),
+                        ctx.pricing.cost({ model: "team-model-v2", inputTokens: 500000 }),
+                        ctx.pricing.cost({ model: "unknown" }),
+                        ctx.pricing.price("team-model").source,
+                        typeof ctx.pricing.catalogVersion,
+                    ].join("|")
+                    "#,
+                )
+                .expect("pricing calls");
+            assert_eq!(result, "3.3|40|1||override|number");
+
+            for bad in ["\"March\"", "NaN", "new Date(\"nope\")", "() => 1"] {
+                let threw = ctx
+                    .eval::<bool, _>(format!(
+                        "try {{ ctx.pricing.price(\"o3\", {}); false }} catch (e) {{ true }}",
+                        bad
+                    ))
+                    .expect("price call");
+                assert!(threw, "price() should throw for at = {}", bad);
+                let threw = ctx
+                    .eval::<bool, _>(format!(
+                        "try {{ ctx.pricing.cost({{ model: \"o3\", at: {} }}); false }} catch (e) {{ true }}",
+                        bad
+                    ))
+                    .expect("cost call");
+                assert!(threw, "cost() should throw for at = {}", bad);
+            }
+            let _ = std::fs::remove_dir_all(&app_data_dir);
+        });
+    }
 }
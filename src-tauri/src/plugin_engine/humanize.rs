@@ -0,0 +1,231 @@
+//! Compact, locale-aware labels for the typed progress formats (tokens, requests, duration,
+//! bytes). Filled in after each probe so the panel and tray show the same text for every
+//! provider instead of each plugin formatting its own numbers.
+
+use crate::plugin_engine::runtime::{DurationUnit, MetricLine, PluginOutput, ProgressFormat};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+const COMPACT_SUFFIXES: [&str; 5] = ["", "K", "M", "B", "T"];
+const BYTE_UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+/// Humanized amounts of a progress line, e.g. `1.2M tokens`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressText {
+    pub used: String,
+    pub limit: String,
+    /// What is left of the limit; never negative.
+    pub left: String,
+    /// Both amounts with the unit once, e.g. `1.2M / 5M tokens`.
+    pub summary: String,
+}
+
+/// Number conventions of a BCP 47 locale tag. Only the decimal separator matters because
+/// compact values never need digit grouping.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberStyle {
+    pub decimal: char,
+}
+
+impl Default for NumberStyle {
+    fn default() -> Self {
+        NumberStyle { decimal: '.' }
+    }
+}
+
+impl NumberStyle {
+    /// Unknown or empty tags use the `en-US` style.
+    pub fn for_locale(tag: &str) -> NumberStyle {
+        let tag = tag.trim().replace('_', "-").to_ascii_lowercase();
+        let language = tag.split('-').next().unwrap_or_default();
+        let comma = matches!(
+            language,
+            "bg" | "cs" | "da" | "de" | "el" | "es" | "fi" | "fr" | "hr" | "hu" | "id" | "it"
+                | "lt" | "lv" | "nb" | "nl" | "nn" | "no" | "pl" | "pt" | "ro" | "ru" | "sk"
+                | "sl" | "sr" | "sv" | "tr" | "uk" | "vi"
+        );
+        // Swiss German and Italian keep the point.
+        if !comma || tag == "de-ch" || tag == "it-ch" {
+            return NumberStyle::default();
+        }
+        NumberStyle { decimal: ',' }
+    }
+
+    fn decimal(&self, value: f64, decimals: usize) -> String {
+        let text = format!("{:.*}", decimals, value);
+        if self.decimal == '.' {
+            text
+        } else {
+            text.replace('.', &self.decimal.to_string())
+        }
+    }
+}
+
+/// `950`, `1.2K`, `12K`, `3.4M`: one decimal below ten of a unit, none above.
+pub fn compact(value: f64, style: &NumberStyle) -> String {
+    let sign = if value < 0.0 { "-" } else { "" };
+    let mut magnitude = value.abs();
+    let mut index = 0;
+    while magnitude >= 1000.0 && index < COMPACT_SUFFIXES.len() - 1 {
+        magnitude /= 1000.0;
+        index += 1;
+    }
+    let decimals = if magnitude < 10.0 && (index > 0 || magnitude.fract() != 0.0) { 1 } else { 0 };
+    let mut rounded = round_to(magnitude, decimals);
+    // 999.96K rounds to 1000K; show it as 1M instead.
+    if rounded >= 1000.0 && index < COMPACT_SUFFIXES.len() - 1 {
+        rounded /= 1000.0;
+        index += 1;
+    }
+    let decimals = if rounded.fract() == 0.0 { 0 } else { decimals };
+    format!("{}{}{}", sign, style.decimal(rounded, decimals), COMPACT_SUFFIXES[index])
+}
+
+fn round_to(value: f64, decimals: usize) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    (value * factor).round() / factor
+}
+
+fn counted(value: f64, style: &NumberStyle, singular: &str, plural: &str) -> String {
+    let noun = if value == 1.0 { singular } else { plural };
+    format!("{} {}", compact(value, style), noun)
+}
+
+/// Largest two units, like the reset countdown: `3d 4h`, `2h 10m`, `45m`, `30s`. Locale
+/// independent: whole units need no decimal separator, and the `d`/`h`/`m`/`s` suffixes
+/// match the countdown the panel shows in every locale.
+pub fn duration(seconds: f64) -> String {
+    let total_seconds = seconds.max(0.0).round() as i64;
+    let total_minutes = total_seconds / 60;
+    let total_hours = total_minutes / 60;
+    let days = total_hours / 24;
+    if days > 0 {
+        format!("{}d {}h", days, total_hours % 24)
+    } else if total_hours > 0 {
+        format!("{}h {}m", total_hours, total_minutes % 60)
+    } else if total_minutes > 0 {
+        format!("{}m", total_minutes)
+    } else {
+        format!("{}s", total_seconds)
+    }
+}
+
+/// Binary multiples with the familiar names, e.g. `1.5 GB` for 1.5 × 1024³ bytes.
+pub fn bytes(value: f64, style: &NumberStyle) -> String {
+    let mut magnitude = value.max(0.0);
+    let mut index = 0;
+    while magnitude >= 1024.0 && index < BYTE_UNITS.len() - 1 {
+        magnitude /= 1024.0;
+        index += 1;
+    }
+    let decimals = if index > 0 && magnitude < 100.0 { 1 } else { 0 };
+    let rounded = round_to(magnitude, decimals);
+    let decimals = if rounded.fract() == 0.0 { 0 } else { decimals };
+    format!("{} {}", style.decimal(rounded, decimals), BYTE_UNITS[index])
+}
+
+fn seconds_per(unit: DurationUnit) -> f64 {
+    match unit {
+        DurationUnit::Seconds => 1.0,
+        DurationUnit::Minutes => 60.0,
+        DurationUnit::Hours => 3600.0,
+    }
+}
+
+/// Label of one amount in a typed format; `None` for the formats the UI formats itself
+/// (percent, money and free-form counts).
+pub fn amount(value: f64, format: &ProgressFormat, style: &NumberStyle) -> Option<String> {
+    match format {
+        ProgressFormat::Tokens => Some(counted(value, style, "token", "tokens")),
+        ProgressFormat::Requests => Some(counted(value, style, "request", "requests")),
+        ProgressFormat::Duration { unit } => Some(duration(value * seconds_per(*unit))),
+        ProgressFormat::Bytes => Some(bytes(value, style)),
+        _ => None,
+    }
+}
+
+/// `used / limit` with a counted noun written once.
+pub fn summary(used: f64, limit: f64, format: &ProgressFormat, style: &NumberStyle) -> Option<String> {
+    let noun = match format {
+        ProgressFormat::Tokens => "tokens",
+        ProgressFormat::Requests => "requests",
+        _ => {
+            let used = amount(used, format, style)?;
+            return Some(format!("{} / {}", used, amount(limit, format, style)?));
+        }
+    };
+    Some(format!("{} / {} {}", compact(used, style), compact(limit, style), noun))
+}
+
+/// Sets `humanized` on every progress line in a typed format, in the style of `locale`.
+pub fn humanize_outputs(outputs: &mut [PluginOutput], locale: &str) {
+    let style = NumberStyle::for_locale(locale);
+    for line in outputs.iter_mut().flat_map(PluginOutput::all_lines_mut) {
+        let MetricLine::Progress {
+            used,
+            limit,
+            format,
+            humanized,
+            ..
+        } = line
+        else {
+            continue;
+        };
+        *humanized = match (
+            amount(*used, format, &style),
+            amount(*limit, format, &style),
+            amount((*limit - *used).max(0.0), format, &style),
+            summary(*used, *limit, format, &style),
+        ) {
+            (Some(used), Some(limit), Some(left), Some(summary)) => Some(Box::new(ProgressText {
+                used,
+                limit,
+                left,
+                summary,
+            })),
+            _ => None,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_numbers_follow_the_locale() {
+        let en = NumberStyle::for_locale("en-US");
+        let de = NumberStyle::for_locale("de_DE");
+        assert_eq!(compact(950.0, &en), "950");
+        assert_eq!(compact(2.5, &en), "2.5");
+        assert_eq!(compact(12.5, &en), "13");
+        assert_eq!(compact(1234.0, &en), "1.2K");
+        assert_eq!(compact(1234.0, &de), "1,2K");
+        assert_eq!(compact(12_345.0, &en), "12K");
+        assert_eq!(compact(999_960.0, &en), "1M");
+        assert_eq!(compact(8_429_301.0, &en), "8.4M");
+        assert_eq!(compact(2_000_000_000.0, &en), "2B");
+        assert_eq!(NumberStyle::for_locale("de-CH"), en);
+        assert_eq!(NumberStyle::for_locale(""), en);
+    }
+
+    #[test]
+    fn typed_formats_are_humanized() {
+        let en = NumberStyle::default();
+        let minutes = ProgressFormat::Duration { unit: DurationUnit::Minutes };
+        assert_eq!(amount(1.0, &ProgressFormat::Tokens, &en).unwrap(), "1 token");
+        assert_eq!(amount(1_200_000.0, &ProgressFormat::Tokens, &en).unwrap(), "1.2M tokens");
+        assert_eq!(amount(340.0, &ProgressFormat::Requests, &en).unwrap(), "340 requests");
+        assert_eq!(amount(130.0, &minutes, &en).unwrap(), "2h 10m");
+        assert_eq!(amount(0.5, &minutes, &en).unwrap(), "30s");
+        assert_eq!(amount(1.5 * 1024.0 * 1024.0 * 1024.0, &ProgressFormat::Bytes, &en).unwrap(), "1.5 GB");
+        assert_eq!(amount(512.0, &ProgressFormat::Bytes, &en).unwrap(), "512 B");
+        assert_eq!(amount(42.0, &ProgressFormat::Percent, &en), None);
+        assert_eq!(
+            summary(1_200_000.0, 5_000_000.0, &ProgressFormat::Tokens, &en).unwrap(),
+            "1.2M / 5M tokens"
+        );
+        assert_eq!(summary(90.0, 300.0, &minutes, &en).unwrap(), "1h 30m / 5h 0m");
+    }
+}
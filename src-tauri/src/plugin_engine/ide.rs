@@ -0,0 +1,280 @@
+use crate::plugin_engine::sqlite;
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine,
+};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Release channel of a VS Code fork; selects the user data folder name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Variant {
+    #[default]
+    Stable,
+    Insiders,
+    Next,
+}
+
+impl Variant {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "stable" => Ok(Self::Stable),
+            "insiders" => Ok(Self::Insiders),
+            "next" => Ok(Self::Next),
+            other => Err(format!(
+                "unknown IDE variant: {} (expected stable, insiders or next)",
+                other
+            )),
+        }
+    }
+
+    fn folder_suffix(self) -> &'static str {
+        match self {
+            Self::Stable => "",
+            Self::Insiders => " - Insiders",
+            Self::Next => " - Next",
+        }
+    }
+}
+
+/// Candidate `state.vscdb` paths for a fork, most specific first. `name` is the user data
+/// folder of the stable build ("Code", "Cursor", "Windsurf"); `config_dir` is the OS config
+/// root (`~/Library/Application Support`, `%APPDATA%`, `$XDG_CONFIG_HOME` or `~/.config`).
+/// Portable installs keep user data in `<portable>/user-data`.
+pub fn state_db_candidates(
+    name: &str,
+    variant: Variant,
+    config_dir: Option<&Path>,
+    portable_dir: Option<&Path>,
+) -> Vec<PathBuf> {
+    let global_storage = |user_data: PathBuf| {
+        user_data
+            .join("User")
+            .join("globalStorage")
+            .join("state.vscdb")
+    };
+    let mut candidates = Vec::new();
+    if let Some(portable) = portable_dir {
+        candidates.push(global_storage(portable.join("user-data")));
+    }
+    if let Some(config) = config_dir {
+        let folder = format!("{}{}", name, variant.folder_suffix());
+        candidates.push(global_storage(config.join(folder)));
+    }
+    candidates
+}
+
+/// First existing `state.vscdb` for the fork on this machine. `VSCODE_PORTABLE` is used
+/// when no portable dir is given.
+pub fn resolve_state_db(name: &str, variant: Variant, portable_dir: Option<&Path>) -> Option<PathBuf> {
+    let env_portable = std::env::var_os("VSCODE_PORTABLE").map(PathBuf::from);
+    let portable = portable_dir.or(env_portable.as_deref());
+    state_db_candidates(name, variant, dirs::config_dir().as_deref(), portable)
+        .into_iter()
+        .find(|path| path.is_file())
+}
+
+/// Raw `ItemTable` value as text (blob values are read as UTF-8), or `None` if the key is absent.
+pub fn get_item(db_path: &str, key: &str) -> Result<Option<String>, String> {
+    let rows = sqlite::query(
+        db_path,
+        "SELECT CAST(value AS TEXT) AS value FROM ItemTable WHERE key = ? LIMIT 1",
+        Some(&Value::Array(vec![Value::String(key.to_string())])),
+    )?;
+    Ok(rows
+        .get(0)
+        .and_then(|row| row.get("value"))
+        .and_then(Value::as_str)
+        .map(str::to_string))
+}
+
+/// `ItemTable` value parsed as JSON.
+pub fn get_json(db_path: &str, key: &str) -> Result<Option<Value>, String> {
+    match get_item(db_path, key)? {
+        Some(text) => serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| format!("{} is not valid JSON: {}", key, e)),
+        None => Ok(None),
+    }
+}
+
+/// `ItemTable` value holding a base64-encoded protobuf message, decoded without a schema.
+pub fn get_proto(db_path: &str, key: &str) -> Result<Option<Value>, String> {
+    let Some(text) = get_item(db_path, key)? else {
+        return Ok(None);
+    };
+    let text = text.trim().trim_matches('"');
+    let bytes = STANDARD
+        .decode(text)
+        .or_else(|_| URL_SAFE_NO_PAD.decode(text.trim_end_matches('=')))
+        .map_err(|e| format!("{} is not valid base64: {}", key, e))?;
+    decode_protobuf(&bytes)
+        .map(Some)
+        .map_err(|e| format!("{} is not a protobuf message: {}", key, e))
+}
+
+/// Insert or replace an `ItemTable` value.
+pub fn set_item(db_path: &str, key: &str, value: &str) -> Result<(), String> {
+    sqlite::exec(
+        db_path,
+        "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?, ?)",
+        Some(&Value::Array(vec![
+            Value::String(key.to_string()),
+            Value::String(value.to_string()),
+        ])),
+    )
+    .map(|_| ())
+}
+
+/// Decode protobuf wire format into an object keyed by field number. Repeated fields become
+/// arrays. Length-delimited fields are decoded as text when printable UTF-8, otherwise as a
+/// nested message when they parse as one, otherwise as base64. Varints and fixed integers
+/// above 2^53 are returned as strings to keep their precision.
+pub fn decode_protobuf(bytes: &[u8]) -> Result<Value, String> {
+    let mut fields = Map::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let tag = read_varint(bytes, &mut pos)?;
+        let field = tag >> 3;
+        if field == 0 {
+            return Err("field number 0".to_string());
+        }
+        let value = match tag & 0x7 {
+            0 => integer(read_varint(bytes, &mut pos)?),
+            1 => integer(u64::from_le_bytes(take(bytes, &mut pos, 8)?.try_into().unwrap())),
+            2 => {
+                let len = read_varint(bytes, &mut pos)? as usize;
+                length_delimited(take(bytes, &mut pos, len)?)
+            }
+            5 => Value::from(u32::from_le_bytes(take(bytes, &mut pos, 4)?.try_into().unwrap())),
+            wire => return Err(format!("unsupported wire type {}", wire)),
+        };
+        match fields.get_mut(&field.to_string()) {
+            Some(Value::Array(items)) => items.push(value),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+            None => {
+                fields.insert(field.to_string(), value);
+            }
+        }
+    }
+    Ok(Value::Object(fields))
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64, String> {
+    let mut result = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos).ok_or("truncated varint")?;
+        *pos += 1;
+        result |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    Err("varint too long".to_string())
+}
+
+fn take<'a>(bytes: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], String> {
+    let end = pos
+        .checked_add(len)
+        .filter(|end| *end <= bytes.len())
+        .ok_or("truncated field")?;
+    let slice = &bytes[*pos..end];
+    *pos = end;
+    Ok(slice)
+}
+
+fn integer(value: u64) -> Value {
+    const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+    if value <= MAX_SAFE_INTEGER {
+        Value::from(value)
+    } else {
+        Value::String(value.to_string())
+    }
+}
+
+fn length_delimited(bytes: &[u8]) -> Value {
+    let printable = |text: &&str| {
+        !text
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+    };
+    if let Some(text) = std::str::from_utf8(bytes).ok().filter(printable) {
+        return Value::String(text.to_string());
+    }
+    match decode_protobuf(bytes) {
+        Ok(message) => message,
+        Err(_) => Value::String(STANDARD.encode(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_engine::test_dir;
+    use serde_json::json;
+
+    fn temp_state_db(label: &str) -> String {
+        let path = test_dir(&format!("ide-{}", label)).join("state.vscdb").to_string_lossy().to_string();
+        let conn = rusqlite::Connection::open(&path).expect("create db");
+        conn.execute_batch("CREATE TABLE ItemTable (key TEXT UNIQUE ON CONFLICT REPLACE, value BLOB);")
+            .expect("seed db");
+        path
+    }
+
+    #[test]
+    fn candidates_cover_variants_and_portable_installs() {
+        let config = Path::new("/home/me/.config");
+        assert_eq!(
+            state_db_candidates("Windsurf", Variant::Next, Some(config), None),
+            vec![PathBuf::from("/home/me/.config/Windsurf - Next/User/globalStorage/state.vscdb")]
+        );
+        assert_eq!(
+            state_db_candidates("Code", Variant::Insiders, Some(config), Some(Path::new("/opt/code/data"))),
+            vec![
+                PathBuf::from("/opt/code/data/user-data/User/globalStorage/state.vscdb"),
+                PathBuf::from("/home/me/.config/Code - Insiders/User/globalStorage/state.vscdb"),
+            ]
+        );
+        assert!(Variant::parse("beta").is_err());
+    }
+
+    #[test]
+    fn reads_text_blob_and_json_items() {
+        let path = temp_state_db("items");
+        set_item(&path, "cursorAuth/accessToken", "tok").expect("set");
+        set_item(&path, "windsurfAuthStatus", r#"{"apiKey":"sk-1"}"#).expect("set");
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute("INSERT INTO ItemTable VALUES ('blob', x'68656C6C6F')", [])
+            .unwrap();
+
+        assert_eq!(get_item(&path, "cursorAuth/accessToken").unwrap().as_deref(), Some("tok"));
+        assert_eq!(get_item(&path, "blob").unwrap().as_deref(), Some("hello"));
+        assert_eq!(get_item(&path, "missing").unwrap(), None);
+        assert_eq!(get_json(&path, "windsurfAuthStatus").unwrap(), Some(json!({ "apiKey": "sk-1" })));
+        assert!(get_json(&path, "cursorAuth/accessToken").is_err());
+    }
+
+    #[test]
+    fn decodes_protobuf_values() {
+        // 1: 150, 2: "plan", 3: { 1: 7 }, 4: [1, 2] (repeated), 5: fixed32 9
+        let message = [
+            0x08, 0x96, 0x01, 0x12, 0x04, b'p', b'l', b'a', b'n', 0x1a, 0x02, 0x08, 0x07, 0x20, 0x01,
+            0x20, 0x02, 0x2d, 0x09, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(
+            decode_protobuf(&message).unwrap(),
+            json!({ "1": 150, "2": "plan", "3": { "1": 7 }, "4": [1, 2], "5": 9 })
+        );
+        let max_varint = [0x08, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert_eq!(
+            decode_protobuf(&max_varint).unwrap(),
+            json!({ "1": "18446744073709551615" })
+        );
+        assert!(decode_protobuf(&[0x12, 0x05, b'a']).is_err());
+
+        let path = temp_state_db("proto");
+        set_item(&path, "userStatus", &STANDARD.encode(message)).expect("set");
+        assert_eq!(get_proto(&path, "userStatus").unwrap().unwrap()["2"], json!("plan"));
+    }
+}
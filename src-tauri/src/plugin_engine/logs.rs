@@ -0,0 +1,402 @@
+use crate::plugin_engine::files;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// File in `pluginDataDir` holding the scan offsets of every pattern the plugin has scanned.
+pub const STATE_FILE: &str = "log-offsets.json";
+
+/// Files read per scan; the rest is picked up by the next call (`more` is set). Unchanged
+/// files only cost a `stat` and do not count.
+pub const MAX_LOG_FILES: usize = 10_000;
+
+/// Bytes read per scan; the rest is picked up by the next call (`more` is set).
+pub const MAX_SCAN_BYTES: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScanOpts {
+    /// Dotted paths to keep from each record (`message.usage.input_tokens`); all when empty.
+    pub fields: Vec<String>,
+    /// Skip records (and untouched files) older than this, as unix ms or an RFC 3339 string.
+    pub since: Option<Value>,
+    /// Field compared against `since`; `timestamp` by default.
+    pub timestamp_field: Option<String>,
+    /// Forget stored offsets and read every file from the start.
+    pub reset: bool,
+    /// Add the path of the file each record came from under this key.
+    pub path_field: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanResult {
+    pub records: Vec<Value>,
+    pub files_scanned: usize,
+    pub bytes_read: u64,
+    /// Files read again from the start because they were replaced or truncated. Records
+    /// from them may have been returned by an earlier scan.
+    pub rewritten: Vec<String>,
+    /// Lines that were not valid JSON.
+    pub skipped: usize,
+    /// The byte budget ran out; call again to read the remainder.
+    pub more: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileState {
+    offset: u64,
+    size: u64,
+    mtime_ms: Option<u64>,
+    id: Option<String>,
+}
+
+type PatternState = BTreeMap<String, FileState>;
+
+/// Identity of the file behind a path, so a log replaced by rotation is read from the start.
+#[cfg(unix)]
+fn file_id(meta: &std::fs::Metadata) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    Some(format!("{}:{}", meta.dev(), meta.ino()))
+}
+
+#[cfg(windows)]
+fn file_id(meta: &std::fs::Metadata) -> Option<String> {
+    use std::os::windows::fs::MetadataExt;
+    Some(meta.creation_time().to_string())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn file_id(_meta: &std::fs::Metadata) -> Option<String> {
+    None
+}
+
+fn mtime_ms(meta: &std::fs::Metadata) -> Option<u64> {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+}
+
+/// `since` as unix milliseconds; accepts a number or an RFC 3339 string.
+pub fn parse_since(value: &Value) -> Result<Option<u64>, String> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Number(n) => Ok(n.as_f64().map(|ms| ms.max(0.0) as u64)),
+        Value::String(text) => timestamp_ms(value)
+            .map(Some)
+            .ok_or_else(|| format!("invalid since: {}", text)),
+        _ => Err("since must be a number or an ISO timestamp".to_string()),
+    }
+}
+
+fn timestamp_ms(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_f64().map(|ms| ms.max(0.0) as u64),
+        Value::String(text) => {
+            time::OffsetDateTime::parse(text, &time::format_description::well_known::Rfc3339)
+                .ok()
+                .map(|t| (t.unix_timestamp_nanos() / 1_000_000).max(0) as u64)
+        }
+        _ => None,
+    }
+}
+
+fn lookup<'a>(record: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(record, |value, key| value.get(key))
+}
+
+/// Copy the requested dotted paths into a new object with the same nesting. `None` when the
+/// record has none of them.
+fn project(record: &Value, fields: &[String]) -> Option<Value> {
+    if fields.is_empty() {
+        return Some(record.clone());
+    }
+    let mut out = Map::new();
+    for field in fields {
+        let Some(value) = lookup(record, field) else {
+            continue;
+        };
+        let mut keys: Vec<&str> = field.split('.').collect();
+        let last = keys.pop().unwrap_or_default();
+        let mut target = &mut out;
+        for key in keys {
+            let entry = target
+                .entry(key.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            target = entry.as_object_mut().expect("object");
+        }
+        target.insert(last.to_string(), value.clone());
+    }
+    (!out.is_empty()).then_some(Value::Object(out))
+}
+
+fn load_state(path: &Path) -> BTreeMap<String, PatternState> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Read lines appended to the files matching `pattern` since the previous scan and return their
+/// projected records. Offsets, sizes, mtimes and file identities are kept per pattern in
+/// `state_path`; unchanged files are skipped without being opened, and a trailing line without
+/// a newline is left for the next scan.
+pub fn scan_jsonl(pattern: &str, opts: &ScanOpts, state_path: &Path) -> Result<ScanResult, String> {
+    scan_with_limit(pattern, opts, state_path, MAX_LOG_FILES)
+}
+
+fn scan_with_limit(
+    pattern: &str,
+    opts: &ScanOpts,
+    state_path: &Path,
+    max_files: usize,
+) -> Result<ScanResult, String> {
+    let since = match &opts.since {
+        Some(value) => parse_since(value)?,
+        None => None,
+    };
+    let timestamp_field = opts.timestamp_field.as_deref().unwrap_or("timestamp");
+    let paths = glob::glob(pattern).map_err(|e| format!("invalid glob pattern: {}", e))?;
+
+    let mut all_state = load_state(state_path);
+    let previous = if opts.reset {
+        PatternState::new()
+    } else {
+        all_state.remove(pattern).unwrap_or_default()
+    };
+    let mut next_state = PatternState::new();
+    let mut result = ScanResult::default();
+    let mut budget = MAX_SCAN_BYTES;
+
+    for path in paths.flatten() {
+        let Ok(meta) = std::fs::metadata(&path) else {
+            continue;
+        };
+        if !meta.is_file() {
+            continue;
+        }
+        let key = path.to_string_lossy().to_string();
+        let current = FileState {
+            offset: 0,
+            size: meta.len(),
+            mtime_ms: mtime_ms(&meta),
+            id: file_id(&meta),
+        };
+        let stored = previous.get(&key);
+
+        let unchanged = stored.is_some_and(|stored| {
+            stored.id == current.id && stored.size == current.size && stored.mtime_ms == current.mtime_ms
+        });
+        let stale = matches!((since, current.mtime_ms), (Some(since), Some(mtime)) if mtime < since);
+        if unchanged || stale {
+            if let Some(stored) = stored {
+                next_state.insert(key, stored.clone());
+            }
+            continue;
+        }
+
+        if budget == 0 || result.files_scanned == max_files {
+            result.more = true;
+            if let Some(stored) = stored {
+                next_state.insert(key, stored.clone());
+            }
+            continue;
+        }
+        let mut offset = match stored {
+            Some(stored) if stored.id == current.id && stored.offset <= current.size => stored.offset,
+            Some(_) => {
+                result.rewritten.push(key.clone());
+                0
+            }
+            None => 0,
+        };
+
+        let mut file = std::fs::File::open(&path).map_err(|e| format!("cannot read {}: {}", key, e))?;
+        file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+        let mut reader = BufReader::new(file);
+        let mut line = Vec::new();
+        result.files_scanned += 1;
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line).map_err(|e| e.to_string())? as u64;
+            // A line still being written has no newline yet; leave it for the next scan
+            if read == 0 || line.last() != Some(&b'\n') {
+                break;
+            }
+            if read > budget {
+                result.more = true;
+                break;
+            }
+            budget -= read;
+            offset += read;
+            result.bytes_read += read;
+
+            let text = String::from_utf8_lossy(&line);
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let Ok(record) = serde_json::from_str::<Value>(text) else {
+                result.skipped += 1;
+                continue;
+            };
+            if let Some(since) = since {
+                let stamp = lookup(&record, timestamp_field).and_then(timestamp_ms);
+                if stamp.is_some_and(|stamp| stamp < since) {
+                    continue;
+                }
+            }
+            if let Some(mut projected) = project(&record, &opts.fields) {
+                if let (Some(field), Some(object)) = (&opts.path_field, projected.as_object_mut()) {
+                    object.insert(field.clone(), Value::String(key.clone()));
+                }
+                result.records.push(projected);
+            }
+        }
+
+        // Keep the size/mtime of a partially read file out of the skip check
+        let complete = offset == current.size;
+        next_state.insert(
+            key,
+            FileState {
+                offset,
+                size: if complete { current.size } else { offset },
+                mtime_ms: if complete { current.mtime_ms } else { None },
+                id: current.id,
+            },
+        );
+    }
+
+    all_state.insert(pattern.to_string(), next_state);
+    let text = serde_json::to_string(&all_state).map_err(|e| e.to_string())?;
+    if let Some(dir) = state_path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    files::write_text_atomic(&state_path.to_string_lossy(), &text)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_engine::test_dir;
+    use serde_json::json;
+    use std::io::Write;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .expect("open log");
+        file.write_all(text.as_bytes()).expect("append");
+    }
+
+    fn opts(fields: &[&str]) -> ScanOpts {
+        ScanOpts {
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+            ..ScanOpts::default()
+        }
+    }
+
+    #[test]
+    fn returns_only_new_complete_lines() {
+        let dir = test_dir("incremental");
+        let state = dir.join("data").join(STATE_FILE);
+        let log = dir.join("session.jsonl");
+        let pattern = format!("{}/*.jsonl", dir.to_string_lossy());
+        append(
+            &log,
+            "{\"type\":\"assistant\",\"message\":{\"model\":\"m1\",\"usage\":{\"input_tokens\":5}}}\nnot json\n{\"type\":\"user\"",
+        );
+
+        let first = scan_jsonl(&pattern, &opts(&["message.model", "message.usage.input_tokens"]), &state).unwrap();
+        assert_eq!(first.records, vec![json!({ "message": { "model": "m1", "usage": { "input_tokens": 5 } } })]);
+        assert_eq!(first.skipped, 1);
+        assert_eq!(first.files_scanned, 1);
+
+        let unchanged = scan_jsonl(&pattern, &opts(&["type"]), &state).unwrap();
+        assert_eq!(unchanged.records, Vec::<Value>::new());
+
+        append(&log, "}\n{\"type\":\"assistant\"}\n");
+        let second = scan_jsonl(&pattern, &opts(&["type"]), &state).unwrap();
+        assert_eq!(second.records, vec![json!({ "type": "user" }), json!({ "type": "assistant" })]);
+
+        let again = scan_jsonl(&pattern, &ScanOpts { reset: true, ..opts(&["type"]) }, &state).unwrap();
+        assert_eq!(again.records.len(), 3);
+    }
+
+    #[test]
+    fn tags_records_with_their_file() {
+        let dir = test_dir("path-field");
+        let state = dir.join(STATE_FILE);
+        let log = dir.join("a.jsonl");
+        let pattern = format!("{}/*.jsonl", dir.to_string_lossy());
+        append(&log, "{\"n\":1}\n");
+
+        let tagged = ScanOpts {
+            path_field: Some("file".to_string()),
+            ..opts(&["n"])
+        };
+        let records = scan_jsonl(&pattern, &tagged, &state).unwrap().records;
+        assert_eq!(records, vec![json!({ "n": 1, "file": log.to_string_lossy() })]);
+    }
+
+    #[test]
+    fn rereads_truncated_files_and_filters_by_since() {
+        let dir = test_dir("rewrite");
+        let state = dir.join(STATE_FILE);
+        let log = dir.join("a.jsonl");
+        let pattern = format!("{}/*.jsonl", dir.to_string_lossy());
+        append(&log, "{\"timestamp\":\"2026-01-01T00:00:00Z\",\"n\":1}\n{\"timestamp\":\"2026-03-01T00:00:00Z\",\"n\":2}\n");
+
+        let since = ScanOpts {
+            since: Some(json!("2026-02-01T00:00:00Z")),
+            ..opts(&["n"])
+        };
+        assert_eq!(scan_jsonl(&pattern, &since, &state).unwrap().records, vec![json!({ "n": 2 })]);
+
+        std::fs::write(&log, "{\"n\":3}\n").unwrap();
+        let rewritten = scan_jsonl(&pattern, &opts(&["n"]), &state).unwrap();
+        assert_eq!(rewritten.records, vec![json!({ "n": 3 })]);
+        assert_eq!(rewritten.rewritten, vec![log.to_string_lossy().to_string()]);
+
+        assert!(parse_since(&json!("yesterday")).is_err());
+        assert_eq!(parse_since(&json!(1000)).unwrap(), Some(1000));
+    }
+
+    #[test]
+    fn files_past_the_limit_are_read_by_the_next_scan() {
+        let dir = test_dir("file-limit");
+        let state = dir.join(STATE_FILE);
+        let pattern = format!("{}/*.jsonl", dir.to_string_lossy());
+        for n in 1..=3 {
+            append(&dir.join(format!("{}.jsonl", n)), &format!("{{\"n\":{}}}\n", n));
+        }
+
+        let first = scan_with_limit(&pattern, &opts(&["n"]), &state, 2).unwrap();
+        assert_eq!(first.records, vec![json!({ "n": 1 }), json!({ "n": 2 })]);
+        assert!(first.more);
+
+        let second = scan_with_limit(&pattern, &opts(&["n"]), &state, 2).unwrap();
+        assert_eq!(second.records, vec![json!({ "n": 3 })]);
+        assert!(!second.more);
+
+        // Offsets of files past the limit survive a scan that does not reach them
+        append(&dir.join("1.jsonl"), "{\"n\":4}\n");
+        append(&dir.join("2.jsonl"), "{\"n\":5}\n");
+        append(&dir.join("3.jsonl"), "{\"n\":6}\n");
+        let third = scan_with_limit(&pattern, &opts(&["n"]), &state, 2).unwrap();
+        assert_eq!(third.records, vec![json!({ "n": 4 }), json!({ "n": 5 })]);
+        let fourth = scan_with_limit(&pattern, &opts(&["n"]), &state, 2).unwrap();
+        assert_eq!(fourth.records, vec![json!({ "n": 6 })]);
+    }
+}
@@ -0,0 +1,650 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A running process with its full command line (arguments joined by spaces).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub command: String,
+}
+
+/// Process enumeration and listening-port lookup, so discovery does not depend on
+/// platform CLIs (`ps`, `lsof`) and can be tested with a fake.
+pub trait ProcessInspector: Send + Sync {
+    /// Processes named like `name` (case-insensitive), with their command lines. Windows
+    /// matches the image name; the other inspectors match the whole command line.
+    fn find_processes(&self, name: &str) -> Result<Vec<ProcessInfo>, String>;
+    /// TCP ports the process is listening on, sorted and deduplicated.
+    fn listening_ports(&self, pid: u32) -> Result<Vec<u16>, String>;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LsDiscoverOpts {
+    pub process_name: String,
+    pub markers: Vec<String>,
+    pub csrf_flag: String,
+    pub port_flag: Option<String>,
+    pub extra_flags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LsDiscoverResult {
+    pub pid: u32,
+    pub csrf: String,
+    pub ports: Vec<u16>,
+    pub extra: HashMap<String, String>,
+    pub extension_port: Option<u16>,
+}
+
+pub fn native_inspector() -> Box<dyn ProcessInspector> {
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(procfs::ProcfsInspector)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        Box::new(windows::WindowsInspector)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        Box::new(ps_lsof::PsLsofInspector)
+    }
+}
+
+/// Every running language server matching `opts`, in process-list order. Instances without
+/// a CSRF token or any reachable port are skipped.
+pub fn discover(
+    inspector: &dyn ProcessInspector,
+    opts: &LsDiscoverOpts,
+    plugin_id: &str,
+) -> Vec<LsDiscoverResult> {
+    let processes = match inspector.find_processes(&opts.process_name) {
+        Ok(processes) => processes,
+        Err(e) => {
+            log::warn!("[plugin:{}] process list failed: {}", plugin_id, e);
+            return Vec::new();
+        }
+    };
+    let markers_lower: Vec<String> = opts.markers.iter().map(|m| m.to_lowercase()).collect();
+
+    let mut results = Vec::new();
+    for process in processes {
+        if !matches_marker(&process.command, &markers_lower) {
+            continue;
+        }
+
+        let Some(csrf) = extract_flag(&process.command, &opts.csrf_flag) else {
+            log::warn!(
+                "[plugin:{}] CSRF token not found in args of pid {}",
+                plugin_id,
+                process.pid
+            );
+            continue;
+        };
+
+        let extension_port = opts
+            .port_flag
+            .as_ref()
+            .and_then(|flag| extract_flag(&process.command, flag))
+            .and_then(|v| v.parse::<u16>().ok());
+
+        let mut extra = HashMap::new();
+        for flag in opts.extra_flags.iter().flatten() {
+            if let Some(val) = extract_flag(&process.command, flag) {
+                // Use flag name without leading dashes as key
+                extra.insert(flag.trim_start_matches('-').to_string(), val);
+            }
+        }
+
+        let ports = inspector.listening_ports(process.pid).unwrap_or_else(|e| {
+            log::warn!(
+                "[plugin:{}] port lookup failed for pid {}: {}",
+                plugin_id,
+                process.pid,
+                e
+            );
+            Vec::new()
+        });
+        if ports.is_empty() && extension_port.is_none() {
+            log::warn!(
+                "[plugin:{}] no listening ports found for pid {}",
+                plugin_id,
+                process.pid
+            );
+            continue;
+        }
+
+        log::info!(
+            "[plugin:{}] LS found: pid={}, ports={:?}, csrf=[REDACTED]",
+            plugin_id,
+            process.pid,
+            ports
+        );
+        results.push(LsDiscoverResult {
+            pid: process.pid,
+            csrf,
+            ports,
+            extra,
+            extension_port,
+        });
+    }
+    results
+}
+
+/// Marker patterns are Codeium-derived. Matching priority:
+///   1. Exact --ide_name / --app_data_dir flag value (prevents
+///      "windsurf" matching "windsurf-next")
+///   2. Path substring (/<marker>/ or \<marker>\) as fallback when no flags found
+fn matches_marker(command: &str, markers_lower: &[String]) -> bool {
+    let command_lower = command.to_lowercase();
+    let ide_name = extract_flag(command, "--ide_name").map(|v| v.to_lowercase());
+    let app_data = extract_flag(command, "--app_data_dir").map(|v| v.to_lowercase());
+
+    markers_lower.iter().any(|m| {
+        if let Some(ref name) = ide_name {
+            return name == m;
+        }
+        if let Some(ref dir) = app_data {
+            return dir == m;
+        }
+        command_lower.contains(&format!("/{}/", m)) || command_lower.contains(&format!("\\{}\\", m))
+    })
+}
+
+/// Extract value of a CLI flag from a command string.
+/// Handles both `--flag value` and `--flag=value` forms.
+fn extract_flag(command: &str, flag: &str) -> Option<String> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    let flag_eq = format!("{}=", flag);
+    for (i, part) in parts.iter().enumerate() {
+        if *part == flag {
+            if i + 1 < parts.len() {
+                return Some(parts[i + 1].to_string());
+            }
+        } else if let Some(value) = part.strip_prefix(&flag_eq) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Linux: `/proc/<pid>/cmdline` for processes, socket inodes from `/proc/<pid>/fd`
+/// matched against listening sockets in `/proc/net/tcp{,6}`.
+#[cfg(any(target_os = "linux", test))]
+mod procfs {
+    use super::{ProcessInfo, ProcessInspector};
+    use std::collections::{BTreeSet, HashSet};
+
+    /// `st` column value for TCP_LISTEN.
+    const TCP_LISTEN: &str = "0A";
+
+    #[cfg_attr(test, allow(dead_code))]
+    pub struct ProcfsInspector;
+
+    impl ProcessInspector for ProcfsInspector {
+        fn find_processes(&self, name: &str) -> Result<Vec<ProcessInfo>, String> {
+            let name_lower = name.to_lowercase();
+            let entries =
+                std::fs::read_dir("/proc").map_err(|e| format!("cannot read /proc: {}", e))?;
+            let mut processes: Vec<ProcessInfo> = entries
+                .flatten()
+                .filter_map(|entry| {
+                    let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+                    // Processes may exit between listing and reading; skip those
+                    let raw = std::fs::read(entry.path().join("cmdline")).ok()?;
+                    let command = cmdline_to_command(&raw);
+                    command
+                        .to_lowercase()
+                        .contains(&name_lower)
+                        .then_some(ProcessInfo { pid, command })
+                })
+                .collect();
+            processes.sort_by_key(|p| p.pid);
+            Ok(processes)
+        }
+
+        fn listening_ports(&self, pid: u32) -> Result<Vec<u16>, String> {
+            let fd_dir = format!("/proc/{}/fd", pid);
+            let inodes: HashSet<u64> = std::fs::read_dir(&fd_dir)
+                .map_err(|e| format!("cannot read {}: {}", fd_dir, e))?
+                .flatten()
+                .filter_map(|entry| {
+                    let target = std::fs::read_link(entry.path()).ok()?;
+                    let target = target.to_str()?;
+                    target.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+                })
+                .collect();
+
+            let mut ports = BTreeSet::new();
+            for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+                let Ok(text) = std::fs::read_to_string(table) else {
+                    continue;
+                };
+                for (inode, port) in parse_listening_sockets(&text) {
+                    if inodes.contains(&inode) {
+                        ports.insert(port);
+                    }
+                }
+            }
+            Ok(ports.into_iter().collect())
+        }
+    }
+
+    /// NUL-separated argv to a space-joined command line.
+    pub fn cmdline_to_command(raw: &[u8]) -> String {
+        raw.split(|b| *b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// `(inode, local port)` of listening sockets in `/proc/net/tcp` format.
+    pub fn parse_listening_sockets(text: &str) -> Vec<(u64, u16)> {
+        text.lines()
+            .skip(1)
+            .filter_map(|line| {
+                let cols: Vec<&str> = line.split_whitespace().collect();
+                if cols.len() < 10 || cols[3] != TCP_LISTEN {
+                    return None;
+                }
+                let port_hex = cols[1].rsplit(':').next()?;
+                let port = u16::from_str_radix(port_hex, 16).ok()?;
+                let inode = cols[9].parse::<u64>().ok()?;
+                Some((inode, port))
+            })
+            .collect()
+    }
+}
+
+/// Windows: Toolhelp snapshot for processes, `NtQueryInformationProcess` for command
+/// lines and `GetExtendedTcpTable` for listening ports.
+#[cfg(target_os = "windows")]
+mod windows {
+    use super::{ProcessInfo, ProcessInspector};
+    use std::collections::BTreeSet;
+    use windows_sys::Wdk::System::Threading::{
+        NtQueryInformationProcess, ProcessCommandLineInformation,
+    };
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, ERROR_INSUFFICIENT_BUFFER, INVALID_HANDLE_VALUE, NO_ERROR, UNICODE_STRING,
+    };
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        GetExtendedTcpTable, MIB_TCP6ROW_OWNER_PID, MIB_TCPROW_OWNER_PID,
+        TCP_TABLE_OWNER_PID_LISTENER,
+    };
+    use windows_sys::Win32::Networking::WinSock::{AF_INET, AF_INET6};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+    use windows_sys::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    /// NTSTATUS for a too-small output buffer.
+    const STATUS_INFO_LENGTH_MISMATCH: i32 = 0xC000_0004_u32 as i32;
+
+    pub struct WindowsInspector;
+
+    impl ProcessInspector for WindowsInspector {
+        fn find_processes(&self, name: &str) -> Result<Vec<ProcessInfo>, String> {
+            let name_lower = name.to_lowercase();
+            let mut processes = Vec::new();
+            // Only processes with a matching image name are opened to read their command line
+            for (pid, exe) in process_list()? {
+                if !exe.to_lowercase().contains(&name_lower) {
+                    continue;
+                }
+                match command_line(pid) {
+                    Some(command) => processes.push(ProcessInfo { pid, command }),
+                    None => log::debug!("cannot read command line of pid {}", pid),
+                }
+            }
+            Ok(processes)
+        }
+
+        fn listening_ports(&self, pid: u32) -> Result<Vec<u16>, String> {
+            let mut ports = BTreeSet::new();
+            let v4 = tcp_table(AF_INET as u32)?;
+            for row in rows::<MIB_TCPROW_OWNER_PID>(&v4) {
+                if row.dwOwningPid == pid {
+                    ports.insert(u16::from_be(row.dwLocalPort as u16));
+                }
+            }
+            let v6 = tcp_table(AF_INET6 as u32)?;
+            for row in rows::<MIB_TCP6ROW_OWNER_PID>(&v6) {
+                if row.dwOwningPid == pid {
+                    ports.insert(u16::from_be(row.dwLocalPort as u16));
+                }
+            }
+            Ok(ports.into_iter().collect())
+        }
+    }
+
+    fn process_list() -> Result<Vec<(u32, String)>, String> {
+        let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+        if snapshot == INVALID_HANDLE_VALUE {
+            return Err("process snapshot failed".to_string());
+        }
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut list = Vec::new();
+        let mut ok = unsafe { Process32FirstW(snapshot, &mut entry) };
+        while ok != 0 {
+            let len = entry
+                .szExeFile
+                .iter()
+                .position(|c| *c == 0)
+                .unwrap_or(entry.szExeFile.len());
+            list.push((
+                entry.th32ProcessID,
+                String::from_utf16_lossy(&entry.szExeFile[..len]),
+            ));
+            ok = unsafe { Process32NextW(snapshot, &mut entry) };
+        }
+        unsafe { CloseHandle(snapshot) };
+        Ok(list)
+    }
+
+    fn command_line(pid: u32) -> Option<String> {
+        let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+        if handle.is_null() {
+            return None;
+        }
+        // u64 storage keeps the UNICODE_STRING header aligned
+        let mut buf: Vec<u64> = vec![0; 512];
+        let mut result = None;
+        for _ in 0..2 {
+            let mut needed = 0u32;
+            let status = unsafe {
+                NtQueryInformationProcess(
+                    handle,
+                    ProcessCommandLineInformation,
+                    buf.as_mut_ptr().cast(),
+                    (buf.len() * 8) as u32,
+                    &mut needed,
+                )
+            };
+            if status == STATUS_INFO_LENGTH_MISMATCH {
+                buf = vec![0; (needed as usize).div_ceil(8)];
+                continue;
+            }
+            if status >= 0 {
+                result = unsafe {
+                    let header = &*(buf.as_ptr() as *const UNICODE_STRING);
+                    (!header.Buffer.is_null()).then(|| {
+                        let chars = std::slice::from_raw_parts(
+                            header.Buffer,
+                            header.Length as usize / 2,
+                        );
+                        String::from_utf16_lossy(chars)
+                    })
+                };
+            }
+            break;
+        }
+        unsafe { CloseHandle(handle) };
+        result
+    }
+
+    fn tcp_table(family: u32) -> Result<Vec<u64>, String> {
+        let mut size = 0u32;
+        let mut buf: Vec<u64> = Vec::new();
+        for _ in 0..3 {
+            let status = unsafe {
+                GetExtendedTcpTable(
+                    buf.as_mut_ptr().cast(),
+                    &mut size,
+                    0,
+                    family,
+                    TCP_TABLE_OWNER_PID_LISTENER,
+                    0,
+                )
+            };
+            if status == NO_ERROR {
+                return Ok(buf);
+            }
+            if status != ERROR_INSUFFICIENT_BUFFER {
+                return Err(format!("GetExtendedTcpTable failed (error {})", status));
+            }
+            buf = vec![0; (size as usize).div_ceil(8)];
+        }
+        Err("GetExtendedTcpTable kept growing".to_string())
+    }
+
+    /// Rows of a `MIB_TCP*TABLE_OWNER_PID`: a u32 count followed by the row array.
+    fn rows<T>(table: &[u64]) -> &[T] {
+        if table.is_empty() {
+            return &[];
+        }
+        let offset = std::mem::align_of::<T>().max(4);
+        unsafe {
+            let base = table.as_ptr() as *const u8;
+            let count = *(base as *const u32) as usize;
+            std::slice::from_raw_parts(base.add(offset) as *const T, count)
+        }
+    }
+}
+
+/// macOS and other Unixes: `ps` for processes and `lsof` for listening ports.
+#[cfg(any(not(any(target_os = "linux", target_os = "windows")), test))]
+mod ps_lsof {
+    use super::{ProcessInfo, ProcessInspector};
+
+    #[cfg_attr(test, allow(dead_code))]
+    pub struct PsLsofInspector;
+
+    impl ProcessInspector for PsLsofInspector {
+        fn find_processes(&self, name: &str) -> Result<Vec<ProcessInfo>, String> {
+            let output = std::process::Command::new("/bin/ps")
+                .args(["-ax", "-o", "pid=,command="])
+                .output()
+                .map_err(|e| format!("ps failed: {}", e))?;
+            if !output.status.success() {
+                return Err("ps returned non-zero".to_string());
+            }
+            let name_lower = name.to_lowercase();
+            Ok(parse_ps(&String::from_utf8_lossy(&output.stdout))
+                .into_iter()
+                .filter(|p| p.command.to_lowercase().contains(&name_lower))
+                .collect())
+        }
+
+        fn listening_ports(&self, pid: u32) -> Result<Vec<u16>, String> {
+            let lsof = ["/usr/sbin/lsof", "/usr/bin/lsof"]
+                .iter()
+                .find(|p| std::path::Path::new(p).exists())
+                .ok_or("lsof not found")?;
+            let output = std::process::Command::new(lsof)
+                .args(["-nP", "-iTCP", "-sTCP:LISTEN", "-a", "-p", &pid.to_string()])
+                .output()
+                .map_err(|e| format!("lsof failed: {}", e))?;
+            if !output.status.success() {
+                return Err("lsof returned non-zero".to_string());
+            }
+            Ok(parse_listening_ports(&String::from_utf8_lossy(&output.stdout)))
+        }
+    }
+
+    /// Parse `ps -ax -o pid=,command=` output.
+    pub fn parse_ps(output: &str) -> Vec<ProcessInfo> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let (pid, command) = line.trim().split_once(char::is_whitespace)?;
+                Some(ProcessInfo {
+                    pid: pid.parse().ok()?,
+                    command: command.trim().to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Parse listening port numbers from `lsof -nP -iTCP -sTCP:LISTEN` output.
+    pub fn parse_listening_ports(output: &str) -> Vec<u16> {
+        let mut ports = std::collections::BTreeSet::new();
+        for line in output.lines() {
+            if !line.contains("LISTEN") {
+                continue;
+            }
+            // lsof -nP output: ... TCP 127.0.0.1:PORT (LISTEN)  or  ... TCP *:PORT
+            // Scan tokens in reverse to find the address:port token.
+            let port = line.split_whitespace().rev().find_map(|token| {
+                let (_, port) = token.rsplit_once(':')?;
+                port.parse::<u16>().ok().filter(|p| *p > 0)
+            });
+            if let Some(port) = port {
+                ports.insert(port);
+            }
+        }
+        ports.into_iter().collect()
+    }
+}
+
+/// In-memory inspector for tests.
+#[cfg(test)]
+pub struct FakeInspector {
+    pub processes: Vec<ProcessInfo>,
+    pub ports: HashMap<u32, Vec<u16>>,
+}
+
+#[cfg(test)]
+impl ProcessInspector for FakeInspector {
+    fn find_processes(&self, name: &str) -> Result<Vec<ProcessInfo>, String> {
+        let name_lower = name.to_lowercase();
+        Ok(self
+            .processes
+            .iter()
+            .filter(|p| p.command.to_lowercase().contains(&name_lower))
+            .cloned()
+            .collect())
+    }
+
+    fn listening_ports(&self, pid: u32) -> Result<Vec<u16>, String> {
+        Ok(self.ports.get(&pid).cloned().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, command: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            command: command.to_string(),
+        }
+    }
+
+    fn windsurf_opts(marker: &str) -> LsDiscoverOpts {
+        LsDiscoverOpts {
+            process_name: "language_server".to_string(),
+            markers: vec![marker.to_string()],
+            csrf_flag: "--csrf_token".to_string(),
+            port_flag: Some("--extension_server_port".to_string()),
+            extra_flags: Some(vec!["--windsurf_version".to_string()]),
+        }
+    }
+
+    fn inspector() -> FakeInspector {
+        FakeInspector {
+            processes: vec![
+                process(10, "/usr/bin/zsh"),
+                process(
+                    11,
+                    "/Applications/Windsurf.app/language_server_macos --ide_name windsurf --csrf_token a1 --extension_server_port 5001 --windsurf_version 1.2.3",
+                ),
+                process(
+                    12,
+                    "/Applications/Windsurf - Next.app/language_server_macos --ide_name windsurf-next --csrf_token b2",
+                ),
+                process(
+                    13,
+                    r"C:\Users\me\AppData\Local\Programs\Windsurf\language_server_windows_x64.exe --ide_name=windsurf --csrf_token=c3",
+                ),
+                process(14, "/opt/windsurf/language_server_linux_x64 --ide_name windsurf"),
+            ],
+            ports: HashMap::from([(11, vec![42100, 42101]), (12, vec![43000]), (13, vec![44000])]),
+        }
+    }
+
+    #[test]
+    fn discover_returns_every_matching_instance() {
+        let results = discover(&inspector(), &windsurf_opts("windsurf"), "windsurf");
+        let pids: Vec<u32> = results.iter().map(|r| r.pid).collect();
+        // 12 is windsurf-next; 14 has no CSRF token
+        assert_eq!(pids, vec![11, 13]);
+
+        let first = &results[0];
+        assert_eq!(first.csrf, "a1");
+        assert_eq!(first.ports, vec![42100, 42101]);
+        assert_eq!(first.extension_port, Some(5001));
+        assert_eq!(first.extra.get("windsurf_version").map(String::as_str), Some("1.2.3"));
+        assert_eq!(results[1].csrf, "c3");
+    }
+
+    #[test]
+    fn discover_marker_falls_back_to_path_segment() {
+        let fake = FakeInspector {
+            processes: vec![
+                process(20, "/opt/antigravity/bin/language_server_linux --csrf_token t"),
+                process(21, r"C:\Program Files\Antigravity\bin\language_server.exe --csrf_token u"),
+                process(22, "/opt/antigravity-old/language_server --csrf_token v"),
+            ],
+            ports: HashMap::from([(20, vec![1]), (21, vec![2]), (22, vec![3])]),
+        };
+        let opts = LsDiscoverOpts {
+            process_name: "language_server".to_string(),
+            markers: vec!["antigravity".to_string()],
+            csrf_flag: "--csrf_token".to_string(),
+            port_flag: None,
+            extra_flags: None,
+        };
+        let pids: Vec<u32> = discover(&fake, &opts, "antigravity").iter().map(|r| r.pid).collect();
+        assert_eq!(pids, vec![20, 21]);
+    }
+
+    #[test]
+    fn discover_skips_instances_without_ports() {
+        let mut fake = inspector();
+        fake.ports.clear();
+        let results = discover(&fake, &windsurf_opts("windsurf"), "windsurf");
+        // Only the instance with an --extension_server_port survives
+        assert_eq!(results.iter().map(|r| r.pid).collect::<Vec<_>>(), vec![11]);
+        assert!(results[0].ports.is_empty());
+    }
+
+    #[test]
+    fn parses_proc_net_tcp_listeners() {
+        let text = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:A478 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 55501 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:A478 0100007F:C350 01 00000000:00000000 00:00000000 00000000  1000        0 55502 1 0000000000000000 20 4 30 10 -1
+   2: 00000000000000000000000000000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 55503 1";
+        assert_eq!(procfs::parse_listening_sockets(text), vec![(55501, 42104), (55503, 8080)]);
+        assert_eq!(
+            procfs::cmdline_to_command(b"/usr/bin/ls\0--csrf_token\0abc\0"),
+            "/usr/bin/ls --csrf_token abc"
+        );
+    }
+
+    #[test]
+    fn parses_ps_and_lsof_output() {
+        let ps = "  101 /usr/bin/language_server --csrf_token x\n  102 /sbin/launchd\n";
+        assert_eq!(
+            ps_lsof::parse_ps(ps),
+            vec![
+                process(101, "/usr/bin/language_server --csrf_token x"),
+                process(102, "/sbin/launchd")
+            ]
+        );
+
+        let lsof = "COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+language_ 101 me    12u  IPv4 0x1      0t0  TCP 127.0.0.1:42100 (LISTEN)
+language_ 101 me    13u  IPv6 0x2      0t0  TCP *:42101 (LISTEN)
+language_ 101 me    14u  IPv4 0x3      0t0  TCP 127.0.0.1:42100 (LISTEN)";
+        assert_eq!(ps_lsof::parse_listening_ports(lsof), vec![42100, 42101]);
+    }
+}
@@ -1,22 +1,129 @@
+use crate::plugin_engine::diagnostics::{pointer_segment, PluginReport};
+use crate::plugin_engine::settings;
+use crate::tray_render::parse_hex_color;
 use base64::{engine::general_purpose::STANDARD, Engine};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+/// Where a declared line is shown: the overview lists only `overview` lines, the
+/// provider detail page lists all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineScope {
+    Overview,
+    Detail,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ManifestLine {
+    /// Stable key of the line, kept when the label changes. Derived from the label when
+    /// omitted; `[A-Za-z0-9_-]` only.
+    #[serde(default)]
+    pub id: String,
     #[serde(rename = "type")]
     pub line_type: String,
     pub label: String,
-    pub scope: String,
+    pub scope: LineScope,
     /// Lower number = higher priority for primary metric selection.
     /// Only progress lines with primary_order are candidates.
     pub primary_order: Option<u32>,
+    /// Progress segment colors (`#rrggbb`), keyed by segment label or kind.
+    #[serde(default)]
+    pub segment_colors: BTreeMap<String, String>,
+    /// Money the user is billed for, counted by the "All providers" total. Included
+    /// allowances and free credits leave this unset.
+    #[serde(default)]
+    pub spend: bool,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestLink {
+    pub label: String,
+    pub url: String,
+}
+
+/// A named account the plugin is probed as (e.g. personal vs. work login).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProfile {
+    /// Stable id, also used as the account's data dir name. `[A-Za-z0-9_-]` only.
+    pub id: String,
+    pub label: String,
+    /// Values returned by `host.env.get` for this account, ahead of the process env.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Named credential paths exposed to the probe as `ctx.account.paths`.
+    #[serde(default)]
+    pub paths: HashMap<String, String>,
+}
+
+/// A user-editable plugin setting, rendered as a form field and passed to the probe
+/// as `ctx.settings[key]`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingField {
+    pub key: String,
+    pub label: String,
+    #[serde(flatten)]
+    pub kind: SettingKind,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SettingKind {
+    #[serde(rename_all = "camelCase")]
+    String {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pattern: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_length: Option<usize>,
+    },
+    /// Like `string`, but masked in the UI and never logged.
+    Secret,
+    Boolean,
+    Enum {
+        options: Vec<String>,
+    },
+    /// A file or directory path; `~` is expanded by the host fs APIs.
+    Path,
+}
+
+/// Valid account and line id: non-empty, `[A-Za-z0-9_-]` only.
+pub fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Line id for a label without one, e.g. `Extra usage` -> `extra-usage`.
+pub fn line_id_from_label(label: &str) -> String {
+    let mut id = String::new();
+    for c in label.chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c.to_ascii_lowercase());
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_end_matches('-').to_string()
+}
+
+/// A `plugin.json`, in the `schemaVersion` 2 shape the app works with. Older manifests are
+/// upgraded on load (see `ManifestV1`).
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PluginManifest {
+    /// Always `CURRENT_SCHEMA_VERSION` once loaded.
     pub schema_version: u32,
     pub id: String,
     pub name: String,
@@ -25,6 +132,209 @@ pub struct PluginManifest {
     pub icon: String,
     pub brand_color: Option<String>,
     pub lines: Vec<ManifestLine>,
+    /// Replace output lines that match no declared line with an error instead of only
+    /// logging a warning.
+    #[serde(default)]
+    pub strict_lines: bool,
+    /// External pages for the provider (dashboard, billing), shown in the tray menu.
+    #[serde(default)]
+    pub links: Vec<ManifestLink>,
+    /// Account profiles; when empty the plugin is probed once with no account.
+    #[serde(default)]
+    pub accounts: Vec<AccountProfile>,
+    /// User settings schema; values are exposed to the probe as `ctx.settings`.
+    #[serde(default)]
+    pub settings: Vec<SettingField>,
+    /// Operating systems the plugin runs on (`macos`, `windows`, `linux`); empty means all.
+    #[serde(default)]
+    pub platforms: Vec<String>,
+    /// Host capabilities the plugin cannot work without (see `capability_available`).
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    /// Minimum minutes between automatic refreshes, for providers with tight rate limits.
+    /// The app-wide auto-update interval applies when it is longer.
+    #[serde(default)]
+    pub refresh_interval: Option<u32>,
+    /// Oldest app version the plugin works with; older apps report it instead of loading it.
+    #[serde(default)]
+    pub min_app_version: Option<String>,
+}
+
+/// A `schemaVersion` 1 manifest, where host capabilities were called `requires`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestV1 {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub entry: String,
+    pub icon: String,
+    pub brand_color: Option<String>,
+    pub lines: Vec<ManifestLine>,
+    #[serde(default)]
+    pub strict_lines: bool,
+    #[serde(default)]
+    pub links: Vec<ManifestLink>,
+    #[serde(default)]
+    pub accounts: Vec<AccountProfile>,
+    #[serde(default)]
+    pub settings: Vec<SettingField>,
+    #[serde(default)]
+    pub platforms: Vec<String>,
+    #[serde(default)]
+    pub requires: Vec<String>,
+}
+
+impl From<ManifestV1> for PluginManifest {
+    fn from(v1: ManifestV1) -> Self {
+        PluginManifest {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            id: v1.id,
+            name: v1.name,
+            version: v1.version,
+            entry: v1.entry,
+            icon: v1.icon,
+            brand_color: v1.brand_color,
+            lines: v1.lines,
+            strict_lines: v1.strict_lines,
+            links: v1.links,
+            accounts: v1.accounts,
+            settings: v1.settings,
+            platforms: v1.platforms,
+            permissions: v1.requires,
+            refresh_interval: None,
+            min_app_version: None,
+        }
+    }
+}
+
+/// Parses a manifest of any supported `schemaVersion` into the current shape. The version
+/// must already have been checked.
+pub fn manifest_from_value(value: serde_json::Value) -> Result<PluginManifest, serde_json::Error> {
+    match value.get("schemaVersion").and_then(serde_json::Value::as_u64) {
+        Some(1) => serde_json::from_value::<ManifestV1>(value).map(PluginManifest::from),
+        _ => serde_json::from_value(value),
+    }
+}
+
+/// `major.minor.patch` of a version string like `0.6.5` or `v1.2`; missing parts are 0 and
+/// pre-release or build suffixes are ignored.
+pub fn parse_app_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version
+        .trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default();
+    let parts: Vec<u64> = core.split('.').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    match parts[..] {
+        [major] => Some((major, 0, 0)),
+        [major, minor] => Some((major, minor, 0)),
+        [major, minor, patch] => Some((major, minor, patch)),
+        _ => None,
+    }
+}
+
+impl PluginManifest {
+    /// Ids of progress lines eligible as the primary metric, highest priority first.
+    pub fn primary_candidates(&self) -> Vec<String> {
+        let mut candidates: Vec<_> = self
+            .lines
+            .iter()
+            .filter(|line| line.line_type == "progress" && line.primary_order.is_some())
+            .collect();
+        candidates.sort_by_key(|line| line.primary_order.unwrap());
+        candidates.iter().map(|line| line.id.clone()).collect()
+    }
+
+    /// Add user-configured profiles; a profile replaces a declared one with the same id.
+    /// Profiles with invalid ids are skipped.
+    pub fn merge_accounts(&mut self, profiles: Vec<AccountProfile>) {
+        for profile in profiles {
+            if !is_valid_id(&profile.id) {
+                log::warn!(
+                    "plugin {} account id '{}' is invalid; ignoring",
+                    self.id,
+                    profile.id
+                );
+                continue;
+            }
+            match self.accounts.iter_mut().find(|existing| existing.id == profile.id) {
+                Some(existing) => *existing = profile,
+                None => self.accounts.push(profile),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PluginSupport {
+    Supported,
+    /// The plugin is listed but never probed; the reason is shown instead.
+    Unsupported(String),
+}
+
+impl PluginSupport {
+    pub fn unsupported_reason(&self) -> Option<&str> {
+        match self {
+            Self::Supported => None,
+            Self::Unsupported(reason) => Some(reason),
+        }
+    }
+}
+
+pub const PLATFORMS: [&str; 3] = ["macos", "windows", "linux"];
+
+/// Whether the host provides a capability on `os`; `None` for capabilities this version
+/// does not know (the plugin targets a newer app).
+pub fn capability_available(capability: &str, os: &str) -> Option<bool> {
+    match capability {
+        "fs" | "http" | "sqlite" | "secrets" | "ide" | "logs" => Some(true),
+        "keychain" => Some(os == "macos"),
+        "ls-discovery" => Some(PLATFORMS.contains(&os)),
+        _ => None,
+    }
+}
+
+fn platform_name(os: &str) -> &str {
+    match os {
+        "macos" => "macOS",
+        "windows" => "Windows",
+        "linux" => "Linux",
+        other => other,
+    }
+}
+
+/// Check a manifest's `platforms` and `permissions` against the running OS.
+pub fn check_support(manifest: &PluginManifest, os: &str) -> PluginSupport {
+    if !manifest.platforms.is_empty() && !manifest.platforms.iter().any(|p| p == os) {
+        let names: Vec<&str> = manifest.platforms.iter().map(|p| platform_name(p)).collect();
+        return PluginSupport::Unsupported(format!(
+            "{} is only available on {}.",
+            manifest.name,
+            names.join(", ")
+        ));
+    }
+    for capability in &manifest.permissions {
+        match capability_available(capability, os) {
+            Some(true) => {}
+            Some(false) => {
+                return PluginSupport::Unsupported(format!(
+                    "{} requires {}, which is not available on {}.",
+                    manifest.name,
+                    capability,
+                    platform_name(os)
+                ));
+            }
+            None => {
+                return PluginSupport::Unsupported(format!(
+                    "{} requires {}, which this version of OpenUsage does not provide. Update the app.",
+                    manifest.name, capability
+                ));
+            }
+        }
+    }
+    PluginSupport::Supported
 }
 
 #[derive(Debug, Clone)]
@@ -33,89 +343,510 @@ pub struct LoadedPlugin {
     pub plugin_dir: PathBuf,
     pub entry_script: String,
     pub icon_data_url: String,
+    pub support: PluginSupport,
 }
 
-pub fn load_plugins_from_dir(plugins_dir: &std::path::Path) -> Vec<LoadedPlugin> {
-    let mut plugins = Vec::new();
+pub const MANIFEST_FILE: &str = "plugin.json";
+
+/// `schemaVersion` values this version of the app can load.
+pub const SCHEMA_VERSIONS: [u32; 2] = [1, 2];
+
+/// The `schemaVersion` manifests are upgraded to.
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+/// Line types the panel can draw; other types get a text skeleton.
+pub const LINE_TYPES: [&str; 6] = ["text", "progress", "badge", "sparkline", "table", "link"];
+
+/// Loads every plugin directory in `plugins_dir`, with a report for each one that has a
+/// `plugin.json`. Plugins with errors are left out, as is a plugin reusing an earlier id.
+pub fn load_plugins_from_dir(plugins_dir: &Path) -> (Vec<LoadedPlugin>, Vec<PluginReport>) {
+    let mut plugins: Vec<LoadedPlugin> = Vec::new();
+    let mut reports = Vec::new();
     let entries = match std::fs::read_dir(plugins_dir) {
         Ok(e) => e,
-        Err(_) => return plugins,
+        Err(_) => return (plugins, reports),
     };
 
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if !path.is_dir() {
-            continue;
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && path.join(MANIFEST_FILE).exists())
+        .collect();
+    dirs.sort();
+
+    for dir in dirs {
+        let (plugin, mut report) = load_single_plugin(&dir);
+        if let Some(plugin) = plugin {
+            if plugins.iter().any(|p| p.manifest.id == plugin.manifest.id) {
+                report
+                    .error("/id", format!("plugin id '{}' is already used by another plugin", plugin.manifest.id))
+                    .suggest("give each plugin a unique id");
+                report.loaded = false;
+            } else {
+                plugins.push(plugin);
+            }
         }
-        let manifest_path = path.join("plugin.json");
-        if !manifest_path.exists() {
+        report.log();
+        reports.push(report);
+    }
+
+    plugins.sort_by(|a, b| a.manifest.id.cmp(&b.manifest.id));
+    (plugins, reports)
+}
+
+/// Loads one plugin directory. The report lists every problem found; the plugin is `None`
+/// when any of them is an error.
+pub fn load_single_plugin(plugin_dir: &Path) -> (Option<LoadedPlugin>, PluginReport) {
+    let mut report = PluginReport::new(&plugin_dir.join(MANIFEST_FILE));
+    let plugin = read_plugin(plugin_dir, &mut report).filter(|_| !report.has_errors());
+    report.loaded = plugin.is_some();
+    (plugin, report)
+}
+
+fn read_plugin(plugin_dir: &Path, report: &mut PluginReport) -> Option<LoadedPlugin> {
+    let manifest_text = match std::fs::read_to_string(plugin_dir.join(MANIFEST_FILE)) {
+        Ok(text) => text,
+        Err(e) => {
+            report.error("", format!("cannot read {}: {}", MANIFEST_FILE, e));
+            return None;
+        }
+    };
+    let value: serde_json::Value = match serde_json::from_str(&manifest_text) {
+        Ok(value) => value,
+        Err(e) => {
+            report
+                .error("", format!("invalid JSON: {}", e))
+                .suggest("check for trailing commas and unquoted keys");
+            return None;
+        }
+    };
+    report.id = value["id"].as_str().map(str::to_string);
+
+    check_manifest_shape(&value, report);
+    if report.has_errors() {
+        return None;
+    }
+    let mut manifest = match manifest_from_value(value) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            report.error("", format!("invalid manifest: {}", e));
+            return None;
+        }
+    };
+    sanitize_manifest(&mut manifest, report);
+    check_app_version(&manifest, env!("CARGO_PKG_VERSION"), report);
+
+    let entry_script = read_entry(plugin_dir, &manifest.entry, report)?;
+    let icon_data_url = read_icon(plugin_dir, &manifest.icon, report)?;
+
+    let support = check_support(&manifest, std::env::consts::OS);
+    if let Some(reason) = support.unsupported_reason() {
+        log::info!("plugin {} unsupported: {}", manifest.id, reason);
+    }
+
+    Some(LoadedPlugin {
+        manifest,
+        plugin_dir: plugin_dir.to_path_buf(),
+        entry_script,
+        icon_data_url,
+        support,
+    })
+}
+
+fn supported_versions() -> String {
+    let versions: Vec<String> = SCHEMA_VERSIONS.iter().map(u32::to_string).collect();
+    format!("this version of OpenUsage supports schemaVersion {}", versions.join(", "))
+}
+
+/// Checks the fields that would otherwise fail deserialization with a single message, so
+/// each problem is reported with its own pointer.
+fn check_manifest_shape(value: &serde_json::Value, report: &mut PluginReport) {
+    let Some(root) = value.as_object() else {
+        report.error("", "plugin.json must contain a JSON object");
+        return;
+    };
+
+    let schema_version = root.get("schemaVersion").and_then(serde_json::Value::as_u64);
+    match root.get("schemaVersion").map(serde_json::Value::as_u64) {
+        None => {
+            report
+                .error("/schemaVersion", "missing schemaVersion")
+                .suggest(format!("add \"schemaVersion\": {}", CURRENT_SCHEMA_VERSION));
+        }
+        Some(Some(version)) if SCHEMA_VERSIONS.iter().any(|v| u64::from(*v) == version) => {}
+        Some(Some(version)) => {
+            report
+                .error("/schemaVersion", format!("unsupported schemaVersion {}", version))
+                .suggest(supported_versions());
+        }
+        Some(None) => {
+            report
+                .error("/schemaVersion", "schemaVersion must be a whole number")
+                .suggest(supported_versions());
+        }
+    }
+
+    for field in ["id", "name", "version", "entry", "icon"] {
+        let present = root
+            .get(field)
+            .and_then(serde_json::Value::as_str)
+            .is_some_and(|value| !value.trim().is_empty());
+        if !present {
+            report.error(format!("/{}", field), format!("{} must be a non-empty string", field));
+        }
+    }
+    if let Some(id) = root
+        .get("id")
+        .and_then(serde_json::Value::as_str)
+        .filter(|id| !id.trim().is_empty() && !is_valid_id(id))
+    {
+        report
+            .error("/id", format!("plugin id '{}' has characters other than [A-Za-z0-9_-]", id))
+            .suggest("use a kebab-case id like \"my-provider\"");
+    }
+    if root.get("brandColor").is_some_and(|color| !color.is_null() && !color.is_string()) {
+        report.error("/brandColor", "brandColor must be a string");
+    }
+    for field in ["links", "accounts", "settings", "platforms", "requires", "permissions"] {
+        if root.get(field).is_some_and(|value| !value.is_array()) {
+            report.error(format!("/{}", field), format!("{} must be an array", field));
+        }
+    }
+    match schema_version {
+        Some(1) => {
+            for field in ["permissions", "refreshInterval", "minAppVersion"] {
+                if root.contains_key(field) {
+                    report
+                        .warning(format!("/{}", field), format!("{} needs schemaVersion 2; ignoring", field))
+                        .suggest("set \"schemaVersion\": 2 and rename requires to permissions");
+                }
+            }
+        }
+        Some(2) if root.contains_key("requires") => {
+            report
+                .warning("/requires", "requires is called permissions since schemaVersion 2; ignoring")
+                .suggest("rename requires to permissions");
+        }
+        _ => {}
+    }
+    let valid_interval = root.get("refreshInterval").is_none_or(|minutes| {
+        minutes.is_null() || minutes.as_u64().is_some_and(|minutes| (1..=u64::from(u32::MAX)).contains(&minutes))
+    });
+    if !valid_interval {
+        report.error("/refreshInterval", "refreshInterval must be a whole number of minutes above 0");
+    }
+    if root.get("minAppVersion").is_some_and(|version| !version.is_null() && !version.is_string()) {
+        report.error("/minAppVersion", "minAppVersion must be a string");
+    }
+
+    let lines = match root.get("lines").map(serde_json::Value::as_array) {
+        Some(Some(lines)) => lines,
+        Some(None) => {
+            report.error("/lines", "lines must be an array");
+            return;
+        }
+        None => {
+            report
+                .error("/lines", "missing lines")
+                .suggest("declare the lines probe() returns, or use an empty array");
+            return;
+        }
+    };
+    for (index, line) in lines.iter().enumerate() {
+        let pointer = format!("/lines/{}", index);
+        let Some(line) = line.as_object() else {
+            report.error(pointer, "line must be an object");
             continue;
+        };
+        for field in ["type", "label"] {
+            if !line.get(field).is_some_and(serde_json::Value::is_string) {
+                report.error(format!("{}/{}", pointer, field), format!("{} must be a string", field));
+            }
         }
-        if let Ok(p) = load_single_plugin(&path) {
-            plugins.push(p);
+        if line.get("id").is_some_and(|id| !id.is_string()) {
+            report.error(format!("{}/id", pointer), "id must be a string");
+        }
+        match line.get("scope").map(serde_json::Value::as_str) {
+            Some(Some("overview" | "detail")) => {}
+            scope => {
+                let message = match scope {
+                    Some(Some(scope)) => format!("unknown scope '{}'", scope),
+                    _ => "scope must be a string".to_string(),
+                };
+                report
+                    .error(format!("{}/scope", pointer), message)
+                    .suggest("use \"overview\" or \"detail\"");
+            }
+        }
+        let valid_order = line.get("primaryOrder").is_none_or(|order| {
+            order.is_null() || order.as_u64().is_some_and(|order| order <= u64::from(u32::MAX))
+        });
+        if !valid_order {
+            report.error(
+                format!("{}/primaryOrder", pointer),
+                "primaryOrder must be a whole number",
+            );
+        }
+        let valid_colors = line.get("segmentColors").is_none_or(|colors| {
+            colors
+                .as_object()
+                .is_some_and(|colors| colors.values().all(serde_json::Value::is_string))
+        });
+        if !valid_colors {
+            report
+                .error(format!("{}/segmentColors", pointer), "segmentColors must be an object of color strings")
+                .suggest("key colors by segment label or kind, e.g. { \"overage\": \"#f97316\" }");
         }
     }
+}
 
-    plugins.sort_by(|a, b| a.manifest.id.cmp(&b.manifest.id));
-    plugins
+/// `Vec::retain_mut` that also passes each element's original index, for diagnostics.
+fn retain_indexed<T>(items: &mut Vec<T>, mut keep: impl FnMut(usize, &mut T) -> bool) {
+    let mut index = 0;
+    items.retain_mut(|item| {
+        let kept = keep(index, item);
+        index += 1;
+        kept
+    });
 }
 
-fn load_single_plugin(
-    plugin_dir: &std::path::Path,
-) -> Result<LoadedPlugin, Box<dyn std::error::Error>> {
-    let manifest_path = plugin_dir.join("plugin.json");
-    let manifest_text = std::fs::read_to_string(&manifest_path)?;
-    let manifest: PluginManifest = serde_json::from_str(&manifest_text)?;
+/// `#rrggbb`, the only color form the panel and tray both accept.
+pub fn is_hex_color(value: &str) -> bool {
+    value.starts_with('#') && parse_hex_color(value).is_some()
+}
 
-    // Validate primary_order: only progress lines can have it
-    for line in manifest.lines.iter() {
+/// Drops the parts of a parsed manifest the app cannot use, with a warning for each.
+fn sanitize_manifest(manifest: &mut PluginManifest, report: &mut PluginReport) {
+    if let Some(color) = manifest
+        .brand_color
+        .take_if(|color| !is_hex_color(color))
+    {
+        report
+            .warning("/brandColor", format!("brandColor '{}' is not a #rrggbb color; using the default", color))
+            .suggest("use a hex color like \"#DE7356\"");
+    }
+
+    let mut line_ids = HashSet::new();
+    let mut labels = HashSet::new();
+    retain_indexed(&mut manifest.lines, |index, line| {
+        let pointer = format!("/lines/{}", index);
+        if line.id.is_empty() {
+            line.id = line_id_from_label(&line.label);
+            if line.id.is_empty() {
+                report
+                    .warning(pointer, format!("no id can be derived from label '{}'; ignoring the line", line.label))
+                    .suggest("set an id");
+                return false;
+            }
+        } else if !is_valid_id(&line.id) {
+            report
+                .warning(format!("{}/id", pointer), format!("line id '{}' is invalid; ignoring the line", line.id))
+                .suggest("use letters, digits, '-' and '_' only");
+            return false;
+        }
+        if !line_ids.insert(line.id.clone()) {
+            report
+                .warning(pointer, format!("line id '{}' is duplicated; ignoring the line", line.id))
+                .suggest("give each line a unique id");
+            return false;
+        }
+        if !labels.insert(line.label.clone()) {
+            report
+                .warning(format!("{}/label", pointer), format!("label '{}' is used by another line", line.label))
+                .suggest("give each line its own label, or return ids from probe()");
+        }
+        if !LINE_TYPES.contains(&line.line_type.as_str()) {
+            report
+                .warning(format!("{}/type", pointer), format!("unknown line type '{}'; shown as text", line.line_type))
+                .suggest(format!("use one of {}", LINE_TYPES.join(", ")));
+        }
         if line.primary_order.is_some() && line.line_type != "progress" {
-            log::warn!(
-                "plugin {} line '{}' has primaryOrder but type is '{}'; will be ignored",
-                manifest.id,
-                line.label,
-                line.line_type
+            report.warning(
+                format!("{}/primaryOrder", pointer),
+                format!("primaryOrder is only used on progress lines, not '{}'; ignoring", line.line_type),
             );
         }
-    }
+        if !line.segment_colors.is_empty() && line.line_type != "progress" {
+            report.warning(
+                format!("{}/segmentColors", pointer),
+                format!("segmentColors is only used on progress lines, not '{}'; ignoring", line.line_type),
+            );
+            line.segment_colors.clear();
+        }
+        if line.spend && line.line_type != "progress" {
+            report.warning(
+                format!("{}/spend", pointer),
+                format!("spend is only used on progress lines, not '{}'; ignoring", line.line_type),
+            );
+            line.spend = false;
+        }
+        line.segment_colors.retain(|segment, color| {
+            let valid = is_hex_color(color);
+            if !valid {
+                report
+                    .warning(
+                        format!("{}/segmentColors/{}", pointer, pointer_segment(segment)),
+                        format!("segment color '{}' is not #rrggbb; ignoring", color),
+                    )
+                    .suggest("use a hex color like \"#22c55e\"");
+            }
+            valid
+        });
+        true
+    });
 
-    if manifest.entry.trim().is_empty() {
-        return Err("plugin entry field cannot be empty".into());
+    // Links are opened in the system browser, so only allow web URLs
+    retain_indexed(&mut manifest.links, |index, link| {
+        let is_web = link.url.starts_with("https://") || link.url.starts_with("http://");
+        if !is_web {
+            report
+                .warning(
+                    format!("/links/{}/url", index),
+                    format!("link '{}' is not an http(s) URL; ignoring", link.label),
+                )
+                .suggest("use an https:// URL");
+        }
+        is_web
+    });
+
+    let mut account_ids = HashSet::new();
+    retain_indexed(&mut manifest.accounts, |index, account| {
+        let keep = is_valid_id(&account.id) && account_ids.insert(account.id.clone());
+        if !keep {
+            report
+                .warning(
+                    format!("/accounts/{}/id", index),
+                    format!("account id '{}' is invalid or duplicated; ignoring", account.id),
+                )
+                .suggest("use a unique id of letters, digits, '-' and '_'");
+        }
+        keep
+    });
+
+    let mut setting_keys = HashSet::new();
+    retain_indexed(&mut manifest.settings, |index, field| {
+        if !setting_keys.insert(field.key.clone()) {
+            report.warning(
+                format!("/settings/{}/key", index),
+                format!("setting '{}' is duplicated; ignoring", field.key),
+            );
+            return false;
+        }
+        match settings::validate_field(field) {
+            Ok(()) => true,
+            Err(e) => {
+                report.warning(format!("/settings/{}", index), format!("setting ignored: {}", e));
+                false
+            }
+        }
+    });
+
+    for (index, platform) in manifest.platforms.iter().enumerate() {
+        if !PLATFORMS.contains(&platform.as_str()) {
+            report
+                .warning(format!("/platforms/{}", index), format!("platform '{}' is unknown", platform))
+                .suggest(format!("use one of {}", PLATFORMS.join(", ")));
+        }
     }
-    if Path::new(&manifest.entry).is_absolute() {
-        return Err("plugin entry must be a relative path".into());
+}
+
+/// Reports an error when the plugin needs a newer app than `app_version`.
+fn check_app_version(manifest: &PluginManifest, app_version: &str, report: &mut PluginReport) {
+    let Some(required) = &manifest.min_app_version else {
+        return;
+    };
+    let Some(min) = parse_app_version(required) else {
+        report
+            .error("/minAppVersion", format!("minAppVersion '{}' is not a version", required))
+            .suggest("use a version like \"0.6.0\"");
+        return;
+    };
+    if parse_app_version(app_version).is_some_and(|running| running < min) {
+        report
+            .error(
+                "/minAppVersion",
+                format!("{} needs OpenUsage {} or later; this is {}", manifest.name, required, app_version),
+            )
+            .suggest("update OpenUsage");
     }
+}
 
-    let entry_path = plugin_dir.join(&manifest.entry);
-    let canonical_plugin_dir = plugin_dir.canonicalize()?;
-    let canonical_entry_path = entry_path.canonicalize()?;
-    if !canonical_entry_path.starts_with(&canonical_plugin_dir) {
-        return Err("plugin entry must remain within plugin directory".into());
+/// Canonical path of a file named by the manifest field at `pointer`; it must be a file
+/// inside the plugin directory.
+fn resolve_plugin_file(
+    plugin_dir: &Path,
+    relative: &str,
+    pointer: &str,
+    report: &mut PluginReport,
+) -> Option<PathBuf> {
+    if Path::new(relative).is_absolute() {
+        report
+            .error(pointer, format!("{} must be a relative path", relative))
+            .suggest("use a path relative to the plugin directory");
+        return None;
     }
-    if !canonical_entry_path.is_file() {
-        return Err("plugin entry must be a file".into());
+    let canonical_dir = match plugin_dir.canonicalize() {
+        Ok(dir) => dir,
+        Err(e) => {
+            report.error("", format!("cannot resolve plugin directory: {}", e));
+            return None;
+        }
+    };
+    let Ok(path) = plugin_dir.join(relative).canonicalize() else {
+        report.error(pointer, format!("{} does not exist", relative));
+        return None;
+    };
+    if !path.starts_with(&canonical_dir) {
+        report.error(pointer, format!("{} must remain within the plugin directory", relative));
+        return None;
     }
+    if !path.is_file() {
+        report.error(pointer, format!("{} must be a file", relative));
+        return None;
+    }
+    Some(path)
+}
 
-    let entry_script = std::fs::read_to_string(&canonical_entry_path)?;
-
-    let icon_file = plugin_dir.join(&manifest.icon);
-    let icon_bytes = std::fs::read(&icon_file)?;
-    let icon_data_url = format!("data:image/svg+xml;base64,{}", STANDARD.encode(&icon_bytes));
+fn read_entry(plugin_dir: &Path, entry: &str, report: &mut PluginReport) -> Option<String> {
+    let path = resolve_plugin_file(plugin_dir, entry, "/entry", report)?;
+    match std::fs::read_to_string(&path) {
+        Ok(script) => Some(script),
+        Err(e) => {
+            report.error("/entry", format!("cannot read {}: {}", entry, e));
+            None
+        }
+    }
+}
 
-    Ok(LoadedPlugin {
-        manifest,
-        plugin_dir: plugin_dir.to_path_buf(),
-        entry_script,
-        icon_data_url,
-    })
+/// The icon as a data URL. Only SVG is accepted so icons can follow the theme color.
+fn read_icon(plugin_dir: &Path, icon: &str, report: &mut PluginReport) -> Option<String> {
+    let path = resolve_plugin_file(plugin_dir, icon, "/icon", report)?;
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            report.error("/icon", format!("cannot read {}: {}", icon, e));
+            return None;
+        }
+    };
+    let is_svg = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+        && std::str::from_utf8(&bytes).is_ok_and(|text| text.contains("<svg"));
+    if !is_svg {
+        report
+            .error("/icon", format!("{} is not an SVG file", icon))
+            .suggest("use an SVG icon with fill=\"currentColor\"");
+        return None;
+    }
+    Some(format!("data:image/svg+xml;base64,{}", STANDARD.encode(&bytes)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::plugin_engine::diagnostics::Severity;
+    use crate::plugin_engine::test_dir;
 
     fn parse_manifest(json: &str) -> PluginManifest {
-        serde_json::from_str::<PluginManifest>(json).expect("manifest parse failed")
+        manifest_from_value(serde_json::from_str(json).expect("invalid JSON")).expect("manifest parse failed")
     }
 
     #[test]
@@ -179,24 +910,443 @@ mod tests {
               "icon": "icon.svg",
               "brandColor": null,
               "lines": [
-                { "type": "progress", "label": "Third", "scope": "overview", "primaryOrder": 3 },
-                { "type": "progress", "label": "First", "scope": "overview", "primaryOrder": 1 },
-                { "type": "progress", "label": "Second", "scope": "overview", "primaryOrder": 2 },
-                { "type": "progress", "label": "None", "scope": "overview" }
+                { "id": "third", "type": "progress", "label": "Third", "scope": "overview", "primaryOrder": 3 },
+                { "id": "first", "type": "progress", "label": "First", "scope": "overview", "primaryOrder": 1 },
+                { "id": "second", "type": "progress", "label": "Second", "scope": "overview", "primaryOrder": 2 },
+                { "id": "none", "type": "progress", "label": "None", "scope": "overview" }
               ]
             }
             "#,
         );
 
-        // Extract candidates sorted by primary_order (same logic as lib.rs)
-        let mut candidates: Vec<_> = manifest
-            .lines
+        assert_eq!(manifest.primary_candidates(), vec!["first", "second", "third"]);
+    }
+
+    #[test]
+    fn links_default_to_empty() {
+        let manifest = parse_manifest(
+            r#"
+            {
+              "schemaVersion": 1,
+              "id": "x",
+              "name": "X",
+              "version": "0.0.1",
+              "entry": "plugin.js",
+              "icon": "icon.svg",
+              "brandColor": null,
+              "lines": []
+            }
+            "#,
+        );
+        assert!(manifest.links.is_empty());
+    }
+
+    #[test]
+    fn links_parsed_correctly() {
+        let manifest = parse_manifest(
+            r#"
+            {
+              "schemaVersion": 1,
+              "id": "x",
+              "name": "X",
+              "version": "0.0.1",
+              "entry": "plugin.js",
+              "icon": "icon.svg",
+              "brandColor": null,
+              "lines": [],
+              "links": [{ "label": "Dashboard", "url": "https://example.com/usage" }]
+            }
+            "#,
+        );
+        assert_eq!(manifest.links.len(), 1);
+        assert_eq!(manifest.links[0].label, "Dashboard");
+        assert_eq!(manifest.links[0].url, "https://example.com/usage");
+    }
+
+    #[test]
+    fn account_ids_are_restricted_to_path_safe_chars() {
+        assert!(is_valid_id("work"));
+        assert!(is_valid_id("personal_2-b"));
+        assert!(!is_valid_id(""));
+        assert!(!is_valid_id("../work"));
+        assert!(!is_valid_id("a b"));
+    }
+
+    #[test]
+    fn merge_accounts_replaces_by_id_and_skips_invalid() {
+        let mut manifest = parse_manifest(
+            r#"
+            {
+              "schemaVersion": 1,
+              "id": "x",
+              "name": "X",
+              "version": "0.0.1",
+              "entry": "plugin.js",
+              "icon": "icon.svg",
+              "brandColor": null,
+              "lines": [],
+              "accounts": [{ "id": "work", "label": "Work", "env": { "CODEX_HOME": "~/.codex-work" } }]
+            }
+            "#,
+        );
+        assert_eq!(manifest.accounts[0].env["CODEX_HOME"], "~/.codex-work");
+
+        let profile = |id: &str, label: &str| AccountProfile {
+            id: id.to_string(),
+            label: label.to_string(),
+            env: HashMap::new(),
+            paths: HashMap::new(),
+        };
+        manifest.merge_accounts(vec![
+            profile("work", "Work (renamed)"),
+            profile("personal", "Personal"),
+            profile("../bad", "Bad"),
+        ]);
+
+        let labels: Vec<_> = manifest.accounts.iter().map(|a| a.label.as_str()).collect();
+        assert_eq!(labels, vec!["Work (renamed)", "Personal"]);
+        assert!(manifest.accounts[0].env.is_empty());
+    }
+
+    #[test]
+    fn check_support_honors_platforms_and_requires() {
+        let manifest = parse_manifest(
+            r#"
+            {
+              "schemaVersion": 1,
+              "id": "x",
+              "name": "X",
+              "version": "0.0.1",
+              "entry": "plugin.js",
+              "icon": "icon.svg",
+              "brandColor": null,
+              "lines": [],
+              "platforms": ["macos", "windows"],
+              "requires": ["sqlite", "keychain"]
+            }
+            "#,
+        );
+        assert_eq!(check_support(&manifest, "macos"), PluginSupport::Supported);
+        assert_eq!(
+            check_support(&manifest, "linux").unsupported_reason(),
+            Some("X is only available on macOS, Windows.")
+        );
+        assert_eq!(
+            check_support(&manifest, "windows").unsupported_reason(),
+            Some("X requires keychain, which is not available on Windows.")
+        );
+
+        let mut future = manifest.clone();
+        future.platforms.clear();
+        future.permissions = vec!["gpu-telemetry".to_string()];
+        assert!(check_support(&future, "macos")
+            .unsupported_reason()
+            .is_some_and(|reason| reason.contains("does not provide")));
+    }
+
+    #[test]
+    fn v1_manifests_are_upgraded() {
+        let manifest = parse_manifest(
+            r#"{ "schemaVersion": 1, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.svg", "lines": [],
+                 "requires": ["sqlite"] }"#,
+        );
+        assert_eq!(manifest.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(manifest.permissions, vec!["sqlite"]);
+        assert_eq!(manifest.refresh_interval, None);
+
+        let manifest = parse_manifest(
+            r#"{ "schemaVersion": 2, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.svg", "lines": [],
+                 "permissions": ["http"], "refreshInterval": 30, "minAppVersion": "0.6.0" }"#,
+        );
+        assert_eq!(manifest.permissions, vec!["http"]);
+        assert_eq!(manifest.refresh_interval, Some(30));
+        assert_eq!(manifest.min_app_version.as_deref(), Some("0.6.0"));
+    }
+
+    #[test]
+    fn app_versions_compare_numerically() {
+        assert_eq!(parse_app_version("0.6.5"), Some((0, 6, 5)));
+        assert_eq!(parse_app_version("v1.2"), Some((1, 2, 0)));
+        assert_eq!(parse_app_version("0.7.0-beta.1"), Some((0, 7, 0)));
+        assert_eq!(parse_app_version("1.2.3.4"), None);
+        assert_eq!(parse_app_version("latest"), None);
+        assert!(parse_app_version("0.10.0") > parse_app_version("0.9.3"));
+    }
+
+    #[test]
+    fn plugins_needing_a_newer_app_are_reported() {
+        let dir = write_plugin(
+            test_dir("min-app-version"),
+            r#"{ "schemaVersion": 2, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.svg", "lines": [],
+                 "minAppVersion": "999.0.0" }"#,
+            ("icon.svg", "<svg/>"),
+        );
+        let (plugin, report) = load_single_plugin(&dir);
+        assert!(plugin.is_none());
+        assert_eq!(problems(&report), vec![(Severity::Error, "/minAppVersion")]);
+        assert_eq!(report.diagnostics[0].suggestion.as_deref(), Some("update OpenUsage"));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut manifest = parse_manifest(
+            r#"{ "schemaVersion": 2, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.svg", "lines": [], "minAppVersion": "0.6.0" }"#,
+        );
+        let mut report = PluginReport::new(Path::new("plugin.json"));
+        check_app_version(&manifest, "0.6.5", &mut report);
+        check_app_version(&manifest, "0.6.0", &mut report);
+        assert!(report.diagnostics.is_empty());
+        check_app_version(&manifest, "0.5.9", &mut report);
+        manifest.min_app_version = Some("soon".to_string());
+        check_app_version(&manifest, "0.6.5", &mut report);
+        assert_eq!(
+            problems(&report),
+            vec![(Severity::Error, "/minAppVersion"), (Severity::Error, "/minAppVersion")]
+        );
+    }
+
+    #[test]
+    fn fields_of_the_other_schema_version_are_ignored_with_a_warning() {
+        let dir = write_plugin(
+            test_dir("v1-with-v2-fields"),
+            r#"{ "schemaVersion": 1, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.svg", "lines": [],
+                 "minAppVersion": "999.0.0" }"#,
+            ("icon.svg", "<svg/>"),
+        );
+        let (plugin, report) = load_single_plugin(&dir);
+        assert!(plugin.expect("load plugin").manifest.min_app_version.is_none());
+        assert_eq!(problems(&report), vec![(Severity::Warning, "/minAppVersion")]);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let dir = write_plugin(
+            test_dir("v2-with-requires"),
+            r#"{ "schemaVersion": 2, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.svg", "lines": [],
+                 "requires": ["sqlite"], "refreshInterval": 0 }"#,
+            ("icon.svg", "<svg/>"),
+        );
+        let (plugin, report) = load_single_plugin(&dir);
+        assert!(plugin.is_none());
+        assert_eq!(
+            problems(&report),
+            vec![(Severity::Warning, "/requires"), (Severity::Error, "/refreshInterval")]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn load_plugins_from_dir_tags_support() {
+        let root = test_dir("manifest");
+        for (id, platforms) in [("anywhere", "[]"), ("elsewhere", r#"["plan9"]"#)] {
+            let manifest = format!(
+                r#"{{ "schemaVersion": 1, "id": "{id}", "name": "{id}", "version": "0.0.1",
+                     "entry": "plugin.js", "icon": "icon.svg", "lines": [], "platforms": {platforms} }}"#
+            );
+            write_plugin(root.join(id), &manifest, ("icon.svg", "<svg/>"));
+        }
+
+        let (plugins, reports) = load_plugins_from_dir(&root);
+        assert_eq!(plugins.len(), 2);
+        assert!(reports.iter().all(|report| report.loaded));
+        assert_eq!(plugins[0].support, PluginSupport::Supported);
+        assert!(plugins[1].support.unsupported_reason().is_some());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn invalid_segment_colors_are_dropped() {
+        let dir = write_plugin(
+            test_dir("segment-colors"),
+            r##"{ "schemaVersion": 1, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.svg",
+                 "lines": [{ "type": "progress", "label": "Usage", "scope": "overview",
+                             "segmentColors": { "included": "#22c55e", "reserved": "22c55e", "overage": "red" } }] }"##,
+            ("icon.svg", "<svg/>"),
+        );
+
+        let plugin = load_single_plugin(&dir).0.expect("load plugin");
+        let colors: Vec<_> = plugin.manifest.lines[0].segment_colors.iter().collect();
+        assert_eq!(colors, vec![(&"included".to_string(), &"#22c55e".to_string())]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn line_ids_default_to_the_label() {
+        assert_eq!(line_id_from_label("Extra usage"), "extra-usage");
+        assert_eq!(line_id_from_label("Claude Opus 4.5"), "claude-opus-4-5");
+        assert_eq!(line_id_from_label("Over limit!"), "over-limit");
+        assert_eq!(line_id_from_label("!!"), "");
+
+        let dir = write_plugin(
+            test_dir("line-ids"),
+            r#"{ "schemaVersion": 1, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.svg",
+                 "lines": [
+                   { "id": "session", "type": "progress", "label": "5h window", "scope": "overview" },
+                   { "type": "text", "label": "Plan tier", "scope": "detail" },
+                   { "type": "text", "label": "Session", "scope": "detail" },
+                   { "id": "bad id", "type": "text", "label": "Bad", "scope": "detail" }
+                 ] }"#,
+            ("icon.svg", "<svg/>"),
+        );
+
+        let plugin = load_single_plugin(&dir).0.expect("load plugin");
+        let ids: Vec<_> = plugin.manifest.lines.iter().map(|line| line.id.as_str()).collect();
+        assert_eq!(ids, vec!["session", "plan-tier"]);
+        assert_eq!(plugin.manifest.lines[1].scope, LineScope::Detail);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unknown_line_scope_is_rejected() {
+        let result = serde_json::from_str::<PluginManifest>(
+            r#"{ "schemaVersion": 1, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.svg", "brandColor": null,
+                 "lines": [{ "type": "text", "label": "A", "scope": "sidebar" }] }"#,
+        );
+        assert!(result.is_err());
+    }
+
+    fn write_plugin(dir: PathBuf, manifest: &str, icon: (&str, &str)) -> PathBuf {
+        std::fs::create_dir_all(&dir).expect("create plugin dir");
+        std::fs::write(dir.join("plugin.js"), "globalThis.__openusage_plugin = {}").unwrap();
+        std::fs::write(dir.join(icon.0), icon.1).unwrap();
+        std::fs::write(dir.join("plugin.json"), manifest).unwrap();
+        dir
+    }
+
+    fn problems(report: &PluginReport) -> Vec<(Severity, &str)> {
+        report
+            .diagnostics
             .iter()
-            .filter(|l| l.line_type == "progress" && l.primary_order.is_some())
-            .collect();
-        candidates.sort_by_key(|l| l.primary_order.unwrap());
-        let labels: Vec<_> = candidates.iter().map(|l| l.label.as_str()).collect();
+            .map(|diagnostic| (diagnostic.severity, diagnostic.pointer.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn validation_reports_every_error() {
+        let dir = write_plugin(
+            test_dir("invalid-manifest"),
+            r#"{ "schemaVersion": 3, "id": "x", "version": "0.0.1", "entry": "plugin.js",
+                 "icon": "icon.svg",
+                 "lines": [{ "type": "text", "label": "A", "scope": "sidebar" }, "B"] }"#,
+            ("icon.svg", "<svg/>"),
+        );
+        let (plugin, report) = load_single_plugin(&dir);
+        assert!(plugin.is_none());
+        assert!(!report.loaded);
+        assert_eq!(report.id.as_deref(), Some("x"));
+        assert_eq!(
+            problems(&report),
+            vec![
+                (Severity::Error, "/schemaVersion"),
+                (Severity::Error, "/name"),
+                (Severity::Error, "/lines/0/scope"),
+                (Severity::Error, "/lines/1"),
+            ]
+        );
+        assert_eq!(
+            report.diagnostics[0].suggestion.as_deref(),
+            Some("this version of OpenUsage supports schemaVersion 1, 2")
+        );
+        assert!(report.diagnostics[0].file.ends_with("plugin.json"));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let dir = write_plugin(
+            test_dir("png-icon"),
+            r#"{ "schemaVersion": 1, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.png", "lines": [] }"#,
+            ("icon.png", "\u{89}PNG"),
+        );
+        let (plugin, report) = load_single_plugin(&dir);
+        assert!(plugin.is_none());
+        assert_eq!(problems(&report), vec![(Severity::Error, "/icon")]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 
-        assert_eq!(labels, vec!["First", "Second", "Third"]);
+    #[test]
+    fn validation_warnings_keep_the_plugin() {
+        let dir = write_plugin(
+            test_dir("manifest-warnings"),
+            r#"{ "schemaVersion": 1, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.svg", "brandColor": "orange",
+                 "lines": [
+                   { "type": "progress", "label": "Usage", "scope": "overview" },
+                   { "id": "usage-2", "type": "gauge", "label": "Usage", "scope": "detail" },
+                   { "type": "text", "label": "Usage!", "scope": "detail" }
+                 ] }"#,
+            ("icon.svg", "<svg/>"),
+        );
+        let (plugin, report) = load_single_plugin(&dir);
+        let plugin = plugin.expect("load plugin");
+        assert!(report.loaded);
+        assert_eq!(plugin.manifest.brand_color, None);
+        assert_eq!(plugin.manifest.lines.len(), 2);
+        assert_eq!(
+            problems(&report),
+            vec![
+                (Severity::Warning, "/brandColor"),
+                (Severity::Warning, "/lines/1/label"),
+                (Severity::Warning, "/lines/1/type"),
+                (Severity::Warning, "/lines/2"),
+            ]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn duplicate_plugin_ids_are_not_loaded() {
+        let root = test_dir("duplicate-ids");
+        for name in ["a", "b"] {
+            write_plugin(
+                root.join(name),
+                r#"{ "schemaVersion": 1, "id": "same", "name": "Same", "version": "0.0.1",
+                     "entry": "plugin.js", "icon": "icon.svg", "lines": [] }"#,
+                ("icon.svg", "<svg/>"),
+            );
+        }
+        let (plugins, reports) = load_plugins_from_dir(&root);
+        assert_eq!(plugins.len(), 1);
+        assert!(plugins[0].plugin_dir.ends_with("a"));
+        assert!(!reports[1].loaded);
+        assert_eq!(problems(&reports[1]), vec![(Severity::Error, "/id")]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn spend_is_dropped_on_non_progress_lines() {
+        let dir = write_plugin(
+            test_dir("spend-lines"),
+            r#"{ "schemaVersion": 1, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.svg",
+                 "lines": [{ "type": "progress", "label": "Usage", "scope": "overview", "spend": true },
+                           { "type": "text", "label": "Note", "scope": "detail", "spend": true }] }"#,
+            ("icon.svg", "<svg/>"),
+        );
+        let (plugin, report) = load_single_plugin(&dir);
+        let plugin = plugin.expect("load plugin");
+        assert!(plugin.manifest.lines[0].spend);
+        assert!(!plugin.manifest.lines[1].spend);
+        assert_eq!(problems(&report), vec![(Severity::Warning, "/lines/1/spend")]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn segment_colors_are_cleared_on_non_progress_lines() {
+        let dir = write_plugin(
+            test_dir("segment-colors-text"),
+            r##"{ "schemaVersion": 1, "id": "x", "name": "X", "version": "0.0.1",
+                 "entry": "plugin.js", "icon": "icon.svg",
+                 "lines": [{ "type": "text", "label": "Note", "scope": "detail",
+                             "segmentColors": { "included": "#22c55e" } }] }"##,
+            ("icon.svg", "<svg/>"),
+        );
+        let (plugin, report) = load_single_plugin(&dir);
+        assert!(plugin.expect("load plugin").manifest.lines[0].segment_colors.is_empty());
+        assert_eq!(problems(&report), vec![(Severity::Warning, "/lines/0/segmentColors")]);
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
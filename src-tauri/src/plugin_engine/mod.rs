@@ -1,18 +1,35 @@
+pub mod budget;
+pub mod currency;
+pub mod diagnostics;
+pub mod files;
+pub mod history;
 pub mod host_api;
+pub mod humanize;
+pub mod ide;
+pub mod logs;
+pub mod ls_discovery;
 pub mod manifest;
+pub mod paths;
+pub mod pricing;
+pub mod provider;
 pub mod runtime;
+pub mod secrets;
+pub mod session_logs;
+pub mod settings;
+pub mod sqlite;
 
+use diagnostics::PluginReport;
 use manifest::LoadedPlugin;
 use std::path::{Path, PathBuf};
 
 pub fn initialize_plugins(
     app_data_dir: &Path,
     resource_dir: &Path,
-) -> (PathBuf, Vec<LoadedPlugin>) {
+) -> (PathBuf, Vec<LoadedPlugin>, Vec<PluginReport>) {
     if let Some(dev_dir) = find_dev_plugins_dir() {
         if !is_dir_empty(&dev_dir) {
-            let plugins = manifest::load_plugins_from_dir(&dev_dir);
-            return (dev_dir, plugins);
+            let (plugins, reports) = manifest::load_plugins_from_dir(&dev_dir);
+            return (dev_dir, plugins, reports);
         }
     }
 
@@ -26,8 +43,8 @@ pub fn initialize_plugins(
         copy_dir_recursive(&bundled_dir, &install_dir);
     }
 
-    let plugins = manifest::load_plugins_from_dir(&install_dir);
-    (install_dir, plugins)
+    let (plugins, reports) = manifest::load_plugins_from_dir(&install_dir);
+    (install_dir, plugins, reports)
 }
 
 fn find_dev_plugins_dir() -> Option<PathBuf> {
@@ -112,3 +129,15 @@ fn copy_dir_recursive(src: &Path, dst: &Path) {
         }
     }
 }
+
+/// Fresh empty directory under the system temp dir, unique to one test.
+#[cfg(test)]
+pub(crate) fn test_dir(label: &str) -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("openusage-test-{}-{}", label, nanos));
+    std::fs::create_dir_all(&dir).expect("create test dir");
+    dir
+}
@@ -0,0 +1,195 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Environment variables that may appear in plugin paths. Only directory locations are
+/// expanded, so paths cannot be used to read arbitrary environment values.
+const PATH_ENV_VARS: [&str; 13] = [
+    "HOME",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PROGRAMDATA",
+    "XDG_CONFIG_HOME",
+    "XDG_DATA_HOME",
+    "XDG_CACHE_HOME",
+    "XDG_STATE_HOME",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "CODEX_HOME",
+];
+
+/// Directory lookups behind path expansion; the system implementation uses `dirs` and the
+/// process environment.
+pub trait PathResolver {
+    fn token(&self, name: &str) -> Option<PathBuf>;
+    fn env(&self, name: &str) -> Option<String>;
+}
+
+struct SystemPaths;
+
+impl PathResolver for SystemPaths {
+    fn token(&self, name: &str) -> Option<PathBuf> {
+        match name {
+            "home" => dirs::home_dir(),
+            "config" => dirs::config_dir(),
+            "data" => dirs::data_dir(),
+            "cache" => dirs::cache_dir(),
+            // Where desktop (Electron) apps keep user data: ~/Library/Application Support,
+            // %APPDATA% or ~/.config
+            "appSupport" => dirs::config_dir(),
+            _ => None,
+        }
+    }
+
+    fn env(&self, name: &str) -> Option<String> {
+        std::env::var(name)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .or_else(|| env_fallback(name).map(|path| path.to_string_lossy().to_string()))
+    }
+}
+
+/// Platform default for a directory variable that is unset, e.g. `$XDG_CONFIG_HOME` on macOS
+/// or `%APPDATA%` outside Windows.
+fn env_fallback(name: &str) -> Option<PathBuf> {
+    match name {
+        "HOME" | "USERPROFILE" => dirs::home_dir(),
+        "APPDATA" => dirs::config_dir(),
+        "LOCALAPPDATA" => dirs::data_local_dir(),
+        "XDG_CONFIG_HOME" => dirs::home_dir().map(|home| home.join(".config")),
+        "XDG_DATA_HOME" => dirs::home_dir().map(|home| home.join(".local").join("share")),
+        "XDG_CACHE_HOME" => dirs::home_dir().map(|home| home.join(".cache")),
+        "XDG_STATE_HOME" => dirs::home_dir().map(|home| home.join(".local").join("state")),
+        "TMPDIR" | "TEMP" | "TMP" => Some(std::env::temp_dir()),
+        _ => None,
+    }
+}
+
+/// Expand a plugin-supplied path for `host.fs`, `host.sqlite` and `host.ide`:
+/// - `~`, `~/...` and `~\...` to the home directory
+/// - `{home}`, `{config}`, `{data}`, `{cache}`, `{appSupport}` to per-OS directories
+/// - `%NAME%`, `$NAME` and `${NAME}` for directory environment variables
+///
+/// Unknown tokens and variables are left as written.
+pub fn expand_path(path: &str) -> String {
+    expand_with(path, &SystemPaths)
+}
+
+pub fn expand_with(path: &str, resolver: &dyn PathResolver) -> String {
+    static PATTERN: OnceLock<regex_lite::Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        regex_lite::Regex::new(
+            r"\{([A-Za-z]+)\}|%([A-Za-z_][A-Za-z0-9_]*)%|\$\{([A-Za-z_][A-Za-z0-9_]*)\}|\$([A-Za-z_][A-Za-z0-9_]*)",
+        )
+        .expect("path token pattern")
+    });
+
+    let path = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            match resolver.token("home") {
+                Some(home) => format!("{}{}", home.to_string_lossy(), rest),
+                None => path.to_string(),
+            }
+        }
+        _ => path.to_string(),
+    };
+
+    pattern
+        .replace_all(&path, |caps: &regex_lite::Captures<'_>| {
+            let whole = caps[0].to_string();
+            if let Some(token) = caps.get(1) {
+                return resolver
+                    .token(token.as_str())
+                    .map(|dir| dir.to_string_lossy().to_string())
+                    .unwrap_or(whole);
+            }
+            // %NAME% follows Windows and ignores case; $NAME is case-sensitive
+            let name = match caps.get(2) {
+                Some(m) => m.as_str().to_ascii_uppercase(),
+                None => caps
+                    .get(3)
+                    .or_else(|| caps.get(4))
+                    .map(|m| m.as_str().to_string())
+                    .unwrap_or_default(),
+            };
+            if !PATH_ENV_VARS.contains(&name.as_str()) {
+                return whole;
+            }
+            resolver.env(&name).unwrap_or(whole)
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct FakePaths {
+        tokens: HashMap<&'static str, &'static str>,
+        env: HashMap<&'static str, &'static str>,
+    }
+
+    impl PathResolver for FakePaths {
+        fn token(&self, name: &str) -> Option<PathBuf> {
+            self.tokens.get(name).map(PathBuf::from)
+        }
+
+        fn env(&self, name: &str) -> Option<String> {
+            self.env.get(name).map(|value| value.to_string())
+        }
+    }
+
+    fn windows() -> FakePaths {
+        FakePaths {
+            tokens: HashMap::from([
+                ("home", r"C:\Users\me"),
+                ("config", r"C:\Users\me\AppData\Roaming"),
+                ("appSupport", r"C:\Users\me\AppData\Roaming"),
+                ("cache", r"C:\Users\me\AppData\Local"),
+            ]),
+            env: HashMap::from([
+                ("APPDATA", r"C:\Users\me\AppData\Roaming"),
+                ("SECRET_TOKEN", "hunter2"),
+            ]),
+        }
+    }
+
+    #[test]
+    fn expands_home_and_directory_tokens() {
+        let fake = windows();
+        assert_eq!(expand_with("~", &fake), r"C:\Users\me");
+        assert_eq!(expand_with("~/.claude/creds.json", &fake), r"C:\Users\me/.claude/creds.json");
+        assert_eq!(
+            expand_with("{appSupport}/Cursor/User/globalStorage/state.vscdb", &fake),
+            r"C:\Users\me\AppData\Roaming/Cursor/User/globalStorage/state.vscdb"
+        );
+        assert_eq!(expand_with("{cache}/x", &fake), r"C:\Users\me\AppData\Local/x");
+        // Unresolvable and unknown tokens stay as written
+        assert_eq!(expand_with("{data}/{nope}/~", &fake), "{data}/{nope}/~");
+        assert_eq!(expand_with("~user/file", &fake), "~user/file");
+    }
+
+    #[test]
+    fn expands_only_directory_env_vars() {
+        let fake = windows();
+        assert_eq!(expand_with(r"%APPDATA%\Kimi", &fake), r"C:\Users\me\AppData\Roaming\Kimi");
+        assert_eq!(expand_with(r"%appdata%\Kimi", &fake), r"C:\Users\me\AppData\Roaming\Kimi");
+        assert_eq!(expand_with("${APPDATA}/a", &fake), r"C:\Users\me\AppData\Roaming/a");
+        assert_eq!(expand_with("$APPDATA/a", &fake), r"C:\Users\me\AppData\Roaming/a");
+        assert_eq!(expand_with("$SECRET_TOKEN/%SECRET_TOKEN%", &fake), "$SECRET_TOKEN/%SECRET_TOKEN%");
+        assert_eq!(expand_with("$XDG_CONFIG_HOME/x", &fake), "$XDG_CONFIG_HOME/x");
+    }
+
+    #[test]
+    fn system_resolver_falls_back_to_platform_dirs() {
+        let home = dirs::home_dir().expect("home dir");
+        assert_eq!(
+            expand_path("{home}/.config"),
+            format!("{}/.config", home.to_string_lossy())
+        );
+        assert!(!expand_path("$XDG_CACHE_HOME/openusage").starts_with('$'));
+        assert!(!expand_path("%LOCALAPPDATA%/openusage").starts_with('%'));
+    }
+}
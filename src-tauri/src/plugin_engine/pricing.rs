@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// User price overrides in the app data dir, in the same format as the built-in catalog.
+pub const OVERRIDES_FILE: &str = "pricing.json";
+
+const BUILTIN_CATALOG: &str = include_str!("pricing_catalog.json");
+
+/// Token counts of one or more requests. `input` excludes cached tokens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TokenUsage {
+    pub input: u64,
+    pub output: u64,
+    pub cache_write: u64,
+    pub cache_read: u64,
+}
+
+impl TokenUsage {
+    pub fn total(&self) -> u64 {
+        self.input + self.output + self.cache_write + self.cache_read
+    }
+
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cache_write += other.cache_write;
+        self.cache_read += other.cache_read;
+    }
+}
+
+/// USD per million tokens, effective from `effective` (`YYYY-MM-DD`) until the next entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    #[serde(default)]
+    pub effective: Option<String>,
+    pub input: f64,
+    pub output: f64,
+    #[serde(default)]
+    pub cache_write: f64,
+    #[serde(default)]
+    pub cache_read: f64,
+}
+
+impl ModelPrice {
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input as f64 * self.input
+            + usage.output as f64 * self.output
+            + usage.cache_write as f64 * self.cache_write
+            + usage.cache_read as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct ModelEntry {
+    /// Model id prefix ending at a `-` or `@` boundary; the longest match wins, so dated ids
+    /// (`claude-sonnet-4-5-20250929`) resolve to their family.
+    #[serde(rename = "match")]
+    pattern: String,
+    prices: Vec<ModelPrice>,
+    #[serde(skip)]
+    from_override: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CatalogFile {
+    #[serde(default)]
+    version: u32,
+    models: Vec<ModelEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PriceSource {
+    Catalog,
+    Override,
+}
+
+/// Catalog entry chosen for a model at a date.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceMatch {
+    #[serde(rename = "match")]
+    pub pattern: String,
+    pub price: ModelPrice,
+    pub source: PriceSource,
+}
+
+/// Model prices by id prefix and effective date: the built-in catalog, optionally with the
+/// user's overrides on top.
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    pub version: u32,
+    models: Vec<ModelEntry>,
+}
+
+pub(crate) fn valid_date(date: &str) -> bool {
+    let bytes = date.as_bytes();
+    bytes.len() == 10
+        && bytes[4] == b'-'
+        && bytes[7] == b'-'
+        && bytes
+            .iter()
+            .enumerate()
+            .all(|(i, b)| i == 4 || i == 7 || b.is_ascii_digit())
+}
+
+fn parse_catalog(text: &str, from_override: bool) -> Result<CatalogFile, String> {
+    let mut file: CatalogFile = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    for entry in &mut file.models {
+        if entry.pattern.trim().is_empty() {
+            return Err("model entry with empty match".to_string());
+        }
+        if entry.prices.is_empty() {
+            return Err(format!("{}: no prices", entry.pattern));
+        }
+        for price in &entry.prices {
+            if let Some(date) = price.effective.as_deref().filter(|d| !valid_date(d)) {
+                return Err(format!("{}: invalid effective date {} (expected YYYY-MM-DD)", entry.pattern, date));
+            }
+            let rates = [price.input, price.output, price.cache_write, price.cache_read];
+            if rates.iter().any(|rate| !rate.is_finite() || *rate < 0.0) {
+                return Err(format!("{}: prices must be non-negative numbers", entry.pattern));
+            }
+        }
+        // Undated entries apply from the beginning; ISO dates sort as text
+        entry.prices.sort_by(|a, b| a.effective.cmp(&b.effective));
+        entry.pattern = entry.pattern.to_ascii_lowercase();
+        entry.from_override = from_override;
+    }
+    Ok(file)
+}
+
+/// UTC calendar date (`YYYY-MM-DD`) of a unix timestamp in milliseconds.
+pub fn date_of_ms(ms: u64) -> String {
+    let date = time::OffsetDateTime::from_unix_timestamp((ms / 1000) as i64)
+        .unwrap_or(time::OffsetDateTime::UNIX_EPOCH)
+        .date();
+    format!("{:04}-{:02}-{:02}", date.year(), date.month() as u8, date.day())
+}
+
+/// Date for a price lookup from a `YYYY-MM-DD` string, an RFC 3339 timestamp or unix ms.
+pub fn date_from_value(value: &serde_json::Value) -> Result<Option<String>, String> {
+    use serde_json::Value;
+    match value {
+        Value::Null => Ok(None),
+        Value::String(text) if valid_date(text) => Ok(Some(text.clone())),
+        Value::String(text) => {
+            time::OffsetDateTime::parse(text, &time::format_description::well_known::Rfc3339)
+                .map(|t| Some(date_of_ms((t.unix_timestamp() * 1000).max(0) as u64)))
+                .map_err(|_| format!("invalid date: {}", text))
+        }
+        Value::Number(n) => Ok(n.as_f64().map(|ms| date_of_ms(ms.max(0.0) as u64))),
+        _ => Err("date must be a YYYY-MM-DD string, ISO timestamp or unix ms".to_string()),
+    }
+}
+
+/// `pattern` is `model` itself or `model` minus a `-`/`@` suffix (variant, date), so `o3`
+/// prices `o3-2025-04-16` but not `o3x`.
+fn matches_model(model: &str, pattern: &str) -> bool {
+    model
+        .strip_prefix(pattern)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['-', '@']))
+}
+
+impl Catalog {
+    pub fn builtin() -> &'static Catalog {
+        static BUILTIN: OnceLock<Catalog> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            let file = parse_catalog(BUILTIN_CATALOG, false).expect("built-in pricing catalog");
+            Catalog {
+                version: file.version,
+                models: file.models,
+            }
+        })
+    }
+
+    /// Built-in catalog with `<app_data_dir>/pricing.json` applied. An invalid overrides file
+    /// is logged and ignored.
+    pub fn load(app_data_dir: &Path) -> Catalog {
+        let path = app_data_dir.join(OVERRIDES_FILE);
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return Catalog::builtin().clone();
+        };
+        Catalog::builtin().with_overrides(&text).unwrap_or_else(|e| {
+            log::warn!("ignoring invalid price overrides in {}: {}", path.display(), e);
+            Catalog::builtin().clone()
+        })
+    }
+
+    /// Overrides win over catalog entries with the same `match` and are preferred when
+    /// two prefixes of equal length match.
+    pub fn with_overrides(&self, text: &str) -> Result<Catalog, String> {
+        let overrides = parse_catalog(text, true)?;
+        let mut models: Vec<ModelEntry> = self
+            .models
+            .iter()
+            .filter(|entry| !overrides.models.iter().any(|o| o.pattern == entry.pattern))
+            .cloned()
+            .collect();
+        models.splice(0..0, overrides.models);
+        Ok(Catalog {
+            version: self.version,
+            models,
+        })
+    }
+
+    /// Price of `model` on `date` (`YYYY-MM-DD`, today when `None`). Provider prefixes such
+    /// as `anthropic/` are ignored; the longest matching entry wins. Dates before the first entry use the first entry.
+    pub fn price_for(&self, model: &str, date: Option<&str>) -> Option<PriceMatch> {
+        let model = model.rsplit('/').next().unwrap_or(model).trim().to_ascii_lowercase();
+        let entry = self
+            .models
+            .iter()
+            .filter(|entry| matches_model(&model, &entry.pattern))
+            .fold(None::<&ModelEntry>, |best, entry| match best {
+                Some(best) if best.pattern.len() >= entry.pattern.len() => Some(best),
+                _ => Some(entry),
+            })?;
+        let today;
+        let date = match date {
+            Some(date) => date,
+            None => {
+                today = date_of_ms((time::OffsetDateTime::now_utc().unix_timestamp() * 1000).max(0) as u64);
+                &today
+            }
+        };
+        let price = entry
+            .prices
+            .iter()
+            .rev()
+            .find(|price| price.effective.as_deref().is_none_or(|from| from <= date))
+            .unwrap_or(&entry.prices[0]);
+        Some(PriceMatch {
+            pattern: entry.pattern.clone(),
+            price: price.clone(),
+            source: if entry.from_override {
+                PriceSource::Override
+            } else {
+                PriceSource::Catalog
+            },
+        })
+    }
+
+    /// Cost in USD, or `None` for a model without a known price.
+    pub fn cost(&self, model: &str, usage: &TokenUsage, date: Option<&str>) -> Option<f64> {
+        self.price_for(model, date).map(|found| found.price.cost(usage))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_prefix_prices_dated_models() {
+        let catalog = Catalog::builtin();
+        let input = |model: &str| catalog.price_for(model, None).map(|m| m.price.input);
+        assert_eq!(input("claude-opus-4-5-20251101"), Some(5.0));
+        assert_eq!(input("claude-opus-4-1-20250805"), Some(15.0));
+        assert_eq!(input("anthropic/Claude-Sonnet-4-5"), Some(3.0));
+        assert_eq!(input("gpt-5-codex"), Some(1.25));
+        assert_eq!(input("gpt-5-mini"), Some(0.25));
+        assert_eq!(input("gpt-5-pro"), Some(15.0));
+        assert_eq!(input("gpt-5-pro-2025-10-06"), Some(15.0));
+        assert_eq!(input("gpt-4.1"), Some(2.0));
+        assert_eq!(input("gpt-4.1-mini"), Some(0.4));
+        assert_eq!(input("gpt-4.1-nano-2025-04-14"), Some(0.1));
+        assert_eq!(input("o3-mini"), Some(1.1));
+        assert_eq!(input("o3-pro"), Some(20.0));
+        assert_eq!(input("o3x"), None);
+        assert_eq!(input("claude-sonnet-4-5@20250929"), Some(3.0));
+        assert_eq!(input("mystery-model"), None);
+
+        let usage = TokenUsage { input: 1_000_000, output: 100_000, cache_write: 0, cache_read: 2_000_000 };
+        let cost = catalog.cost("claude-sonnet-4-5", &usage, None).unwrap();
+        assert!((cost - (3.0 + 1.5 + 0.6)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn effective_dates_select_the_price_in_force() {
+        let catalog = Catalog::builtin();
+        let output = |date: &str| catalog.price_for("o3-2025-04-16", Some(date)).map(|m| m.price.output);
+        assert_eq!(output("2025-05-01"), Some(40.0));
+        assert_eq!(output("2025-06-10"), Some(8.0));
+        assert_eq!(output("2024-01-01"), Some(40.0));
+    }
+
+    #[test]
+    fn overrides_replace_and_extend_the_catalog() {
+        let catalog = Catalog::builtin()
+            .with_overrides(
+                r#"{ "models": [
+                    { "match": "gpt-5", "prices": [{ "input": 1, "output": 2 }] },
+                    { "match": "internal-llm", "prices": [{ "effective": "2026-01-01", "input": 0.5, "output": 0.5 }] }
+                ] }"#,
+            )
+            .expect("overrides");
+        let found = catalog.price_for("gpt-5-codex", None).unwrap();
+        assert_eq!((found.price.output, found.source), (2.0, PriceSource::Override));
+        assert_eq!(catalog.price_for("gpt-5-mini", None).unwrap().source, PriceSource::Catalog);
+        assert_eq!(catalog.price_for("internal-llm-7b", Some("2026-02-01")).unwrap().price.input, 0.5);
+        assert_eq!(catalog.version, Catalog::builtin().version);
+
+        assert!(Catalog::builtin().with_overrides(r#"{ "models": [{ "match": "x", "prices": [] }] }"#).is_err());
+        assert_eq!(date_from_value(&serde_json::json!("2026-03-01T23:00:00-02:00")).unwrap().as_deref(), Some("2026-03-02"));
+        assert!(date_from_value(&serde_json::json!("March")).is_err());
+        assert!(Catalog::builtin()
+            .with_overrides(r#"{ "models": [{ "match": "x", "prices": [{ "effective": "Jan 1", "input": 1, "output": 1 }] }] }"#)
+            .is_err());
+    }
+}
@@ -0,0 +1,192 @@
+use crate::plugin_engine::budget::Budget;
+use crate::plugin_engine::currency::RateTable;
+use crate::plugin_engine::manifest::{AccountProfile, LoadedPlugin, PluginManifest, PluginSupport};
+use crate::plugin_engine::runtime::{self, PluginOutput, SettingValues};
+use crate::plugin_engine::session_logs::SessionLogs;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use time::UtcOffset;
+
+/// How a provider is implemented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderKind {
+    /// JS plugin run in QuickJS.
+    Plugin,
+    /// Built into the app in Rust.
+    Native,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    pub kind: ProviderKind,
+    /// Whether user account profiles are probed separately. Providers without it never get
+    /// profiles in `ProbeContext::accounts`.
+    pub accounts: bool,
+    /// Built from the other providers' outputs, so probed after them in a batch.
+    pub aggregate: bool,
+}
+
+/// Inputs of one probe, resolved by the batch runner.
+pub struct ProbeContext<'a> {
+    pub app_data_dir: &'a PathBuf,
+    pub app_version: &'a str,
+    pub settings: &'a SettingValues,
+    /// User-configured account profiles, merged over the ones the provider declares.
+    pub accounts: &'a [AccountProfile],
+    /// ISO 4217 code money lines are shown in.
+    pub display_currency: &'a str,
+    /// Exchange rates loaded once for the batch.
+    pub rates: &'a RateTable,
+    /// Latest outputs per provider id, for aggregate providers.
+    pub outputs: &'a HashMap<String, Vec<PluginOutput>>,
+    /// Every provider, for aggregate providers to read the others' manifests.
+    pub providers: &'a [Arc<dyn Provider>],
+    /// Offset of the user's time zone, for calendar days and billing periods.
+    pub local_offset: UtcOffset,
+}
+
+/// A source of usage data shown in the panel and tray. JS plugins and built-in Rust
+/// providers are listed, configured and probed the same way.
+pub trait Provider: Send + Sync {
+    /// Id, name, lines, links, settings and declared accounts.
+    fn manifest(&self) -> &PluginManifest;
+
+    fn icon_data_url(&self) -> &str;
+
+    fn capabilities(&self) -> Capabilities;
+
+    fn support(&self) -> &PluginSupport;
+
+    /// One output per account profile, or a single output. Errors are reported as error
+    /// lines, never by panicking.
+    fn probe(&self, ctx: &ProbeContext<'_>) -> Vec<PluginOutput>;
+
+    fn id(&self) -> &str {
+        &self.manifest().id
+    }
+}
+
+impl Provider for LoadedPlugin {
+    fn manifest(&self) -> &PluginManifest {
+        &self.manifest
+    }
+
+    fn icon_data_url(&self) -> &str {
+        &self.icon_data_url
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            kind: ProviderKind::Plugin,
+            accounts: true,
+            aggregate: false,
+        }
+    }
+
+    fn support(&self) -> &PluginSupport {
+        &self.support
+    }
+
+    fn probe(&self, ctx: &ProbeContext<'_>) -> Vec<PluginOutput> {
+        if ctx.accounts.is_empty() {
+            return runtime::run_probes(self, ctx.app_data_dir, ctx.app_version, ctx.settings);
+        }
+        let mut plugin = self.clone();
+        plugin.manifest.merge_accounts(ctx.accounts.to_vec());
+        runtime::run_probes(&plugin, ctx.app_data_dir, ctx.app_version, ctx.settings)
+    }
+}
+
+/// Loaded JS plugins followed by the built-in providers. A plugin whose id collides with a
+/// built-in provider replaces it.
+pub fn all_providers(plugins: Vec<LoadedPlugin>) -> Vec<Arc<dyn Provider>> {
+    let mut providers: Vec<Arc<dyn Provider>> = plugins
+        .into_iter()
+        .map(|plugin| {
+            log::debug!("plugin {} loaded from {}", plugin.manifest.id, plugin.plugin_dir.display());
+            Arc::new(plugin) as Arc<dyn Provider>
+        })
+        .collect();
+    let builtin: Vec<Arc<dyn Provider>> = vec![Arc::new(SessionLogs::new()), Arc::new(Budget::new())];
+    for provider in builtin {
+        if providers.iter().any(|existing| existing.id() == provider.id()) {
+            log::warn!("plugin {} overrides the built-in provider", provider.id());
+            continue;
+        }
+        providers.push(provider);
+    }
+    providers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_engine::test_dir;
+    use crate::plugin_engine::{budget, session_logs};
+
+    fn js_plugin(id: &str) -> LoadedPlugin {
+        let manifest: PluginManifest = serde_json::from_value(serde_json::json!({
+            "schemaVersion": 1,
+            "id": id,
+            "name": "Test",
+            "version": "0.0.0",
+            "entry": "plugin.js",
+            "icon": "icon.svg",
+            "brandColor": null,
+            "lines": [],
+        }))
+        .expect("manifest");
+        LoadedPlugin {
+            manifest,
+            plugin_dir: PathBuf::from("."),
+            entry_script: "globalThis.__openusage_plugin = { probe: function () { return { lines: [{ type: 'text', label: 'A', value: '1' }] } } }".to_string(),
+            icon_data_url: "data:".to_string(),
+            support: PluginSupport::Supported,
+        }
+    }
+
+    #[test]
+    fn builtin_providers_follow_plugins_unless_overridden() {
+        let providers = all_providers(vec![js_plugin("codex")]);
+        let ids: Vec<&str> = providers.iter().map(|p| p.id()).collect();
+        assert_eq!(ids, vec!["codex", session_logs::ID, budget::ID]);
+        assert_eq!(providers[0].capabilities().kind, ProviderKind::Plugin);
+        assert_eq!(providers[1].capabilities().kind, ProviderKind::Native);
+
+        let overridden = all_providers(vec![js_plugin(session_logs::ID)]);
+        assert_eq!(overridden.len(), 2);
+        assert_eq!(overridden[0].capabilities().kind, ProviderKind::Plugin);
+        assert!(overridden[1].capabilities().aggregate);
+    }
+
+    #[test]
+    fn js_plugin_probes_each_user_account() {
+        let plugin = js_plugin("test");
+        let app_data_dir = test_dir("provider");
+        let accounts: Vec<AccountProfile> = serde_json::from_value(serde_json::json!([
+            { "id": "work", "label": "Work" },
+            { "id": "home", "label": "Home" },
+        ]))
+        .expect("accounts");
+        let settings = SettingValues::new();
+        let outputs = HashMap::new();
+        let ctx = ProbeContext {
+            app_data_dir: &app_data_dir,
+            app_version: "0.0.0",
+            settings: &settings,
+            accounts: &accounts,
+            display_currency: "USD",
+            rates: RateTable::builtin(),
+            outputs: &outputs,
+            providers: &[],
+            local_offset: UtcOffset::UTC,
+        };
+
+        let outputs = plugin.probe(&ctx);
+        let labels: Vec<Option<&str>> = outputs.iter().map(|o| o.account.as_deref()).collect();
+        assert_eq!(labels, vec![Some("Work"), Some("Home")]);
+        assert!(plugin.manifest.accounts.is_empty());
+        let _ = std::fs::remove_dir_all(&app_data_dir);
+    }
+}
@@ -1,27 +1,124 @@
+use crate::plugin_engine::currency::{self, ConvertedAmount};
 use crate::plugin_engine::host_api;
-use crate::plugin_engine::manifest::LoadedPlugin;
+use crate::plugin_engine::humanize::ProgressText;
+use crate::plugin_engine::manifest::{self, AccountProfile, LoadedPlugin, ManifestLine};
+use crate::plugin_engine::settings;
 use rquickjs::{Array, Context, Ctx, Error, Object, Promise, Runtime, Value};
-use serde::Serialize;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 
-#[derive(Debug, Clone, Serialize)]
+/// Most points a sparkline keeps; plugins should downsample longer series.
+pub const MAX_SPARKLINE_POINTS: usize = 240;
+pub const MAX_TABLE_COLUMNS: usize = 8;
+pub const MAX_TABLE_ROWS: usize = 100;
+pub const MAX_PROGRESS_SEGMENTS: usize = 8;
+pub const MAX_SECTIONS: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "kind", rename_all = "camelCase")]
 pub enum ProgressFormat {
     Percent,
     Dollars,
+    /// Money in an ISO 4217 currency, e.g. `EUR`.
+    Currency { code: String },
     Count { suffix: String },
+    Tokens,
+    Requests,
+    /// Time, with `used` and `limit` counted in `unit`.
+    Duration { unit: DurationUnit },
+    Bytes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DurationUnit {
+    Seconds,
+    Minutes,
+    Hours,
+}
+
+impl DurationUnit {
+    fn parse(value: &str) -> Option<DurationUnit> {
+        match value {
+            "seconds" => Some(DurationUnit::Seconds),
+            "minutes" => Some(DurationUnit::Minutes),
+            "hours" => Some(DurationUnit::Hours),
+            _ => None,
+        }
+    }
+}
+
+/// What a progress segment's usage counts as. Segments are drawn in this order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SegmentKind {
+    /// Usage covered by the plan's allowance.
+    Included,
+    /// Held for in-flight work; counts against the allowance but is not final yet.
+    Reserved,
+    /// Usage past the allowance, usually billed separately.
+    Overage,
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl SegmentKind {
+    fn parse(value: &str) -> Option<SegmentKind> {
+        match value {
+            "included" => Some(SegmentKind::Included),
+            "reserved" => Some(SegmentKind::Reserved),
+            "overage" => Some(SegmentKind::Overage),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SegmentKind::Included => "included",
+            SegmentKind::Reserved => "reserved",
+            SegmentKind::Overage => "overage",
+        }
+    }
+
+    fn default_label(self) -> &'static str {
+        match self {
+            SegmentKind::Included => "Included",
+            SegmentKind::Reserved => "Reserved",
+            SegmentKind::Overage => "Overage",
+        }
+    }
+}
+
+/// Named part of a progress line's `used`, in the line's unit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressSegment {
+    pub kind: SegmentKind,
+    pub label: String,
+    pub used: f64,
+    pub color: Option<String>,
+}
+
+/// One value of a sparkline, optionally with the time it was taken (ISO 8601).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SparkPoint {
+    pub value: f64,
+    pub at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum MetricLine {
     Text {
+        /// Stable key of the line, matched against the manifest; defaults to the id of the
+        /// declared line with the same label.
+        #[serde(default)]
+        id: Option<String>,
         label: String,
         value: String,
         color: Option<String>,
         subtitle: Option<String>,
     },
     Progress {
+        #[serde(default)]
+        id: Option<String>,
         label: String,
         used: f64,
         limit: f64,
@@ -31,16 +128,111 @@ pub enum MetricLine {
         #[serde(rename = "periodDurationMs")]
         period_duration_ms: Option<u64>,
         color: Option<String>,
+        /// Stacked parts of `used`; empty for a plain bar.
+        #[serde(default)]
+        segments: Vec<ProgressSegment>,
+        /// Usage at which the line is shown as a warning.
+        #[serde(rename = "softLimit", default)]
+        soft_limit: Option<f64>,
+        /// Usage at which the provider blocks further use; never below `limit`.
+        #[serde(rename = "hardLimit", default)]
+        hard_limit: Option<f64>,
+        /// `used`/`limit` in the user's display currency, filled in after the probe for
+        /// money lines in another currency.
+        converted: Option<ConvertedAmount>,
+        /// Labels for the typed formats (tokens, requests, duration, bytes), filled in after
+        /// the probe in the user's locale.
+        #[serde(default)]
+        humanized: Option<Box<ProgressText>>,
     },
     Badge {
+        #[serde(default)]
+        id: Option<String>,
         label: String,
         text: String,
         color: Option<String>,
         subtitle: Option<String>,
     },
+    /// Small trend chart, oldest point first.
+    Sparkline {
+        #[serde(default)]
+        id: Option<String>,
+        label: String,
+        points: Vec<SparkPoint>,
+        unit: String,
+        color: Option<String>,
+        subtitle: Option<String>,
+    },
+    /// Cells under column headings, e.g. usage per model. Every row has one cell per column.
+    Table {
+        #[serde(default)]
+        id: Option<String>,
+        label: String,
+        columns: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    /// Opened in the default browser.
+    Link {
+        #[serde(default)]
+        id: Option<String>,
+        label: String,
+        url: String,
+        subtitle: Option<String>,
+    },
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl MetricLine {
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Text { label, .. }
+            | Self::Progress { label, .. }
+            | Self::Badge { label, .. }
+            | Self::Sparkline { label, .. }
+            | Self::Table { label, .. }
+            | Self::Link { label, .. } => label,
+        }
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Self::Text { id, .. }
+            | Self::Progress { id, .. }
+            | Self::Badge { id, .. }
+            | Self::Sparkline { id, .. }
+            | Self::Table { id, .. }
+            | Self::Link { id, .. } => id.as_deref(),
+        }
+    }
+
+    fn id_mut(&mut self) -> &mut Option<String> {
+        match self {
+            Self::Text { id, .. }
+            | Self::Progress { id, .. }
+            | Self::Badge { id, .. }
+            | Self::Sparkline { id, .. }
+            | Self::Table { id, .. }
+            | Self::Link { id, .. } => id,
+        }
+    }
+
+    /// The `type` tag, as used in plugin.json.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Text { .. } => "text",
+            Self::Progress { .. } => "progress",
+            Self::Badge { .. } => "badge",
+            Self::Sparkline { .. } => "sparkline",
+            Self::Table { .. } => "table",
+            Self::Link { .. } => "link",
+        }
+    }
+
+    fn is_error(&self) -> bool {
+        matches!(self, Self::Badge { id: None, label, .. } if label == "Error")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PluginOutput {
     pub provider_id: String,
@@ -48,13 +240,92 @@ pub struct PluginOutput {
     pub plan: Option<String>,
     pub lines: Vec<MetricLine>,
     pub icon_url: String,
+    /// Account profile label when the plugin has multiple accounts.
+    pub account: Option<String>,
+    /// Id of that account profile. Labels may repeat; ids are unique per plugin.
+    #[serde(default)]
+    pub account_id: Option<String>,
+    /// Titled groups shown after `lines`, e.g. one per model.
+    #[serde(default)]
+    pub sections: Vec<OutputSection>,
+}
+
+impl PluginOutput {
+    /// Top-level lines followed by the lines of every section.
+    pub fn all_lines(&self) -> impl Iterator<Item = &MetricLine> {
+        self.lines
+            .iter()
+            .chain(self.sections.iter().flat_map(|section| section.lines.iter()))
+    }
+
+    pub fn all_lines_mut(&mut self) -> impl Iterator<Item = &mut MetricLine> {
+        self.lines
+            .iter_mut()
+            .chain(self.sections.iter_mut().flat_map(|section| section.lines.iter_mut()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputSection {
+    pub title: String,
+    pub lines: Vec<MetricLine>,
+    /// Hint that the section starts folded; the user can still expand it.
+    #[serde(default)]
+    pub collapsed: bool,
+}
+
+/// Resolved plugin setting values, keyed by setting key.
+pub type SettingValues = serde_json::Map<String, serde_json::Value>;
+
+/// Probe a plugin once per account profile (or once when it has none).
+pub fn run_probes(
+    plugin: &LoadedPlugin,
+    app_data_dir: &Path,
+    app_version: &str,
+    settings: &SettingValues,
+) -> Vec<PluginOutput> {
+    if let Some(reason) = plugin.support.unsupported_reason() {
+        return vec![error_output(plugin, reason.to_string())];
+    }
+    if plugin.manifest.accounts.is_empty() {
+        return vec![run_probe(plugin, app_data_dir, app_version, settings)];
+    }
+    plugin
+        .manifest
+        .accounts
+        .iter()
+        .map(|account| {
+            let mut output = probe_as(plugin, app_data_dir, app_version, settings, Some(account));
+            output.account = Some(account.label.clone());
+            output.account_id = Some(account.id.clone());
+            output
+        })
+        .collect()
 }
 
 pub fn run_probe(
     plugin: &LoadedPlugin,
-    app_data_dir: &PathBuf,
+    app_data_dir: &Path,
     app_version: &str,
+    settings: &SettingValues,
 ) -> PluginOutput {
+    probe_as(plugin, app_data_dir, app_version, settings, None)
+}
+
+fn probe_as(
+    plugin: &LoadedPlugin,
+    app_data_dir: &Path,
+    app_version: &str,
+    settings: &SettingValues,
+    account: Option<&AccountProfile>,
+) -> PluginOutput {
+    if let Some(field) = settings::missing_required(&plugin.manifest.settings, settings) {
+        return error_output(
+            plugin,
+            format!("Missing required setting: {}. Set it in plugin settings.", field.label),
+        );
+    }
+
     let fallback = error_output(plugin, "runtime error".to_string());
 
     let rt = match Runtime::new() {
@@ -71,10 +342,10 @@ pub fn run_probe(
     let display_name = plugin.manifest.name.clone();
     let entry_script = plugin.entry_script.clone();
     let icon_url = plugin.icon_data_url.clone();
-    let app_data = app_data_dir.clone();
+    let app_data = app_data_dir.to_path_buf();
 
     ctx.with(|ctx| {
-        if host_api::inject_host_api(&ctx, &plugin_id, &app_data, app_version).is_err() {
+        if host_api::inject_host_api(&ctx, &plugin_id, &app_data, app_version, account).is_err() {
             return error_output(plugin, "host api injection failed".to_string());
         }
         if host_api::patch_http_wrapper(&ctx).is_err() {
@@ -83,6 +354,9 @@ pub fn run_probe(
         if host_api::patch_ls_wrapper(&ctx).is_err() {
             return error_output(plugin, "ls wrapper patch failed".to_string());
         }
+        if host_api::inject_settings(&ctx, settings).is_err() {
+            return error_output(plugin, "settings injection failed".to_string());
+        }
         if host_api::inject_utils(&ctx).is_err() {
             return error_output(plugin, "utils injection failed".to_string());
         }
@@ -131,11 +405,36 @@ pub fn run_probe(
 
         let plan: Option<String> = result.get::<_, String>("plan").ok().filter(|s| !s.is_empty());
 
-        let lines = match parse_lines(&result) {
-            Ok(lines) if !lines.is_empty() => lines,
+        let (mut sections, section_errors) = parse_sections(&result);
+        let mut lines = match parse_lines(&result) {
+            Ok(lines) if !lines.is_empty() || !sections.is_empty() => lines,
             Ok(_) => vec![error_line("no lines returned".to_string())],
+            // `lines` may be left out when everything is in sections.
+            Err(_) if !sections.is_empty() && !result.contains_key("lines").unwrap_or(false) => {
+                Vec::new()
+            }
             Err(msg) => vec![error_line(msg)],
         };
+        let (undeclared, mismatched) = check_line_contract(&mut lines, &plugin.manifest.lines);
+        for (idx, problem) in undeclared {
+            if plugin.manifest.strict_lines {
+                lines[idx] = error_line(problem);
+            } else {
+                log::warn!("plugin {}: {}", plugin_id, problem);
+            }
+        }
+        for problem in mismatched {
+            log::warn!("plugin {}: {}", plugin_id, problem);
+        }
+        // Section lines only get ids, so primary candidates can name them.
+        for section in sections.iter_mut() {
+            check_line_contract(&mut section.lines, &plugin.manifest.lines);
+        }
+        lines.extend(section_errors.into_iter().map(error_line));
+        apply_segment_colors(&mut lines, &plugin.manifest.lines);
+        for section in sections.iter_mut() {
+            apply_segment_colors(&mut section.lines, &plugin.manifest.lines);
+        }
 
         PluginOutput {
             provider_id: plugin_id,
@@ -143,10 +442,134 @@ pub fn run_probe(
             plan,
             lines,
             icon_url,
+            account: None,
+            account_id: None,
+            sections,
         }
     })
 }
 
+/// Gives lines the id of the manifest line they report (by id, else by label). Returns the
+/// index of every line plugin.json does not declare with the reason, and the declared lines
+/// returned as another type. Error badges are exempt.
+fn check_line_contract(
+    lines: &mut [MetricLine],
+    manifest_lines: &[ManifestLine],
+) -> (Vec<(usize, String)>, Vec<String>) {
+    let mut undeclared = Vec::new();
+    let mut mismatched = Vec::new();
+    for (idx, line) in lines.iter_mut().enumerate() {
+        if line.is_error() {
+            continue;
+        }
+        let declared = match line.id() {
+            Some(id) => manifest_lines.iter().find(|declared| declared.id == id),
+            None => manifest_lines.iter().find(|declared| declared.label == line.label()),
+        };
+        let Some(declared) = declared else {
+            let problem = match line.id() {
+                Some(id) => format!("line '{}' has undeclared id '{}'", line.label(), id),
+                None => format!("line '{}' is not declared in plugin.json", line.label()),
+            };
+            undeclared.push((idx, problem));
+            continue;
+        };
+        if declared.line_type != line.type_name() {
+            mismatched.push(format!(
+                "line '{}' is declared as {} but returned as {}",
+                line.label(),
+                declared.line_type,
+                line.type_name()
+            ));
+        }
+        *line.id_mut() = Some(declared.id.clone());
+    }
+    (undeclared, mismatched)
+}
+
+/// Colors segments the plugin left uncolored from the manifest line with the same id (or
+/// label, for lines without one), matching the segment label first and then its kind.
+fn apply_segment_colors(lines: &mut [MetricLine], manifest_lines: &[ManifestLine]) {
+    for line in lines {
+        let MetricLine::Progress { id, label, segments, .. } = line else {
+            continue;
+        };
+        let Some(declared) = manifest_lines.iter().find(|declared| {
+            declared.line_type == "progress"
+                && id.as_ref().map_or(&declared.label == label, |id| &declared.id == id)
+        }) else {
+            continue;
+        };
+        for segment in segments.iter_mut().filter(|segment| segment.color.is_none()) {
+            segment.color = declared
+                .segment_colors
+                .get(&segment.label)
+                .or_else(|| declared.segment_colors.get(segment.kind.as_str()))
+                .cloned();
+        }
+    }
+}
+
+/// Sections of a probe result; invalid sections are dropped and reported as errors.
+fn parse_sections(result: &Object) -> (Vec<OutputSection>, Vec<String>) {
+    let mut sections: Vec<OutputSection> = Vec::new();
+    let mut errors = Vec::new();
+    let raw: Value = match result.get("sections") {
+        Ok(value) => value,
+        Err(_) => return (sections, errors),
+    };
+    if raw.is_undefined() || raw.is_null() {
+        return (sections, errors);
+    }
+    let Some(raw) = raw.into_array() else {
+        errors.push("sections must be an array".to_string());
+        return (sections, errors);
+    };
+    if raw.len() > MAX_SECTIONS {
+        errors.push(format!("sections must have at most {} entries", MAX_SECTIONS));
+        return (sections, errors);
+    }
+    for idx in 0..raw.len() {
+        let Ok(section) = raw.get::<Object>(idx) else {
+            errors.push(format!("invalid section at index {}", idx));
+            continue;
+        };
+        let title = section
+            .get::<_, String>("title")
+            .map(|title| title.trim().to_string())
+            .unwrap_or_default();
+        if title.is_empty() {
+            errors.push(format!("section at index {} missing title", idx));
+            continue;
+        }
+        if sections.iter().any(|other| other.title == title) {
+            errors.push(format!("section '{}' is duplicated", title));
+            continue;
+        }
+        let collapsed = match section.get::<_, Value>("collapsed") {
+            Ok(value) if value.is_bool() => value.as_bool().unwrap_or(false),
+            Ok(value) if value.is_undefined() || value.is_null() => false,
+            _ => {
+                errors.push(format!("section '{}' collapsed must be a boolean", title));
+                continue;
+            }
+        };
+        let lines = match parse_lines(&section) {
+            Ok(lines) if !lines.is_empty() => lines,
+            Ok(_) => {
+                errors.push(format!("section '{}' has no lines", title));
+                continue;
+            }
+            Err(e) => {
+                errors.push(format!("section '{}': {}", title, e));
+                continue;
+            }
+        };
+        sections.push(OutputSection { title, lines, collapsed });
+    }
+    (sections, errors)
+}
+
 fn parse_lines(result: &Object) -> Result<Vec<MetricLine>, String> {
     let lines: Array = result
         .get("lines")
@@ -163,11 +586,25 @@ fn parse_lines(result: &Object) -> Result<Vec<MetricLine>, String> {
         let label = line.get::<_, String>("label").unwrap_or_default();
         let color = line.get::<_, String>("color").ok();
         let subtitle = line.get::<_, String>("subtitle").ok();
+        let id = match line.get::<_, Value>("id") {
+            Ok(v) if v.is_undefined() || v.is_null() => None,
+            Ok(v) => match v.as_string().and_then(|s| s.to_string().ok()) {
+                Some(id) if manifest::is_valid_id(&id) => Some(id),
+                _ => {
+                    out.push(error_line(format!(
+                        "line at index {} invalid id (expected [A-Za-z0-9_-] string)",
+                        idx
+                    )));
+                    continue;
+                }
+            },
+            Err(_) => None,
+        };
 
         match line_type.as_str() {
             "text" => {
                 let value = line.get::<_, String>("value").unwrap_or_default();
-                out.push(MetricLine::Text { label, value, color, subtitle });
+                out.push(MetricLine::Text { id, label, value, color, subtitle });
             }
             "progress" => {
                 let used_value: Value = match line.get("used") {
@@ -177,8 +614,13 @@ fn parse_lines(result: &Object) -> Result<Vec<MetricLine>, String> {
                         continue;
                     }
                 };
+                // With segments, `used` may be omitted and is their sum.
+                let has_segments = line
+                    .get::<_, Value>("segments")
+                    .is_ok_and(|v| !v.is_undefined() && !v.is_null());
                 let used = match used_value.as_number() {
-                    Some(n) => n,
+                    Some(n) => Some(n),
+                    None if has_segments && used_value.is_undefined() => None,
                     None => {
                         out.push(error_line(format!(
                             "progress line at index {} invalid used (expected number)",
@@ -209,7 +651,7 @@ fn parse_lines(result: &Object) -> Result<Vec<MetricLine>, String> {
                     }
                 };
 
-                if !used.is_finite() || used < 0.0 {
+                if let Some(used) = used.filter(|used| !used.is_finite() || *used < 0.0) {
                     out.push(error_line(format!(
                         "progress line at index {} invalid used: {}",
                         idx, used
@@ -223,6 +665,13 @@ fn parse_lines(result: &Object) -> Result<Vec<MetricLine>, String> {
                     )));
                     continue;
                 }
+                let extent = match parse_extent(&js_json(&line), used, limit) {
+                    Ok(extent) => extent,
+                    Err(e) => {
+                        out.push(error_line(format!("progress line at index {}: {}", idx, e)));
+                        continue;
+                    }
+                };
 
                 let format_obj: Object = match line.get("format") {
                     Ok(obj) => obj,
@@ -266,6 +715,21 @@ fn parse_lines(result: &Object) -> Result<Vec<MetricLine>, String> {
                         ProgressFormat::Percent
                     }
                     "dollars" => ProgressFormat::Dollars,
+                    "currency" => {
+                        let code = match format_obj.get::<_, Value>("code") {
+                            Ok(v) => v.as_string().and_then(|s| s.to_string().ok()),
+                            Err(_) => None,
+                        };
+                        let code = code.unwrap_or_default().trim().to_ascii_uppercase();
+                        if !currency::valid_code(&code) {
+                            out.push(error_line(format!(
+                                "progress line at index {}: currency format requires an ISO 4217 code (e.g. \"EUR\")",
+                                idx
+                            )));
+                            continue;
+                        }
+                        ProgressFormat::Currency { code }
+                    }
                     "count" => {
                         let suffix_value: Value = match format_obj.get("suffix") {
                             Ok(v) => v,
@@ -297,6 +761,25 @@ fn parse_lines(result: &Object) -> Result<Vec<MetricLine>, String> {
                         }
                         ProgressFormat::Count { suffix }
                     }
+                    "tokens" => ProgressFormat::Tokens,
+                    "requests" => ProgressFormat::Requests,
+                    "bytes" => ProgressFormat::Bytes,
+                    "duration" => {
+                        let unit = match format_obj.get::<_, Value>("unit") {
+                            Ok(v) => v.as_string().and_then(|s| s.to_string().ok()),
+                            Err(_) => None,
+                        };
+                        match unit.as_deref().and_then(DurationUnit::parse) {
+                            Some(unit) => ProgressFormat::Duration { unit },
+                            None => {
+                                out.push(error_line(format!(
+                                    "progress line at index {}: duration format requires unit \"seconds\", \"minutes\" or \"hours\"",
+                                    idx
+                                )));
+                                continue;
+                            }
+                        }
+                    }
                     _ => {
                         out.push(error_line(format!(
                             "progress line at index {} invalid format.kind: {}",
@@ -386,18 +869,39 @@ fn parse_lines(result: &Object) -> Result<Vec<MetricLine>, String> {
                 };
 
                 out.push(MetricLine::Progress {
+                    id,
                     label,
-                    used,
+                    used: extent.used,
                     limit,
                     format,
                     resets_at,
                     period_duration_ms,
                     color,
+                    segments: extent.segments,
+                    soft_limit: extent.soft_limit,
+                    hard_limit: extent.hard_limit,
+                    converted: None,
+                    humanized: None,
                 });
             }
             "badge" => {
                 let text = line.get::<_, String>("text").unwrap_or_default();
-                out.push(MetricLine::Badge { label, text, color, subtitle });
+                out.push(MetricLine::Badge { id, label, text, color, subtitle });
+            }
+            "sparkline" | "table" | "link" => {
+                let json = js_json(&line);
+                let parsed = match line_type.as_str() {
+                    "sparkline" => parse_sparkline(&json, id, label, color, subtitle),
+                    "table" => parse_table(&json, id, label),
+                    _ => parse_link(&json, id, label, subtitle),
+                };
+                match parsed {
+                    Ok(parsed) => out.push(parsed),
+                    Err(e) => out.push(error_line(format!(
+                        "{} line at index {}: {}",
+                        line_type, idx, e
+                    ))),
+                }
             }
             _ => {
                 out.push(error_line(format!(
@@ -411,6 +915,247 @@ fn parse_lines(result: &Object) -> Result<Vec<MetricLine>, String> {
     Ok(out)
 }
 
+/// A JS object as JSON, or `null` when it cannot be stringified.
+fn js_json(object: &Object) -> serde_json::Value {
+    object
+        .ctx()
+        .json_stringify(object.clone())
+        .ok()
+        .flatten()
+        .and_then(|s| s.to_string().ok())
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .unwrap_or(serde_json::Value::Null)
+}
+
+/// Usage of a progress line after its segments and extra limits are validated.
+#[derive(Debug, PartialEq)]
+struct ProgressExtent {
+    used: f64,
+    segments: Vec<ProgressSegment>,
+    soft_limit: Option<f64>,
+    hard_limit: Option<f64>,
+}
+
+/// `used` past `limit` is overflow: the line reads above 100% and any overage segments
+/// lie beyond the limit mark. Included and reserved usage must fit within `limit`.
+fn parse_extent(
+    line: &serde_json::Value,
+    used: Option<f64>,
+    limit: f64,
+) -> Result<ProgressExtent, String> {
+    let optional_limit = |key: &str| match &line[key] {
+        serde_json::Value::Null => Ok(None),
+        value => value
+            .as_f64()
+            .filter(|n| n.is_finite() && *n > 0.0)
+            .map(Some)
+            .ok_or_else(|| format!("{} must be a positive number", key)),
+    };
+    let soft_limit = optional_limit("softLimit")?;
+    let hard_limit = optional_limit("hardLimit")?;
+    if hard_limit.is_some_and(|hard| hard < limit) {
+        return Err("hardLimit must be at least limit".to_string());
+    }
+    if soft_limit.zip(hard_limit).is_some_and(|(soft, hard)| soft > hard) {
+        return Err("softLimit must not exceed hardLimit".to_string());
+    }
+
+    let segments = match &line["segments"] {
+        serde_json::Value::Null => Vec::new(),
+        serde_json::Value::Array(raw) => parse_segments(raw)?,
+        _ => return Err("segments must be an array".to_string()),
+    };
+    if segments.is_empty() {
+        return Ok(ProgressExtent {
+            used: used.ok_or_else(|| "missing used".to_string())?,
+            segments,
+            soft_limit,
+            hard_limit,
+        });
+    }
+
+    let total: f64 = segments.iter().map(|segment| segment.used).sum();
+    let tolerance = 1e-9 * total.max(1.0);
+    if let Some(used) = used.filter(|used| (used - total).abs() > tolerance) {
+        return Err(format!(
+            "used ({}) must equal the sum of its segments ({})",
+            used, total
+        ));
+    }
+    let within_limit: f64 = segments
+        .iter()
+        .filter(|segment| segment.kind != SegmentKind::Overage)
+        .map(|segment| segment.used)
+        .sum();
+    if within_limit > limit + tolerance {
+        return Err(format!(
+            "included and reserved segments ({}) exceed limit ({}); report the excess as overage",
+            within_limit, limit
+        ));
+    }
+    Ok(ProgressExtent {
+        used: total,
+        segments,
+        soft_limit,
+        hard_limit,
+    })
+}
+
+fn parse_segments(raw: &[serde_json::Value]) -> Result<Vec<ProgressSegment>, String> {
+    if raw.is_empty() || raw.len() > MAX_PROGRESS_SEGMENTS {
+        return Err(format!("segments must have 1 to {} entries", MAX_PROGRESS_SEGMENTS));
+    }
+    let mut segments: Vec<ProgressSegment> = Vec::with_capacity(raw.len());
+    for (i, segment) in raw.iter().enumerate() {
+        let kind = segment["kind"]
+            .as_str()
+            .and_then(SegmentKind::parse)
+            .ok_or_else(|| {
+                format!("segment {} kind must be one of included, reserved, overage", i)
+            })?;
+        let used = segment["used"]
+            .as_f64()
+            .filter(|used| used.is_finite() && *used >= 0.0)
+            .ok_or_else(|| format!("segment {} used must be a non-negative number", i))?;
+        let label = match &segment["label"] {
+            serde_json::Value::Null => kind.default_label().to_string(),
+            serde_json::Value::String(label) if !label.trim().is_empty() => label.trim().to_string(),
+            _ => return Err(format!("segment {} label must be a non-empty string", i)),
+        };
+        if segments.iter().any(|other| other.label == label) {
+            return Err(format!("segment label '{}' is duplicated", label));
+        }
+        let color = segment["color"]
+            .as_str()
+            .filter(|color| manifest::is_hex_color(color))
+            .map(str::to_string);
+        segments.push(ProgressSegment { kind, label, used, color });
+    }
+    segments.sort_by_key(|segment| segment.kind);
+    Ok(segments)
+}
+
+fn parse_sparkline(
+    line: &serde_json::Value,
+    id: Option<String>,
+    label: String,
+    color: Option<String>,
+    subtitle: Option<String>,
+) -> Result<MetricLine, String> {
+    let unit = line["unit"].as_str().map(str::trim).unwrap_or_default();
+    if unit.is_empty() {
+        return Err("unit must be a non-empty string".to_string());
+    }
+    let raw_points = line["points"]
+        .as_array()
+        .ok_or_else(|| "points must be an array".to_string())?;
+    if raw_points.is_empty() || raw_points.len() > MAX_SPARKLINE_POINTS {
+        return Err(format!("points must have 1 to {} entries", MAX_SPARKLINE_POINTS));
+    }
+    let mut points = Vec::with_capacity(raw_points.len());
+    for (i, point) in raw_points.iter().enumerate() {
+        let (value, at) = match point {
+            serde_json::Value::Object(obj) => (obj.get("value").and_then(|v| v.as_f64()), obj.get("at")),
+            other => (other.as_f64(), None),
+        };
+        let value = value
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| format!("point {} must be a finite number or {{ value, at }}", i))?;
+        let at = match at {
+            None | Some(serde_json::Value::Null) => None,
+            Some(serde_json::Value::String(at))
+                if time::OffsetDateTime::parse(at, &time::format_description::well_known::Rfc3339)
+                    .is_ok() =>
+            {
+                Some(at.clone())
+            }
+            Some(_) => return Err(format!("point {} has an invalid at (expected ISO timestamp)", i)),
+        };
+        points.push(SparkPoint { value, at });
+    }
+    Ok(MetricLine::Sparkline {
+        id,
+        label,
+        points,
+        unit: unit.to_string(),
+        color,
+        subtitle,
+    })
+}
+
+fn table_cell(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Null => Some(String::new()),
+        serde_json::Value::Number(n) => Some(match n.as_f64() {
+            Some(f) if f.fract() == 0.0 && f.abs() < 1e15 => format!("{}", f as i64),
+            _ => n.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+fn parse_table(
+    line: &serde_json::Value,
+    id: Option<String>,
+    label: String,
+) -> Result<MetricLine, String> {
+    let columns: Vec<String> = line["columns"]
+        .as_array()
+        .ok_or_else(|| "columns must be an array".to_string())?
+        .iter()
+        .map(|c| c.as_str().map(|c| c.trim().to_string()).filter(|c| !c.is_empty()))
+        .collect::<Option<_>>()
+        .ok_or_else(|| "columns must be non-empty strings".to_string())?;
+    if columns.is_empty() || columns.len() > MAX_TABLE_COLUMNS {
+        return Err(format!("columns must have 1 to {} entries", MAX_TABLE_COLUMNS));
+    }
+    let raw_rows = line["rows"]
+        .as_array()
+        .ok_or_else(|| "rows must be an array".to_string())?;
+    if raw_rows.len() > MAX_TABLE_ROWS {
+        return Err(format!("at most {} rows", MAX_TABLE_ROWS));
+    }
+    let mut rows = Vec::with_capacity(raw_rows.len());
+    for (i, row) in raw_rows.iter().enumerate() {
+        let cells = row
+            .as_array()
+            .filter(|cells| cells.len() == columns.len())
+            .ok_or_else(|| format!("row {} must be an array of {} cells", i, columns.len()))?;
+        let cells: Vec<String> = cells
+            .iter()
+            .map(table_cell)
+            .collect::<Option<_>>()
+            .ok_or_else(|| format!("row {} cells must be strings or numbers", i))?;
+        rows.push(cells);
+    }
+    Ok(MetricLine::Table { id, label, columns, rows })
+}
+
+fn parse_link(
+    line: &serde_json::Value,
+    id: Option<String>,
+    label: String,
+    subtitle: Option<String>,
+) -> Result<MetricLine, String> {
+    if label.trim().is_empty() {
+        return Err("label must be a non-empty string".to_string());
+    }
+    let url = line["url"].as_str().map(str::trim).unwrap_or_default();
+    let valid = (url.starts_with("https://") || url.starts_with("http://"))
+        && url.split_once("://").is_some_and(|(_, rest)| !rest.is_empty())
+        && !url.chars().any(char::is_whitespace);
+    if !valid {
+        return Err(format!("url must be an http(s) URL (got {:?})", url));
+    }
+    Ok(MetricLine::Link {
+        id,
+        label,
+        url: url.to_string(),
+        subtitle,
+    })
+}
+
 fn error_output(plugin: &LoadedPlugin, message: String) -> PluginOutput {
     PluginOutput {
         provider_id: plugin.manifest.id.clone(),
@@ -418,6 +1163,9 @@ fn error_output(plugin: &LoadedPlugin, message: String) -> PluginOutput {
         plan: None,
         lines: vec![error_line(message)],
         icon_url: plugin.icon_data_url.clone(),
+        account: None,
+        account_id: None,
+        sections: Vec::new(),
     }
 }
 
@@ -436,8 +1184,9 @@ fn extract_error_string(ctx: &Ctx<'_>) -> String {
     "The plugin failed, try again or contact plugin author.".to_string()
 }
 
-fn error_line(message: String) -> MetricLine {
+pub(crate) fn error_line(message: String) -> MetricLine {
     MetricLine::Badge {
+        id: None,
         label: "Error".to_string(),
         text: message,
         color: Some("#ef4444".to_string()),
@@ -448,15 +1197,15 @@ fn error_line(message: String) -> MetricLine {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::plugin_engine::manifest::{LoadedPlugin, PluginManifest};
+    use crate::plugin_engine::test_dir;
+    use crate::plugin_engine::manifest::{LoadedPlugin, PluginManifest, PluginSupport};
     use serde_json::Value as JsonValue;
     use std::path::PathBuf;
-    use std::time::{SystemTime, UNIX_EPOCH};
 
     fn test_plugin(entry_script: &str) -> LoadedPlugin {
         LoadedPlugin {
             manifest: PluginManifest {
-                schema_version: 1,
+                schema_version: 2,
                 id: "test".to_string(),
                 name: "Test".to_string(),
                 version: "0.0.0".to_string(),
@@ -464,21 +1213,22 @@ mod tests {
                 icon: "icon.svg".to_string(),
                 brand_color: None,
                 lines: vec![],
+                strict_lines: false,
+                links: vec![],
+                accounts: vec![],
+                settings: vec![],
+                platforms: vec![],
+                permissions: vec![],
+                refresh_interval: None,
+                min_app_version: None,
             },
             plugin_dir: PathBuf::from("."),
             entry_script: entry_script.to_string(),
             icon_data_url: "data:image/svg+xml;base64,".to_string(),
+            support: PluginSupport::Supported,
         }
     }
 
-    fn temp_app_dir(label: &str) -> PathBuf {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_nanos();
-        std::env::temp_dir().join(format!("openusage-test-{}-{}", label, nanos))
-    }
-
     fn error_text(output: PluginOutput) -> String {
         match output.lines.first() {
             Some(MetricLine::Badge { text, .. }) => text.clone(),
@@ -497,7 +1247,7 @@ mod tests {
             };
             "#,
         );
-        let output = run_probe(&plugin, &temp_app_dir("sync"), "0.0.0");
+        let output = run_probe(&plugin, &test_dir("sync"), "0.0.0", &SettingValues::new());
         assert_eq!(error_text(output), "boom");
     }
 
@@ -512,13 +1262,221 @@ mod tests {
             };
             "#,
         );
-        let output = run_probe(&plugin, &temp_app_dir("async"), "0.0.0");
+        let output = run_probe(&plugin, &test_dir("async"), "0.0.0", &SettingValues::new());
         assert_eq!(error_text(output), "boom");
     }
 
+    #[test]
+    fn run_probes_returns_one_output_per_account() {
+        let mut plugin = test_plugin(
+            r#"
+            globalThis.__openusage_plugin = {
+                probe(ctx) {
+                    return { lines: [ctx.line.text({ label: "Account", value: ctx.account.id })] };
+                }
+            };
+            "#,
+        );
+        for (id, label) in [("personal", "Personal"), ("work", "Work")] {
+            plugin.manifest.accounts.push(crate::plugin_engine::manifest::AccountProfile {
+                id: id.to_string(),
+                label: label.to_string(),
+                env: Default::default(),
+                paths: Default::default(),
+            });
+        }
+
+        let outputs = run_probes(&plugin, &test_dir("accounts"), "0.0.0", &SettingValues::new());
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].account.as_deref(), Some("Personal"));
+        assert_eq!(outputs[1].account.as_deref(), Some("Work"));
+        match &outputs[1].lines[0] {
+            MetricLine::Text { value, .. } => assert_eq!(value, "work"),
+            other => panic!("expected text line, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn run_probes_without_accounts_has_no_account_label() {
+        let plugin = test_plugin(
+            r#"
+            globalThis.__openusage_plugin = {
+                probe(ctx) {
+                    return { lines: [ctx.line.text({ label: "Has account", value: String(!!ctx.account) })] };
+                }
+            };
+            "#,
+        );
+        let outputs = run_probes(&plugin, &test_dir("no-accounts"), "0.0.0", &SettingValues::new());
+        assert_eq!(outputs.len(), 1);
+        assert!(outputs[0].account.is_none());
+    }
+
+    #[test]
+    fn run_probe_exposes_settings_on_ctx() {
+        let plugin = test_plugin(
+            r#"
+            globalThis.__openusage_plugin = {
+                probe(ctx) {
+                    return { lines: [ctx.line.text({ label: "Region", value: ctx.settings.region })] };
+                }
+            };
+            "#,
+        );
+        let mut settings = SettingValues::new();
+        settings.insert("region".to_string(), "eu".into());
+
+        let output = run_probe(&plugin, &test_dir("settings"), "0.0.0", &settings);
+        match &output.lines[0] {
+            MetricLine::Text { value, .. } => assert_eq!(value, "eu"),
+            other => panic!("expected text line, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn currency_format_requires_iso_code() {
+        let plugin = test_plugin(
+            r#"
+            globalThis.__openusage_plugin = {
+                probe(ctx) {
+                    return { lines: [
+                        ctx.line.progress({ label: "Spend", used: 5, limit: 50, format: { kind: "currency", code: "eur" } }),
+                        ctx.line.progress({ label: "Bad", used: 5, limit: 50, format: { kind: "currency", code: "euro" } }),
+                    ] };
+                }
+            };
+            "#,
+        );
+        let output = run_probe(&plugin, &test_dir("currency"), "0.0.0", &SettingValues::new());
+        match &output.lines[0] {
+            MetricLine::Progress { format: ProgressFormat::Currency { code }, .. } => assert_eq!(code, "EUR"),
+            other => panic!("expected currency line, got {:?}", other),
+        }
+        match &output.lines[1] {
+            MetricLine::Badge { text, .. } => assert!(text.contains("ISO 4217"), "{}", text),
+            other => panic!("expected error line, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn typed_formats_are_parsed() {
+        let plugin = test_plugin(
+            r#"
+            globalThis.__openusage_plugin = {
+                probe(ctx) {
+                    return { lines: [
+                        ctx.line.progress({ label: "Tokens", used: 5, limit: 50, format: { kind: "tokens" } }),
+                        ctx.line.progress({ label: "Time", used: 5, limit: 50, format: { kind: "duration", unit: "hours" } }),
+                        ctx.line.progress({ label: "Bad", used: 5, limit: 50, format: { kind: "duration", unit: "days" } }),
+                    ] };
+                }
+            };
+            "#,
+        );
+        let output = run_probe(&plugin, &test_dir("typed"), "0.0.0", &SettingValues::new());
+        let formats: Vec<_> = output.lines[..2]
+            .iter()
+            .map(|line| match line {
+                MetricLine::Progress { format, .. } => format.clone(),
+                other => panic!("expected progress line, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            formats,
+            vec![
+                ProgressFormat::Tokens,
+                ProgressFormat::Duration { unit: DurationUnit::Hours }
+            ]
+        );
+        match &output.lines[2] {
+            MetricLine::Badge { text, .. } => assert!(text.contains("requires unit"), "{}", text),
+            other => panic!("expected error line, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn sections_group_lines() {
+        let plugin = test_plugin(
+            r#"
+            globalThis.__openusage_plugin = {
+                probe(ctx) {
+                    var percent = { kind: "percent" };
+                    return { sections: [
+                        { title: "Gemini 3 Pro", lines: [ctx.line.progress({ label: "Requests", used: 20, limit: 100, format: percent })] },
+                        { title: "Claude", collapsed: true, lines: [ctx.line.text({ label: "Status", value: "ok" })] },
+                        { title: "Claude", lines: [ctx.line.text({ label: "Again", value: "x" })] },
+                        { title: "Empty", lines: [] },
+                        { lines: [] },
+                    ] };
+                }
+            };
+            "#,
+        );
+        let output = run_probe(&plugin, &test_dir("sections"), "0.0.0", &SettingValues::new());
+        let titles: Vec<_> = output
+            .sections
+            .iter()
+            .map(|section| (section.title.as_str(), section.collapsed, section.lines.len()))
+            .collect();
+        assert_eq!(titles, vec![("Gemini 3 Pro", false, 1), ("Claude", true, 1)]);
+
+        let errors: Vec<String> = output
+            .lines
+            .iter()
+            .map(|line| match line {
+                MetricLine::Badge { text, .. } => text.clone(),
+                other => panic!("expected error line, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "section 'Claude' is duplicated",
+                "section 'Empty' has no lines",
+                "section at index 4 missing title",
+            ]
+        );
+        assert_eq!(output.all_lines().count(), 5);
+    }
+
+    #[test]
+    fn run_probe_reports_missing_required_setting() {
+        let mut plugin = test_plugin(
+            r#"
+            globalThis.__openusage_plugin = {
+                probe() { return { lines: [] }; }
+            };
+            "#,
+        );
+        plugin.manifest.settings = serde_json::from_str(
+            r#"[{ "key": "orgId", "label": "Organization", "type": "string", "required": true }]"#,
+        )
+        .expect("settings parse");
+
+        let output = run_probe(&plugin, &test_dir("required"), "0.0.0", &SettingValues::new());
+        assert_eq!(
+            error_text(output),
+            "Missing required setting: Organization. Set it in plugin settings."
+        );
+    }
+
+    #[test]
+    fn run_probes_skips_unsupported_plugins() {
+        let mut plugin = test_plugin(r#"throw "should not run";"#);
+        plugin.support = PluginSupport::Unsupported("Test is only available on Windows.".to_string());
+
+        let outputs = run_probes(&plugin, &test_dir("unsupported"), "0.0.0", &SettingValues::new());
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(
+            error_text(outputs.into_iter().next().unwrap()),
+            "Test is only available on Windows."
+        );
+    }
+
     #[test]
     fn progress_resets_at_serializes_as_resets_at_camelcase() {
         let line = MetricLine::Progress {
+            id: None,
             label: "Session".to_string(),
             used: 1.0,
             limit: 100.0,
@@ -526,6 +1484,11 @@ mod tests {
             resets_at: Some("2099-01-01T00:00:00.000Z".to_string()),
             period_duration_ms: None,
             color: None,
+            segments: Vec::new(),
+            soft_limit: None,
+            hard_limit: None,
+            converted: None,
+            humanized: None,
         };
 
         let json: JsonValue = serde_json::to_value(&line).expect("serialize");
@@ -533,4 +1496,267 @@ mod tests {
         assert!(obj.get("resetsAt").is_some(), "expected resetsAt key");
         assert!(obj.get("resets_at").is_none(), "did not expect resets_at key");
     }
+
+    #[test]
+    fn structured_lines_round_trip_through_serde() {
+        let lines = vec![
+            MetricLine::Sparkline {
+                id: None,
+                label: "Daily tokens".to_string(),
+                points: vec![
+                    SparkPoint { value: 1.5, at: Some("2026-10-01T00:00:00Z".to_string()) },
+                    SparkPoint { value: 3.0, at: None },
+                ],
+                unit: "tokens".to_string(),
+                color: Some("#22c55e".to_string()),
+                subtitle: None,
+            },
+            MetricLine::Table {
+                id: None,
+                label: "Models".to_string(),
+                columns: vec!["Model".to_string(), "Cost".to_string()],
+                rows: vec![vec!["gpt-5".to_string(), "$1.20".to_string()]],
+            },
+            MetricLine::Link {
+                id: None,
+                label: "Dashboard".to_string(),
+                url: "https://example.com/usage".to_string(),
+                subtitle: None,
+            },
+        ];
+
+        let json = serde_json::to_value(&lines).expect("serialize");
+        assert_eq!(json[0]["type"], "sparkline");
+        assert_eq!(json[1]["type"], "table");
+        assert_eq!(json[2]["type"], "link");
+        let back: Vec<MetricLine> = serde_json::from_value(json).expect("deserialize");
+        assert_eq!(back, lines);
+    }
+
+    #[test]
+    fn structured_lines_are_validated() {
+        let plugin = test_plugin(
+            r#"
+            globalThis.__openusage_plugin = {
+                probe(ctx) {
+                    return { lines: [
+                        ctx.line.sparkline({ label: "Trend", unit: "req", points: [1, { value: 2, at: "2026-10-01T00:00:00Z" }] }),
+                        ctx.line.table({ label: "Models", columns: ["Model", "Tokens"], rows: [["a", 1200], ["b", null]] }),
+                        ctx.line.link({ label: "Billing", url: "https://example.com/billing" }),
+                        ctx.line.sparkline({ label: "Bad", unit: "req", points: [1, "x"] }),
+                        ctx.line.table({ label: "Bad", columns: ["A", "B"], rows: [["only one"]] }),
+                        ctx.line.link({ label: "Bad", url: "javascript:alert(1)" }),
+                    ] };
+                }
+            };
+            "#,
+        );
+        let output = run_probe(&plugin, &test_dir("structured"), "0.0.0", &SettingValues::new());
+        assert_eq!(output.lines.len(), 6);
+        match &output.lines[0] {
+            MetricLine::Sparkline { points, .. } => {
+                assert_eq!(points.len(), 2);
+                assert_eq!(points[1].at.as_deref(), Some("2026-10-01T00:00:00Z"));
+            }
+            other => panic!("expected sparkline, got {:?}", other),
+        }
+        match &output.lines[1] {
+            MetricLine::Table { rows, .. } => assert_eq!(rows[0], vec!["a", "1200"]),
+            other => panic!("expected table, got {:?}", other),
+        }
+        assert!(matches!(&output.lines[2], MetricLine::Link { url, .. } if url == "https://example.com/billing"));
+
+        let errors: Vec<String> = output.lines[3..]
+            .iter()
+            .map(|line| match line {
+                MetricLine::Badge { text, .. } => text.clone(),
+                other => panic!("expected error line, got {:?}", other),
+            })
+            .collect();
+        assert!(errors[0].contains("sparkline line at index 3: point 1"), "{}", errors[0]);
+        assert!(errors[1].contains("row 0 must be an array of 2 cells"), "{}", errors[1]);
+        assert!(errors[2].contains("http(s) URL"), "{}", errors[2]);
+    }
+
+    #[test]
+    fn segmented_progress_is_validated() {
+        let plugin = test_plugin(
+            r##"
+            globalThis.__openusage_plugin = {
+                probe(ctx) {
+                    var format = { kind: "count", suffix: "requests" };
+                    return { lines: [
+                        ctx.line.progress({ label: "Requests", limit: 500, format: format, hardLimit: 800, softLimit: 450,
+                            segments: [{ kind: "overage", used: 40, color: "#ef4444" }, { kind: "included", used: 500, color: "red;x:url(y)" }] }),
+                        ctx.line.progress({ label: "Mismatch", used: 10, limit: 500, format: format,
+                            segments: [{ kind: "included", used: 12 }] }),
+                        ctx.line.progress({ label: "Past limit", limit: 500, format: format,
+                            segments: [{ kind: "included", used: 450 }, { kind: "reserved", used: 60 }] }),
+                        ctx.line.progress({ label: "Kind", limit: 500, format: format, segments: [{ kind: "bonus", used: 1 }] }),
+                        ctx.line.progress({ label: "Hard", used: 1, limit: 500, format: format, hardLimit: 400 }),
+                        ctx.line.progress({ label: "Soft", used: 1, limit: 500, format: format, softLimit: 900, hardLimit: 800 }),
+                        ctx.line.progress({ label: "Dup", limit: 500, format: format,
+                            segments: [{ kind: "included", used: 1 }, { kind: "overage", used: 1, label: "Included" }] }),
+                    ] };
+                }
+            };
+            "##,
+        );
+        let output = run_probe(&plugin, &test_dir("segments"), "0.0.0", &SettingValues::new());
+        match &output.lines[0] {
+            MetricLine::Progress {
+                used,
+                segments,
+                soft_limit,
+                hard_limit,
+                ..
+            } => {
+                assert_eq!(*used, 540.0);
+                let kinds: Vec<_> = segments.iter().map(|s| (s.kind, s.label.as_str())).collect();
+                assert_eq!(
+                    kinds,
+                    vec![(SegmentKind::Included, "Included"), (SegmentKind::Overage, "Overage")]
+                );
+                let colors: Vec<_> = segments.iter().map(|s| s.color.as_deref()).collect();
+                assert_eq!(colors, vec![None, Some("#ef4444")]);
+                assert_eq!((*soft_limit, *hard_limit), (Some(450.0), Some(800.0)));
+            }
+            other => panic!("expected progress, got {:?}", other),
+        }
+
+        let errors: Vec<String> = output.lines[1..]
+            .iter()
+            .map(|line| match line {
+                MetricLine::Badge { text, .. } => text.clone(),
+                other => panic!("expected error line, got {:?}", other),
+            })
+            .collect();
+        assert!(errors[0].contains("must equal the sum of its segments"), "{}", errors[0]);
+        assert!(errors[1].contains("exceed limit"), "{}", errors[1]);
+        assert!(errors[2].contains("segment 0 kind"), "{}", errors[2]);
+        assert!(errors[3].contains("hardLimit must be at least limit"), "{}", errors[3]);
+        assert!(errors[4].contains("softLimit must not exceed hardLimit"), "{}", errors[4]);
+        assert!(errors[5].contains("'Included' is duplicated"), "{}", errors[5]);
+    }
+
+    #[test]
+    fn manifest_segment_colors_fill_unset_colors() {
+        let mut plugin = test_plugin(
+            r##"
+            globalThis.__openusage_plugin = {
+                probe(ctx) {
+                    return { lines: [ctx.line.progress({ label: "Usage", limit: 20, format: { kind: "dollars" },
+                        segments: [
+                            { kind: "included", used: 20, color: "#000000" },
+                            { kind: "reserved", used: 0 },
+                            { kind: "overage", label: "On-demand", used: 5 },
+                        ] })] };
+                }
+            };
+            "##,
+        );
+        plugin.manifest.lines = serde_json::from_str(
+            r##"[{ "type": "progress", "label": "Usage", "scope": "overview",
+                   "segmentColors": { "included": "#22c55e", "overage": "#f97316", "On-demand": "#ef4444" } }]"##,
+        )
+        .unwrap();
+        let output = run_probe(&plugin, &test_dir("segment-colors"), "0.0.0", &SettingValues::new());
+        match &output.lines[0] {
+            MetricLine::Progress { segments, .. } => {
+                let colors: Vec<_> = segments.iter().map(|s| s.color.as_deref()).collect();
+                assert_eq!(colors, vec![Some("#000000"), None, Some("#ef4444")]);
+            }
+            other => panic!("expected progress, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn output_lines_are_mapped_to_declared_lines() {
+        let mut plugin = test_plugin(
+            r#"
+            globalThis.__openusage_plugin = {
+                probe(ctx) {
+                    var percent = { kind: "percent" };
+                    return { lines: [
+                        ctx.line.progress({ label: "5h window", id: "session", used: 40, limit: 100, format: percent }),
+                        ctx.line.text({ label: "Plan tier", value: "Pro" }),
+                        ctx.line.text({ label: "Debug", value: "x" }),
+                        ctx.line.badge({ label: "Beta", id: "beta", text: "on" }),
+                        ctx.line.text({ label: "Bad", id: "no spaces", value: "x" }),
+                    ] };
+                }
+            };
+            "#,
+        );
+        plugin.manifest.lines = serde_json::from_str(
+            r#"[{ "id": "session", "type": "progress", "label": "Session", "scope": "overview" },
+                { "id": "plan", "type": "text", "label": "Plan tier", "scope": "detail" }]"#,
+        )
+        .unwrap();
+        let summary = |output: &PluginOutput| -> Vec<(Option<String>, String)> {
+            output
+                .lines
+                .iter()
+                .map(|line| match line {
+                    MetricLine::Badge { id: None, label, text, .. } if label == "Error" => (None, text.clone()),
+                    other => (other.id().map(str::to_string), other.label().to_string()),
+                })
+                .collect()
+        };
+
+        let output = run_probe(&plugin, &test_dir("line-contract"), "0.0.0", &SettingValues::new());
+        assert_eq!(
+            summary(&output),
+            vec![
+                (Some("session".to_string()), "5h window".to_string()),
+                (Some("plan".to_string()), "Plan tier".to_string()),
+                (None, "Debug".to_string()),
+                (Some("beta".to_string()), "Beta".to_string()),
+                (None, "line at index 4 invalid id (expected [A-Za-z0-9_-] string)".to_string()),
+            ]
+        );
+
+        plugin.manifest.strict_lines = true;
+        let output = run_probe(&plugin, &test_dir("line-contract-strict"), "0.0.0", &SettingValues::new());
+        let summary = summary(&output);
+        assert_eq!(summary[2], (None, "line 'Debug' is not declared in plugin.json".to_string()));
+        assert_eq!(summary[3], (None, "line 'Beta' has undeclared id 'beta'".to_string()));
+        assert_eq!(summary[1].0.as_deref(), Some("plan"));
+    }
+
+    #[test]
+    fn section_lines_get_declared_ids_and_type_mismatches_are_returned() {
+        let mut plugin = test_plugin(
+            r#"
+            globalThis.__openusage_plugin = {
+                probe(ctx) {
+                    return { sections: [{ title: "Pro", lines: [
+                        ctx.line.progress({ label: "Session", used: 40, limit: 100, format: { kind: "percent" } }),
+                        ctx.line.text({ label: "Debug", value: "x" }),
+                    ] }] };
+                }
+            };
+            "#,
+        );
+        plugin.manifest.lines = serde_json::from_str(
+            r#"[{ "id": "session", "type": "progress", "label": "Session", "scope": "overview" }]"#,
+        )
+        .unwrap();
+        plugin.manifest.strict_lines = true;
+        let output = run_probe(&plugin, &test_dir("section-line-ids"), "0.0.0", &SettingValues::new());
+        let ids: Vec<_> = output.sections[0].lines.iter().map(|line| line.id()).collect();
+        assert_eq!(ids, vec![Some("session"), None]);
+
+        let mut lines = vec![MetricLine::Text {
+            id: None,
+            label: "Session".to_string(),
+            value: "40%".to_string(),
+            color: None,
+            subtitle: None,
+        }];
+        let (undeclared, mismatched) = check_line_contract(&mut lines, &plugin.manifest.lines);
+        assert!(undeclared.is_empty());
+        assert_eq!(mismatched, vec!["line 'Session' is declared as progress but returned as text".to_string()]);
+        assert_eq!(lines[0].id(), Some("session"));
+    }
 }
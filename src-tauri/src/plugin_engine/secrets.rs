@@ -0,0 +1,684 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Prefix for OS credential store entries, so ours are easy to find and never collide
+/// with credentials owned by other apps (e.g. `gh:github.com`).
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const SERVICE_PREFIX: &str = "OpenUsage";
+const MASTER_KEY_FILE: &str = "master.key";
+const VAULT_FILE: &str = "secrets.json";
+const VAULT_VERSION: u32 = 1;
+/// Set to `file` to force the encrypted file backend even where an OS store exists.
+const BACKEND_ENV_VAR: &str = "OPENUSAGE_SECRETS_BACKEND";
+
+/// Storage for plugin secrets. `scope` is the plugin id (plus account id when probing an
+/// account profile); backends must keep scopes isolated from each other.
+pub trait SecretBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn get(&self, scope: &str, key: &str) -> Result<Option<String>, String>;
+    fn set(&self, scope: &str, key: &str, value: &str) -> Result<(), String>;
+    /// Returns whether a secret was removed.
+    fn delete(&self, scope: &str, key: &str) -> Result<bool, String>;
+    /// Keys stored in `scope`, sorted.
+    fn list(&self, scope: &str) -> Result<Vec<String>, String>;
+}
+
+pub fn is_valid_secret_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 128
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+/// OS credential store where one is available, otherwise the encrypted file backend
+/// under `{appDataDir}/secrets`.
+pub fn default_backend(app_data_dir: &Path) -> Arc<dyn SecretBackend> {
+    let file_backend = || -> Arc<dyn SecretBackend> {
+        Arc::new(EncryptedFileBackend::new(app_data_dir.join("secrets")))
+    };
+    if std::env::var(BACKEND_ENV_VAR).is_ok_and(|v| v == "file") {
+        return file_backend();
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Arc::new(KeychainBackend)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        Arc::new(CredentialManagerBackend)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        file_backend()
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn service_name(scope: &str) -> String {
+    format!("{}:{}", SERVICE_PREFIX, scope)
+}
+
+// --- Encrypted file backend ---
+
+/// Serializes vault read-modify-write cycles; probes for different plugins run in parallel.
+static VAULT_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    /// scope -> key -> base64(nonce || ciphertext || tag)
+    entries: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Secrets encrypted with ChaCha20-Poly1305 under a random master key stored next to the
+/// vault (`master.key`, owner-only permissions). Each value is bound to its scope and key
+/// as associated data, so entries cannot be swapped between plugins.
+pub struct EncryptedFileBackend {
+    dir: PathBuf,
+}
+
+impl EncryptedFileBackend {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn master_key(&self) -> Result<LessSafeKey, String> {
+        let path = self.dir.join(MASTER_KEY_FILE);
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => self.create_master_key(&path)?,
+            Err(e) => return Err(format!("failed to read master key: {}", e)),
+        };
+        let unbound = UnboundKey::new(&CHACHA20_POLY1305, &bytes)
+            .map_err(|_| "master key is corrupt".to_string())?;
+        Ok(LessSafeKey::new(unbound))
+    }
+
+    fn create_master_key(&self, path: &Path) -> Result<Vec<u8>, String> {
+        use std::io::Write;
+
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("failed to create secrets dir: {}", e))?;
+        let mut key = vec![0u8; CHACHA20_POLY1305.key_len()];
+        SystemRandom::new()
+            .fill(&mut key)
+            .map_err(|_| "failed to generate master key".to_string())?;
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(path)
+            .map_err(|e| format!("failed to create master key: {}", e))?;
+        file.write_all(&key)
+            .and_then(|_| file.sync_all())
+            .map_err(|e| format!("failed to write master key: {}", e))?;
+        Ok(key)
+    }
+
+    fn load(&self) -> Result<VaultFile, String> {
+        let path = self.dir.join(VAULT_FILE);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(VaultFile {
+                    version: VAULT_VERSION,
+                    ..Default::default()
+                });
+            }
+            Err(e) => return Err(format!("failed to read secrets: {}", e)),
+        };
+        let vault: VaultFile =
+            serde_json::from_str(&text).map_err(|e| format!("secrets file is corrupt: {}", e))?;
+        if vault.version != VAULT_VERSION {
+            return Err(format!("unsupported secrets file version {}", vault.version));
+        }
+        Ok(vault)
+    }
+
+    fn save(&self, vault: &VaultFile) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("failed to create secrets dir: {}", e))?;
+        let text = serde_json::to_string_pretty(vault).map_err(|e| e.to_string())?;
+        let path = self.dir.join(VAULT_FILE);
+        let tmp = self.dir.join(format!("{}.tmp", VAULT_FILE));
+        std::fs::write(&tmp, text)
+            .and_then(|_| std::fs::rename(&tmp, &path))
+            .map_err(|e| format!("failed to write secrets: {}", e))
+    }
+
+    fn encrypt(key: &LessSafeKey, scope: &str, name: &str, value: &str) -> Result<String, String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| "failed to generate nonce".to_string())?;
+        let mut data = value.as_bytes().to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(associated_data(scope, name).as_bytes()),
+            &mut data,
+        )
+        .map_err(|_| "failed to encrypt secret".to_string())?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&data);
+        Ok(STANDARD.encode(sealed))
+    }
+
+    fn decrypt(key: &LessSafeKey, scope: &str, name: &str, sealed: &str) -> Result<String, String> {
+        let bytes = STANDARD
+            .decode(sealed)
+            .map_err(|_| format!("secret '{}' is corrupt", name))?;
+        if bytes.len() < NONCE_LEN {
+            return Err(format!("secret '{}' is corrupt", name));
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| format!("secret '{}' is corrupt", name))?;
+        let mut data = ciphertext.to_vec();
+        let plain = key
+            .open_in_place(
+                nonce,
+                Aad::from(associated_data(scope, name).as_bytes()),
+                &mut data,
+            )
+            .map_err(|_| format!("secret '{}' could not be decrypted", name))?;
+        String::from_utf8(plain.to_vec()).map_err(|_| format!("secret '{}' is not UTF-8", name))
+    }
+}
+
+fn associated_data(scope: &str, key: &str) -> String {
+    format!("{}\n{}", scope, key)
+}
+
+impl SecretBackend for EncryptedFileBackend {
+    fn name(&self) -> &'static str {
+        "encrypted-file"
+    }
+
+    fn get(&self, scope: &str, key: &str) -> Result<Option<String>, String> {
+        let _guard = VAULT_LOCK.lock().map_err(|_| "secrets lock poisoned")?;
+        let vault = self.load()?;
+        match vault.entries.get(scope).and_then(|entries| entries.get(key)) {
+            Some(sealed) => Self::decrypt(&self.master_key()?, scope, key, sealed).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn set(&self, scope: &str, key: &str, value: &str) -> Result<(), String> {
+        let _guard = VAULT_LOCK.lock().map_err(|_| "secrets lock poisoned")?;
+        let mut vault = self.load()?;
+        let sealed = Self::encrypt(&self.master_key()?, scope, key, value)?;
+        vault
+            .entries
+            .entry(scope.to_string())
+            .or_default()
+            .insert(key.to_string(), sealed);
+        self.save(&vault)
+    }
+
+    fn delete(&self, scope: &str, key: &str) -> Result<bool, String> {
+        let _guard = VAULT_LOCK.lock().map_err(|_| "secrets lock poisoned")?;
+        let mut vault = self.load()?;
+        let Some(entries) = vault.entries.get_mut(scope) else {
+            return Ok(false);
+        };
+        if entries.remove(key).is_none() {
+            return Ok(false);
+        }
+        if entries.is_empty() {
+            vault.entries.remove(scope);
+        }
+        self.save(&vault)?;
+        Ok(true)
+    }
+
+    fn list(&self, scope: &str) -> Result<Vec<String>, String> {
+        let _guard = VAULT_LOCK.lock().map_err(|_| "secrets lock poisoned")?;
+        let vault = self.load()?;
+        Ok(vault
+            .entries
+            .get(scope)
+            .map(|entries| entries.keys().cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+// --- macOS Keychain backend ---
+
+/// Generic passwords with service `OpenUsage:<scope>` and the secret key as account,
+/// via the `security` CLI (same mechanism as `host.keychain`).
+#[cfg(target_os = "macos")]
+pub struct KeychainBackend;
+
+/// `security` exit status for "The specified item could not be found in the keychain."
+#[cfg(target_os = "macos")]
+const SEC_ITEM_NOT_FOUND: i32 = 44;
+
+#[cfg(target_os = "macos")]
+impl KeychainBackend {
+    fn security(args: &[&str]) -> Result<std::process::Output, String> {
+        std::process::Command::new("security")
+            .args(args)
+            .output()
+            .map_err(|e| format!("keychain command failed: {}", e))
+    }
+
+    fn failure(output: &std::process::Output) -> String {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        format!("keychain error: {}", stderr.lines().next().unwrap_or("").trim())
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl SecretBackend for KeychainBackend {
+    fn name(&self) -> &'static str {
+        "keychain"
+    }
+
+    fn get(&self, scope: &str, key: &str) -> Result<Option<String>, String> {
+        let service = service_name(scope);
+        let output = Self::security(&["find-generic-password", "-s", &service, "-a", key, "-w"])?;
+        if output.status.success() {
+            let value = String::from_utf8_lossy(&output.stdout);
+            return Ok(Some(value.trim_end_matches('\n').to_string()));
+        }
+        if output.status.code() == Some(SEC_ITEM_NOT_FOUND) {
+            return Ok(None);
+        }
+        Err(Self::failure(&output))
+    }
+
+    fn set(&self, scope: &str, key: &str, value: &str) -> Result<(), String> {
+        let (command, input) = add_password_invocation(&service_name(scope), key, value)?;
+        let output = run_with_input(command, &input)?;
+        // Interactive mode exits 0 even when a command fails; errors only show up on stderr.
+        if output.status.success() && output.stderr.is_empty() {
+            Ok(())
+        } else {
+            Err(Self::failure(&output))
+        }
+    }
+
+    fn delete(&self, scope: &str, key: &str) -> Result<bool, String> {
+        let service = service_name(scope);
+        let output = Self::security(&["delete-generic-password", "-s", &service, "-a", key])?;
+        if output.status.success() {
+            return Ok(true);
+        }
+        if output.status.code() == Some(SEC_ITEM_NOT_FOUND) {
+            return Ok(false);
+        }
+        Err(Self::failure(&output))
+    }
+
+    fn list(&self, scope: &str) -> Result<Vec<String>, String> {
+        let output = Self::security(&["dump-keychain"])?;
+        if !output.status.success() {
+            return Err(Self::failure(&output));
+        }
+        let dump = String::from_utf8_lossy(&output.stdout);
+        Ok(accounts_for_service(&dump, &service_name(scope)))
+    }
+}
+
+/// `security -i` and the command line it reads from stdin to store a password. The secret
+/// goes on stdin so it never appears in the process arguments, where any local user could
+/// read it.
+#[cfg(any(target_os = "macos", test))]
+fn add_password_invocation(
+    service: &str,
+    account: &str,
+    value: &str,
+) -> Result<(std::process::Command, String), String> {
+    let mut command = std::process::Command::new("security");
+    command
+        .arg("-i")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped());
+    Ok((command, add_password_command(service, account, value)?))
+}
+
+/// Runs `command` with `input` written to its stdin and waits for it to exit.
+#[cfg(any(target_os = "macos", test))]
+fn run_with_input(mut command: std::process::Command, input: &str) -> Result<std::process::Output, String> {
+    use std::io::Write;
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("keychain command failed: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| format!("keychain command failed: {}", e))?;
+    }
+    child
+        .wait_with_output()
+        .map_err(|e| format!("keychain command failed: {}", e))
+}
+
+#[cfg(any(target_os = "macos", test))]
+fn add_password_command(service: &str, account: &str, value: &str) -> Result<String, String> {
+    fn quote(arg: &str) -> String {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    if [service, account, value].iter().any(|arg| arg.contains(['\n', '\r'])) {
+        return Err("keychain values cannot contain line breaks".to_string());
+    }
+    Ok(format!(
+        "add-generic-password -U -s {} -a {} -w {}\n",
+        quote(service),
+        quote(account),
+        quote(value)
+    ))
+}
+
+/// Accounts of generic passwords with the given service in `security dump-keychain` output.
+#[cfg(any(target_os = "macos", test))]
+fn accounts_for_service(dump: &str, service: &str) -> Vec<String> {
+    fn blob_value<'a>(line: &'a str, attr: &str) -> Option<&'a str> {
+        let marker = format!("\"{}\"<blob>=\"", attr);
+        let start = line.find(&marker)? + marker.len();
+        let rest = &line[start..];
+        Some(&rest[..rest.rfind('"')?])
+    }
+
+    let mut accounts = Vec::new();
+    let mut account: Option<String> = None;
+    let mut matches_service = false;
+    let mut flush = |account: &mut Option<String>, matches_service: &mut bool| {
+        if let Some(name) = account.take().filter(|_| *matches_service) {
+            accounts.push(name);
+        }
+        *matches_service = false;
+    };
+    for line in dump.lines() {
+        if line.starts_with("keychain:") {
+            flush(&mut account, &mut matches_service);
+        } else if let Some(value) = blob_value(line, "acct") {
+            account = Some(value.to_string());
+        } else if let Some(value) = blob_value(line, "svce") {
+            matches_service = value == service;
+        }
+    }
+    flush(&mut account, &mut matches_service);
+    accounts.sort();
+    accounts.dedup();
+    accounts
+}
+
+// --- Windows Credential Manager backend ---
+
+/// Generic credentials with target `OpenUsage:<scope>/<key>`.
+#[cfg(target_os = "windows")]
+pub struct CredentialManagerBackend;
+
+#[cfg(target_os = "windows")]
+impl SecretBackend for CredentialManagerBackend {
+    fn name(&self) -> &'static str {
+        "credential-manager"
+    }
+
+    fn get(&self, scope: &str, key: &str) -> Result<Option<String>, String> {
+        wincred::read(&format!("{}/{}", service_name(scope), key))
+    }
+
+    fn set(&self, scope: &str, key: &str, value: &str) -> Result<(), String> {
+        wincred::write(&format!("{}/{}", service_name(scope), key), key, value)
+    }
+
+    fn delete(&self, scope: &str, key: &str) -> Result<bool, String> {
+        wincred::delete(&format!("{}/{}", service_name(scope), key))
+    }
+
+    fn list(&self, scope: &str) -> Result<Vec<String>, String> {
+        let prefix = format!("{}/", service_name(scope));
+        let mut keys: Vec<String> = wincred::enumerate(&format!("{}*", prefix))?
+            .into_iter()
+            .filter_map(|target| target.strip_prefix(&prefix).map(str::to_string))
+            // Account scopes nest under the plugin scope
+            .filter(|key| !key.contains('/'))
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod wincred {
+    use windows_sys::Win32::Foundation::{GetLastError, ERROR_NOT_FOUND};
+    use windows_sys::Win32::Security::Credentials::{
+        CredDeleteW, CredEnumerateW, CredFree, CredReadW, CredWriteW, CREDENTIALW,
+        CRED_PERSIST_LOCAL_MACHINE, CRED_TYPE_GENERIC,
+    };
+
+    fn wide(value: &str) -> Vec<u16> {
+        value.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    /// # Safety
+    /// `ptr` must be null or point to a NUL-terminated UTF-16 string.
+    unsafe fn from_wide(ptr: *const u16) -> String {
+        if ptr.is_null() {
+            return String::new();
+        }
+        let mut len = 0;
+        while unsafe { *ptr.add(len) } != 0 {
+            len += 1;
+        }
+        String::from_utf16_lossy(unsafe { std::slice::from_raw_parts(ptr, len) })
+    }
+
+    pub fn read(target: &str) -> Result<Option<String>, String> {
+        let target = wide(target);
+        let mut credential: *mut CREDENTIALW = std::ptr::null_mut();
+        if unsafe { CredReadW(target.as_ptr(), CRED_TYPE_GENERIC, 0, &mut credential) } == 0 {
+            let err = unsafe { GetLastError() };
+            if err == ERROR_NOT_FOUND {
+                return Ok(None);
+            }
+            return Err(format!("credential read failed (error {})", err));
+        }
+        let value = unsafe {
+            let credential = &*credential;
+            let blob = std::slice::from_raw_parts(
+                credential.CredentialBlob,
+                credential.CredentialBlobSize as usize,
+            );
+            String::from_utf8_lossy(blob).into_owned()
+        };
+        unsafe { CredFree(credential as *const _) };
+        Ok(Some(value))
+    }
+
+    pub fn write(target: &str, user: &str, value: &str) -> Result<(), String> {
+        let mut target = wide(target);
+        let mut user = wide(user);
+        let mut blob = value.as_bytes().to_vec();
+        let credential = CREDENTIALW {
+            Type: CRED_TYPE_GENERIC,
+            TargetName: target.as_mut_ptr(),
+            CredentialBlobSize: blob.len() as u32,
+            CredentialBlob: blob.as_mut_ptr(),
+            Persist: CRED_PERSIST_LOCAL_MACHINE,
+            UserName: user.as_mut_ptr(),
+            ..Default::default()
+        };
+        if unsafe { CredWriteW(&credential, 0) } == 0 {
+            return Err(format!("credential write failed (error {})", unsafe {
+                GetLastError()
+            }));
+        }
+        Ok(())
+    }
+
+    pub fn delete(target: &str) -> Result<bool, String> {
+        let target = wide(target);
+        if unsafe { CredDeleteW(target.as_ptr(), CRED_TYPE_GENERIC, 0) } == 0 {
+            let err = unsafe { GetLastError() };
+            if err == ERROR_NOT_FOUND {
+                return Ok(false);
+            }
+            return Err(format!("credential delete failed (error {})", err));
+        }
+        Ok(true)
+    }
+
+    pub fn enumerate(filter: &str) -> Result<Vec<String>, String> {
+        let filter = wide(filter);
+        let mut count = 0u32;
+        let mut credentials: *mut *mut CREDENTIALW = std::ptr::null_mut();
+        if unsafe { CredEnumerateW(filter.as_ptr(), 0, &mut count, &mut credentials) } == 0 {
+            let err = unsafe { GetLastError() };
+            if err == ERROR_NOT_FOUND {
+                return Ok(Vec::new());
+            }
+            return Err(format!("credential enumerate failed (error {})", err));
+        }
+        let targets = unsafe {
+            std::slice::from_raw_parts(credentials, count as usize)
+                .iter()
+                .map(|credential| from_wide((**credential).TargetName))
+                .collect()
+        };
+        unsafe { CredFree(credentials as *const _) };
+        Ok(targets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_engine::test_dir;
+
+    #[test]
+    fn file_backend_round_trips_and_scopes_by_plugin() {
+        let dir = test_dir("roundtrip");
+        let backend = EncryptedFileBackend::new(dir.clone());
+
+        backend.set("copilot", "token", "ghu_secret").unwrap();
+        backend.set("copilot", "refresh", "r1").unwrap();
+        backend.set("codex", "token", "other").unwrap();
+
+        assert_eq!(backend.get("copilot", "token").unwrap().as_deref(), Some("ghu_secret"));
+        assert_eq!(backend.get("codex", "token").unwrap().as_deref(), Some("other"));
+        assert_eq!(backend.get("claude", "token").unwrap(), None);
+        assert_eq!(backend.list("copilot").unwrap(), vec!["refresh", "token"]);
+
+        assert!(backend.delete("copilot", "token").unwrap());
+        assert!(!backend.delete("copilot", "token").unwrap());
+        assert_eq!(backend.list("copilot").unwrap(), vec!["refresh"]);
+
+        // Values are not stored in plain text
+        let raw = std::fs::read_to_string(dir.join(VAULT_FILE)).unwrap();
+        assert!(!raw.contains("other") && !raw.contains("r1\""));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn file_backend_rejects_values_moved_between_scopes() {
+        let dir = test_dir("aad");
+        let backend = EncryptedFileBackend::new(dir.clone());
+        backend.set("a", "token", "secret-a").unwrap();
+
+        let mut vault = backend.load().unwrap();
+        let sealed = vault.entries["a"]["token"].clone();
+        vault.entries.entry("b".to_string()).or_default().insert("token".to_string(), sealed);
+        backend.save(&vault).unwrap();
+
+        assert!(backend.get("b", "token").is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn file_backend_fails_with_a_different_master_key() {
+        let dir = test_dir("rekey");
+        let backend = EncryptedFileBackend::new(dir.clone());
+        backend.set("a", "token", "secret").unwrap();
+
+        std::fs::remove_file(dir.join(MASTER_KEY_FILE)).unwrap();
+        let err = backend.get("a", "token").unwrap_err();
+        assert!(err.contains("could not be decrypted"), "{}", err);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn master_key_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = test_dir("perms");
+        let backend = EncryptedFileBackend::new(dir.clone());
+        backend.set("a", "k", "v").unwrap();
+        let mode = std::fs::metadata(dir.join(MASTER_KEY_FILE)).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn secret_keys_are_restricted() {
+        assert!(is_valid_secret_key("token"));
+        assert!(is_valid_secret_key("oauth.refresh_token-2"));
+        assert!(!is_valid_secret_key(""));
+        assert!(!is_valid_secret_key("../token"));
+        assert!(!is_valid_secret_key(&"a".repeat(129)));
+    }
+
+    #[test]
+    fn keychain_set_keeps_the_secret_off_argv() {
+        let secret = r#"hun"ter\2"#;
+        let (command, input) = add_password_invocation("OpenUsage:copilot", "token", secret).unwrap();
+        assert_eq!(command.get_program(), "security");
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, ["-i"]);
+        assert!(!args.iter().any(|arg| arg.to_string_lossy().contains(secret)));
+        assert_eq!(
+            input,
+            "add-generic-password -U -s \"OpenUsage:copilot\" -a \"token\" -w \"hun\\\"ter\\\\2\"\n"
+        );
+        // The input reaches the child on stdin
+        #[cfg(unix)]
+        {
+            let mut cat = std::process::Command::new("cat");
+            cat.stdin(std::process::Stdio::piped()).stdout(std::process::Stdio::piped());
+            let output = run_with_input(cat, &input).unwrap();
+            assert_eq!(String::from_utf8_lossy(&output.stdout), input);
+        }
+        assert!(add_password_command("OpenUsage:copilot", "token", "a\nb").is_err());
+    }
+
+    #[test]
+    fn parses_accounts_from_keychain_dump() {
+        let dump = r#"keychain: "/Users/me/Library/Keychains/login.keychain-db"
+class: "genp"
+attributes:
+    "acct"<blob>="token"
+    "svce"<blob>="OpenUsage:copilot"
+keychain: "/Users/me/Library/Keychains/login.keychain-db"
+class: "genp"
+attributes:
+    "acct"<blob>="me"
+    "svce"<blob>="gh:github.com"
+keychain: "/Users/me/Library/Keychains/login.keychain-db"
+class: "genp"
+attributes:
+    "acct"<blob>="refresh"
+    "svce"<blob>="OpenUsage:copilot"
+"#;
+        assert_eq!(
+            accounts_for_service(dump, "OpenUsage:copilot"),
+            vec!["refresh", "token"]
+        );
+    }
+}
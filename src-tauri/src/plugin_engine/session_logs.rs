@@ -0,0 +1,730 @@
+//! Built-in provider that totals token usage and cost from local Claude Code and Codex
+//! session logs. Logs are read incrementally with `logs::scan_jsonl`; hourly totals per
+//! source and model are kept in the provider's data dir and summarized per day and per
+//! 5-hour block.
+
+use crate::plugin_engine::logs::{self, ScanOpts};
+use crate::plugin_engine::manifest::{
+    LineScope, ManifestLine, PluginManifest, PluginSupport, CURRENT_SCHEMA_VERSION,
+};
+use crate::plugin_engine::pricing::{self, Catalog, TokenUsage};
+use crate::plugin_engine::provider::{Capabilities, ProbeContext, Provider, ProviderKind};
+use crate::plugin_engine::runtime::{error_line, MetricLine, PluginOutput, ProgressFormat};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use time::{OffsetDateTime, Time, UtcOffset};
+
+pub const ID: &str = "session-logs";
+pub const NAME: &str = "Session Logs";
+
+const USAGE_FILE: &str = "usage.json";
+const HOUR_MS: u64 = 60 * 60 * 1000;
+const DAY_MS: u64 = 24 * HOUR_MS;
+const BLOCK_MS: u64 = 5 * HOUR_MS;
+/// Hourly totals older than this are dropped.
+const RETENTION_MS: u64 = 31 * DAY_MS;
+/// Scans per probe when the byte budget runs out; the rest waits for the next refresh.
+const MAX_SCAN_ROUNDS: usize = 8;
+
+const ICON_SVG: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><path d="M14 2H6a2 2 0 0 0-2 2v16a2 2 0 0 0 2 2h12a2 2 0 0 0 2-2V8z"/><path d="M14 2v6h6"/><path d="M8 13h8"/><path d="M8 17h5"/></svg>"##;
+
+fn manifest_lines() -> Vec<ManifestLine> {
+    let line = |id: &str, line_type: &str, label: &str, scope: LineScope, primary_order: Option<u32>| ManifestLine {
+        id: id.to_string(),
+        line_type: line_type.to_string(),
+        label: label.to_string(),
+        scope,
+        primary_order,
+        segment_colors: BTreeMap::new(),
+        spend: false,
+    };
+    vec![
+        line("block", "progress", "5h Block", LineScope::Overview, Some(1)),
+        line("today", "text", "Today", LineScope::Overview, None),
+        line("last-30-days", "text", "Last 30 Days", LineScope::Detail, None),
+    ]
+}
+
+/// The built-in provider; see `probe`.
+pub struct SessionLogs {
+    manifest: PluginManifest,
+    icon_data_url: String,
+    support: PluginSupport,
+}
+
+impl SessionLogs {
+    pub fn new() -> Self {
+        Self {
+            manifest: PluginManifest {
+                schema_version: CURRENT_SCHEMA_VERSION,
+                id: ID.to_string(),
+                name: NAME.to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                entry: String::new(),
+                icon: String::new(),
+                brand_color: None,
+                lines: manifest_lines(),
+                strict_lines: false,
+                links: Vec::new(),
+                accounts: Vec::new(),
+                settings: Vec::new(),
+                platforms: Vec::new(),
+                permissions: vec!["logs".to_string()],
+                refresh_interval: None,
+                min_app_version: None,
+            },
+            icon_data_url: format!("data:image/svg+xml;base64,{}", STANDARD.encode(ICON_SVG)),
+            support: PluginSupport::Supported,
+        }
+    }
+}
+
+impl Provider for SessionLogs {
+    fn manifest(&self) -> &PluginManifest {
+        &self.manifest
+    }
+
+    fn icon_data_url(&self) -> &str {
+        &self.icon_data_url
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            kind: ProviderKind::Native,
+            accounts: false,
+            aggregate: false,
+        }
+    }
+
+    fn support(&self) -> &PluginSupport {
+        &self.support
+    }
+
+    fn probe(&self, ctx: &ProbeContext<'_>) -> Vec<PluginOutput> {
+        vec![probe(ctx.app_data_dir, &self.icon_data_url, ctx.local_offset)]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Claude,
+    Codex,
+}
+
+impl Source {
+    fn key(self) -> &'static str {
+        match self {
+            Self::Claude => "claude",
+            Self::Codex => "codex",
+        }
+    }
+
+    fn label(key: &str) -> &str {
+        match key {
+            "claude" => "Claude Code",
+            "codex" => "Codex",
+            other => other,
+        }
+    }
+
+    fn fields(self) -> &'static [&'static str] {
+        match self {
+            Self::Claude => &[
+                "timestamp",
+                "type",
+                "requestId",
+                "message.id",
+                "message.model",
+                "message.usage",
+            ],
+            Self::Codex => &[
+                "timestamp",
+                "type",
+                "payload.type",
+                "payload.model",
+                "payload.info.last_token_usage",
+                "payload.info.total_token_usage",
+            ],
+        }
+    }
+}
+
+/// Session log globs: `$CLAUDE_CONFIG_DIR` (comma-separated) or `~/.claude` and
+/// `~/.config/claude` for Claude Code, `$CODEX_HOME` or `~/.codex` for Codex.
+fn log_patterns() -> Vec<(Source, String)> {
+    let home = dirs::home_dir();
+    let mut claude_roots: Vec<PathBuf> = std::env::var("CLAUDE_CONFIG_DIR")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    if claude_roots.is_empty() {
+        claude_roots.extend(
+            home.iter()
+                .flat_map(|home| [home.join(".claude"), home.join(".config").join("claude")]),
+        );
+    }
+    let codex_root = std::env::var_os("CODEX_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| home.join(".codex")));
+
+    let pattern = |root: &Path, sub: &str| {
+        format!("{}/{}/**/*.jsonl", glob::Pattern::escape(&root.to_string_lossy()), sub)
+    };
+    let mut patterns: Vec<(Source, String)> = claude_roots
+        .iter()
+        .filter(|root| root.join("projects").is_dir())
+        .map(|root| (Source::Claude, pattern(root, "projects")))
+        .collect();
+    if let Some(root) = codex_root.filter(|root| root.join("sessions").is_dir()) {
+        patterns.push((Source::Codex, pattern(&root, "sessions")));
+    }
+    patterns
+}
+
+/// One request's usage taken from a log line.
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    timestamp_ms: u64,
+    model: String,
+    usage: TokenUsage,
+    /// Claude Code copies earlier messages into resumed sessions; the message and request
+    /// ids identify them.
+    dedupe_key: Option<String>,
+}
+
+fn timestamp_ms(record: &Value) -> Option<u64> {
+    let text = record.get("timestamp")?.as_str()?;
+    OffsetDateTime::parse(text, &time::format_description::well_known::Rfc3339)
+        .ok()
+        .map(|t| (t.unix_timestamp_nanos() / 1_000_000).max(0) as u64)
+}
+
+fn count(value: &Value, key: &str) -> u64 {
+    value.get(key).and_then(Value::as_u64).unwrap_or(0)
+}
+
+fn claude_entry(record: &Value) -> Option<Entry> {
+    if record.get("type")?.as_str()? != "assistant" {
+        return None;
+    }
+    let message = record.get("message")?;
+    let model = message.get("model")?.as_str()?;
+    if model.starts_with('<') {
+        // `<synthetic>` messages are generated locally and not billed
+        return None;
+    }
+    let usage = message.get("usage")?;
+    let dedupe_key = match (
+        message.get("id").and_then(Value::as_str),
+        record.get("requestId").and_then(Value::as_str),
+    ) {
+        (Some(id), Some(request)) => Some(format!("{}:{}", id, request)),
+        _ => None,
+    };
+    Some(Entry {
+        timestamp_ms: timestamp_ms(record)?,
+        model: model.to_string(),
+        usage: TokenUsage {
+            input: count(usage, "input_tokens"),
+            output: count(usage, "output_tokens"),
+            cache_write: count(usage, "cache_creation_input_tokens"),
+            cache_read: count(usage, "cache_read_input_tokens"),
+        },
+        dedupe_key,
+    })
+}
+
+/// Key under which scanned records carry the path of their log file.
+const PATH_FIELD: &str = "file";
+
+/// What a Codex session file has logged so far that later records depend on.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct CodexSession {
+    model: Option<String>,
+    last_total: Option<u64>,
+    /// Timestamp of the newest record, for pruning.
+    seen_ms: u64,
+}
+
+/// Codex logs the model in `turn_context` records and usage in later `token_count` events,
+/// so entries depend on the records before them in the same file. Events that repeat the
+/// previous running total are duplicates.
+fn codex_entry(record: &Value, session: &mut CodexSession) -> Option<Entry> {
+    let payload = record.get("payload")?;
+    if let Some(at) = timestamp_ms(record) {
+        session.seen_ms = session.seen_ms.max(at);
+    }
+    let (model, last_total) = (&mut session.model, &mut session.last_total);
+    match record.get("type")?.as_str()? {
+        "turn_context" => {
+            if let Some(name) = payload.get("model").and_then(Value::as_str) {
+                *model = Some(name.to_string());
+            }
+            None
+        }
+        "event_msg" if payload.get("type").and_then(Value::as_str) == Some("token_count") => {
+            let info = payload.get("info")?;
+            let last = info.get("last_token_usage")?;
+            let total = info
+                .get("total_token_usage")
+                .and_then(|total| total.get("total_tokens"))
+                .and_then(Value::as_u64);
+            if total.is_some() && total == *last_total {
+                return None;
+            }
+            *last_total = total;
+            // Codex counts cached tokens as part of the input
+            let cached = count(last, "cached_input_tokens");
+            Some(Entry {
+                timestamp_ms: timestamp_ms(record)?,
+                model: model.clone().unwrap_or_else(|| "gpt-5".to_string()),
+                usage: TokenUsage {
+                    input: count(last, "input_tokens").saturating_sub(cached),
+                    output: count(last, "output_tokens"),
+                    cache_write: 0,
+                    cache_read: cached,
+                },
+                dedupe_key: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Hourly totals persisted between probes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct UsageStore {
+    /// Hour start (unix ms) -> source -> model -> usage.
+    hours: BTreeMap<u64, BTreeMap<String, BTreeMap<String, TokenUsage>>>,
+    /// Claude Code dedupe keys already counted, with their timestamp for pruning.
+    seen: HashMap<String, u64>,
+    /// Codex session file path -> state carried between its records.
+    codex_sessions: HashMap<String, CodexSession>,
+}
+
+impl UsageStore {
+    fn record(&mut self, source: Source, entry: Entry) {
+        let duplicate = entry
+            .dedupe_key
+            .is_some_and(|key| self.seen.insert(key, entry.timestamp_ms).is_some());
+        if duplicate {
+            return;
+        }
+        let hour = entry.timestamp_ms - entry.timestamp_ms % HOUR_MS;
+        self.hours
+            .entry(hour)
+            .or_default()
+            .entry(source.key().to_string())
+            .or_default()
+            .entry(entry.model)
+            .or_default()
+            .add(&entry.usage);
+    }
+
+    fn ingest(&mut self, source: Source, records: &[Value]) {
+        for record in records {
+            let entry = match source {
+                Source::Claude => claude_entry(record),
+                Source::Codex => {
+                    let file = record.get(PATH_FIELD).and_then(Value::as_str).unwrap_or_default();
+                    let session = self.codex_sessions.entry(file.to_string()).or_default();
+                    codex_entry(record, session)
+                }
+            };
+            if let Some(entry) = entry {
+                self.record(source, entry);
+            }
+        }
+    }
+
+    fn prune(&mut self, now_ms: u64) {
+        let cutoff = now_ms.saturating_sub(RETENTION_MS);
+        self.hours.retain(|hour, _| *hour >= cutoff);
+        self.seen.retain(|_, at| *at >= cutoff);
+        self.codex_sessions.retain(|_, session| session.seen_ms >= cutoff);
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Totals {
+    usage: TokenUsage,
+    cost: f64,
+    /// Some usage was for models without a price and is not in `cost`.
+    unpriced: bool,
+}
+
+impl Totals {
+    fn add(&mut self, catalog: &Catalog, date: &str, model: &str, usage: &TokenUsage) {
+        self.usage.add(usage);
+        match catalog.cost(model, usage, Some(date)) {
+            Some(cost) => self.cost += cost,
+            None => self.unpriced = true,
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "${:.2}{} · {} tokens",
+            self.cost,
+            if self.unpriced { "+" } else { "" },
+            compact(self.usage.total())
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Summary {
+    today: Totals,
+    last_30_days: Totals,
+    today_by_source: BTreeMap<String, Totals>,
+    today_by_model: BTreeMap<String, Totals>,
+    /// Start (unix ms) and totals of the block containing `now`.
+    active_block: Option<(u64, Totals)>,
+    /// Largest token total of an earlier block, used as the progress scale.
+    max_block_tokens: u64,
+}
+
+/// Blocks start at the first active hour and last five hours; the next activity after a
+/// block ends starts a new one.
+fn summarize(store: &UsageStore, catalog: &Catalog, now: OffsetDateTime) -> Summary {
+    let now_ms = (now.unix_timestamp_nanos() / 1_000_000).max(0) as u64;
+    let midnight = now.replace_time(Time::MIDNIGHT);
+    let today_start = (midnight.unix_timestamp_nanos() / 1_000_000).max(0) as u64;
+    let month_start = now_ms.saturating_sub(30 * DAY_MS);
+
+    let mut summary = Summary::default();
+    let mut blocks: Vec<(u64, Totals)> = Vec::new();
+    for (hour, sources) in store.hours.range(..=now_ms) {
+        match blocks.last() {
+            Some((start, _)) if *hour < start + BLOCK_MS => {}
+            _ => blocks.push((*hour, Totals::default())),
+        }
+        let block = &mut blocks.last_mut().expect("block").1;
+        // Prices in force when the tokens were used
+        let date = pricing::date_of_ms(*hour);
+        for (source, models) in sources {
+            for (model, usage) in models {
+                let add = |totals: &mut Totals| totals.add(catalog, &date, model, usage);
+                add(block);
+                if *hour >= month_start {
+                    add(&mut summary.last_30_days);
+                }
+                if *hour >= today_start {
+                    add(&mut summary.today);
+                    add(summary.today_by_source.entry(source.clone()).or_default());
+                    add(summary.today_by_model.entry(model.clone()).or_default());
+                }
+            }
+        }
+    }
+    if blocks.last().is_some_and(|(start, _)| now_ms < start + BLOCK_MS) {
+        summary.active_block = blocks.pop();
+    }
+    summary.max_block_tokens = blocks.iter().map(|(_, totals)| totals.usage.total()).max().unwrap_or(0);
+    summary
+}
+
+fn compact(tokens: u64) -> String {
+    let n = tokens as f64;
+    if n >= 1e9 {
+        format!("{:.1}B", n / 1e9)
+    } else if n >= 1e6 {
+        format!("{:.1}M", n / 1e6)
+    } else if n >= 1e3 {
+        format!("{:.1}K", n / 1e3)
+    } else {
+        tokens.to_string()
+    }
+}
+
+fn iso(ms: u64) -> Option<String> {
+    OffsetDateTime::from_unix_timestamp_nanos(ms as i128 * 1_000_000)
+        .ok()?
+        .format(&time::format_description::well_known::Rfc3339)
+        .ok()
+}
+
+fn summary_lines(summary: &Summary) -> Vec<MetricLine> {
+    let block_used = summary
+        .active_block
+        .as_ref()
+        .map_or(0, |(_, totals)| totals.usage.total());
+    let mut lines = vec![MetricLine::Progress {
+        id: Some("block".to_string()),
+        label: "5h Block".to_string(),
+        used: block_used as f64,
+        limit: summary.max_block_tokens.max(block_used).max(1) as f64,
+        format: ProgressFormat::Tokens,
+        resets_at: summary
+            .active_block
+            .as_ref()
+            .and_then(|(start, _)| iso(start + BLOCK_MS)),
+        period_duration_ms: Some(BLOCK_MS),
+        color: None,
+        segments: Vec::new(),
+        soft_limit: None,
+        hard_limit: None,
+        converted: None,
+        humanized: None,
+    }];
+    // Per-source and per-model lines are not declared in the manifest and have no id.
+    let text = |id: Option<&str>, label: &str, value: String, subtitle: Option<String>| MetricLine::Text {
+        id: id.map(str::to_string),
+        label: label.to_string(),
+        value,
+        color: None,
+        subtitle,
+    };
+    lines.push(text(
+        Some("today"),
+        "Today",
+        summary.today.describe(),
+        summary
+            .active_block
+            .as_ref()
+            .map(|(_, totals)| format!("Current block {}", totals.describe())),
+    ));
+    lines.push(text(Some("last-30-days"), "Last 30 Days", summary.last_30_days.describe(), None));
+    for (source, totals) in &summary.today_by_source {
+        lines.push(text(None, Source::label(source), totals.describe(), Some("Today".to_string())));
+    }
+    let mut models: Vec<_> = summary.today_by_model.iter().collect();
+    models.sort_by(|a, b| b.1.cost.total_cmp(&a.1.cost).then(a.0.cmp(b.0)));
+    for (model, totals) in models.into_iter().take(5) {
+        lines.push(text(None, model, totals.describe(), Some("Today".to_string())));
+    }
+    lines
+}
+
+fn scan_all(
+    store: &mut UsageStore,
+    patterns: &[(Source, String)],
+    state_path: &Path,
+    since_ms: u64,
+    reset: bool,
+) -> Result<bool, String> {
+    let mut rewritten = false;
+    for (source, pattern) in patterns {
+        let mut opts = ScanOpts {
+            fields: source.fields().iter().map(|f| f.to_string()).collect(),
+            since: Some(Value::from(since_ms)),
+            timestamp_field: None,
+            reset,
+            path_field: Some(PATH_FIELD.to_string()),
+        };
+        for _ in 0..MAX_SCAN_ROUNDS {
+            let result = logs::scan_jsonl(pattern, &opts, state_path)?;
+            rewritten |= !result.rewritten.is_empty();
+            store.ingest(*source, &result.records);
+            if !result.more {
+                break;
+            }
+            opts.reset = false;
+        }
+    }
+    Ok(rewritten)
+}
+
+fn load_store(path: &Path) -> UsageStore {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn collect(app_data_dir: &Path, now: OffsetDateTime) -> Result<Option<Summary>, String> {
+    let data_dir = app_data_dir.join("plugins_data").join(ID);
+    let data_dir = data_dir.as_path();
+    let patterns = log_patterns();
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    std::fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+    let state_path = data_dir.join(logs::STATE_FILE);
+    let usage_path = data_dir.join(USAGE_FILE);
+    let now_ms = (now.unix_timestamp_nanos() / 1_000_000).max(0) as u64;
+    let since_ms = now_ms.saturating_sub(RETENTION_MS);
+
+    let mut store = load_store(&usage_path);
+    if scan_all(&mut store, &patterns, &state_path, since_ms, false)? {
+        // A log was replaced or truncated; totals from it can't be subtracted, so rebuild
+        log::info!("[plugin:{}] session log rewritten, rebuilding totals", ID);
+        store = UsageStore::default();
+        scan_all(&mut store, &patterns, &state_path, since_ms, true)?;
+    }
+    store.prune(now_ms);
+    let text = serde_json::to_string(&store).map_err(|e| e.to_string())?;
+    crate::plugin_engine::files::write_text_atomic(&usage_path.to_string_lossy(), &text)?;
+    Ok(Some(summarize(&store, &Catalog::load(app_data_dir), now)))
+}
+
+/// Probe the local session logs. State lives in `<app_data_dir>/plugins_data/session-logs`.
+fn probe(app_data_dir: &Path, icon_data_url: &str, local_offset: UtcOffset) -> PluginOutput {
+    let now = OffsetDateTime::now_utc().to_offset(local_offset);
+    let lines = match collect(app_data_dir, now) {
+        Ok(Some(summary)) => summary_lines(&summary),
+        // Neither tool is installed; nothing to show rather than an error
+        Ok(None) => Vec::new(),
+        Err(e) => {
+            log::warn!("[plugin:{}] probe failed: {}", ID, e);
+            vec![error_line(e)]
+        }
+    };
+    PluginOutput {
+        provider_id: ID.to_string(),
+        display_name: NAME.to_string(),
+        plan: None,
+        lines,
+        icon_url: icon_data_url.to_string(),
+        account: None,
+        account_id: None,
+        sections: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn at(text: &str) -> OffsetDateTime {
+        OffsetDateTime::parse(text, &time::format_description::well_known::Rfc3339).unwrap()
+    }
+
+    fn claude(ts: &str, id: &str, model: &str, input: u64, output: u64) -> Value {
+        json!({
+            "timestamp": ts,
+            "type": "assistant",
+            "requestId": format!("req-{}", id),
+            "message": {
+                "id": id,
+                "model": model,
+                "usage": { "input_tokens": input, "output_tokens": output, "cache_read_input_tokens": 1000 }
+            }
+        })
+    }
+
+    #[test]
+    fn parses_claude_and_codex_records() {
+        let mut store = UsageStore::default();
+        let first = claude("2026-03-02T10:05:00Z", "m1", "claude-sonnet-4-5-20250929", 100, 50);
+        store.ingest(Source::Claude, &[first.clone(), first, json!({ "type": "user", "timestamp": "2026-03-02T10:06:00Z" })]);
+
+        let token_count = |total: u64| {
+            json!({
+                "timestamp": "2026-03-02T11:00:00Z",
+                "type": "event_msg",
+                "payload": { "type": "token_count", "info": {
+                    "last_token_usage": { "input_tokens": 300, "cached_input_tokens": 200, "output_tokens": 40 },
+                    "total_token_usage": { "total_tokens": total }
+                }}
+            })
+        };
+        store.ingest(
+            Source::Codex,
+            &[
+                json!({ "timestamp": "2026-03-02T10:59:00Z", "type": "turn_context", "payload": { "model": "gpt-5-codex" } }),
+                token_count(340),
+                token_count(340),
+            ],
+        );
+
+        let ten = at("2026-03-02T10:00:00Z").unix_timestamp() as u64 * 1000;
+        assert_eq!(
+            store.hours[&ten]["claude"]["claude-sonnet-4-5-20250929"],
+            TokenUsage { input: 100, output: 50, cache_write: 0, cache_read: 1000 }
+        );
+        assert_eq!(
+            store.hours[&(ten + HOUR_MS)]["codex"]["gpt-5-codex"],
+            TokenUsage { input: 100, output: 40, cache_write: 0, cache_read: 200 }
+        );
+    }
+
+    #[test]
+    fn tracks_codex_model_and_totals_per_session_file() {
+        let turn = |file: &str, model: &str| {
+            json!({ "file": file, "timestamp": "2026-03-02T10:00:00Z", "type": "turn_context", "payload": { "model": model } })
+        };
+        let token_count = |file: &str, input: u64, total: u64| {
+            json!({
+                "file": file,
+                "timestamp": "2026-03-02T10:30:00Z",
+                "type": "event_msg",
+                "payload": { "type": "token_count", "info": {
+                    "last_token_usage": { "input_tokens": input, "output_tokens": 0 },
+                    "total_token_usage": { "total_tokens": total }
+                }}
+            })
+        };
+        let mut store = UsageStore::default();
+        store.ingest(
+            Source::Codex,
+            &[
+                turn("a.jsonl", "gpt-5-codex"),
+                turn("b.jsonl", "gpt-5-mini"),
+                token_count("a.jsonl", 100, 100),
+                token_count("b.jsonl", 100, 100),
+                token_count("a.jsonl", 10, 110),
+                token_count("b.jsonl", 10, 110),
+            ],
+        );
+
+        let ten = at("2026-03-02T10:00:00Z").unix_timestamp() as u64 * 1000;
+        let codex = &store.hours[&ten]["codex"];
+        assert_eq!(codex["gpt-5-codex"].input, 110);
+        assert_eq!(codex["gpt-5-mini"].input, 110);
+    }
+
+    #[test]
+    fn summarizes_days_and_blocks() {
+        let mut store = UsageStore::default();
+        store.ingest(
+            Source::Claude,
+            &[
+                claude("2026-03-01T08:00:00Z", "a", "claude-sonnet-4-5", 1_000_000, 0),
+                claude("2026-03-02T01:30:00Z", "b", "claude-sonnet-4-5", 1_000, 0),
+                claude("2026-03-02T04:10:00Z", "c", "mystery", 10, 0),
+                claude("2026-03-02T07:00:00Z", "d", "claude-opus-4-5", 2_000, 0),
+            ],
+        );
+
+        let summary = summarize(&store, Catalog::builtin(), at("2026-03-02T08:00:00Z"));
+        // Blocks: 03-01 08:00, 03-02 01:00 (b, c), 03-02 07:00 (d, active)
+        let (start, active) = summary.active_block.clone().expect("active block");
+        assert_eq!(iso(start).as_deref(), Some("2026-03-02T07:00:00Z"));
+        assert_eq!(active.usage.total(), 3_000);
+        assert_eq!(summary.max_block_tokens, 1_001_000);
+        assert_eq!(summary.today.usage.input, 1_000 + 10 + 2_000);
+        assert!(summary.today.unpriced);
+        assert!((summary.last_30_days.cost - (3.0 + 0.003 + 0.01 + 2.0 * 0.0003 + 0.0005)).abs() < 1e-9);
+
+        let lines = summary_lines(&summary);
+        assert!(matches!(
+            &lines[0],
+            MetricLine::Progress { used, limit, resets_at: Some(reset), .. }
+                if *used == 3_000.0 && *limit == 1_001_000.0 && reset == "2026-03-02T12:00:00Z"
+        ));
+        assert!(matches!(&lines[1], MetricLine::Text { label, value, .. } if label == "Today" && value.ends_with("+ · 6.0K tokens")));
+
+        let later = summarize(&store, Catalog::builtin(), at("2026-03-02T13:00:00Z"));
+        assert_eq!(later.active_block, None);
+    }
+
+    #[test]
+    fn prune_drops_old_hours_and_dedupe_keys() {
+        let mut store = UsageStore::default();
+        store.ingest(Source::Claude, &[claude("2026-01-01T00:00:00Z", "old", "claude-sonnet-4", 1, 1)]);
+        store.prune(at("2026-03-01T00:00:00Z").unix_timestamp() as u64 * 1000);
+        assert!(store.hours.is_empty());
+        assert!(store.seen.is_empty());
+    }
+}
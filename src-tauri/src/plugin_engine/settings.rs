@@ -0,0 +1,323 @@
+use crate::plugin_engine::manifest::{SettingField, SettingKind};
+use crate::plugin_engine::secrets::SecretBackend;
+use serde_json::{Map, Value};
+
+/// Stored in place of a `secret` value, which lives in the secrets backend instead of the
+/// settings store. The settings form gets it back as "a value is set".
+pub const SECRET_SET: Value = Value::Bool(true);
+
+/// Secrets-backend scope holding a plugin's `secret` settings. Kept apart from the plugin's own
+/// `host.secrets` scope so a probe cannot overwrite them.
+pub fn secrets_scope(plugin_id: &str) -> String {
+    format!("{}/settings", plugin_id)
+}
+
+fn is_secret(field: &SettingField) -> bool {
+    matches!(field.kind, SettingKind::Secret)
+}
+
+/// Check a single value against its field definition.
+pub fn validate_value(field: &SettingField, value: &Value) -> Result<(), String> {
+    match &field.kind {
+        SettingKind::String {
+            pattern,
+            max_length,
+        } => {
+            let text = value
+                .as_str()
+                .ok_or_else(|| format!("{} must be a string", field.key))?;
+            if let Some(max) = max_length.filter(|max| text.chars().count() > *max) {
+                return Err(format!("{} must be at most {} characters", field.key, max));
+            }
+            if let Some(pattern) = pattern {
+                let re = regex_lite::Regex::new(pattern)
+                    .map_err(|e| format!("{} has an invalid pattern: {}", field.key, e))?;
+                if !re.is_match(text) {
+                    return Err(format!("{} does not match {}", field.key, pattern));
+                }
+            }
+            Ok(())
+        }
+        SettingKind::Secret => value
+            .as_str()
+            .map(|_| ())
+            .ok_or_else(|| format!("{} must be a string", field.key)),
+        SettingKind::Path => match value.as_str() {
+            Some(path) if !path.trim().is_empty() => Ok(()),
+            Some(_) => Err(format!("{} must not be empty", field.key)),
+            None => Err(format!("{} must be a string", field.key)),
+        },
+        SettingKind::Boolean => value
+            .as_bool()
+            .map(|_| ())
+            .ok_or_else(|| format!("{} must be a boolean", field.key)),
+        SettingKind::Enum { options } => match value.as_str() {
+            Some(choice) if options.iter().any(|option| option == choice) => Ok(()),
+            _ => Err(format!("{} must be one of: {}", field.key, options.join(", "))),
+        },
+    }
+}
+
+/// Check a field definition itself: enum options, pattern syntax and default value.
+pub fn validate_field(field: &SettingField) -> Result<(), String> {
+    if field.key.trim().is_empty() {
+        return Err("setting key cannot be empty".to_string());
+    }
+    match &field.kind {
+        SettingKind::Enum { options } if options.is_empty() => {
+            return Err(format!("{} enum must declare options", field.key));
+        }
+        SettingKind::String {
+            pattern: Some(pattern),
+            ..
+        } => {
+            regex_lite::Regex::new(pattern)
+                .map_err(|e| format!("{} has an invalid pattern: {}", field.key, e))?;
+        }
+        _ => {}
+    }
+    if let Some(default) = &field.default {
+        validate_value(field, default).map_err(|e| format!("invalid default: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Resolve stored values: the stored value when valid, otherwise the default, otherwise `null`.
+/// Keys not in the schema are dropped. Secret fields resolve to `SECRET_SET` at most; use
+/// `reveal_secrets` to build `ctx.settings`.
+pub fn resolve(fields: &[SettingField], stored: Option<&Map<String, Value>>) -> Map<String, Value> {
+    let mut values = Map::new();
+    for field in fields {
+        let stored_value = stored
+            .and_then(|stored| stored.get(&field.key))
+            .filter(|value| !value.is_null());
+        let value = match stored_value {
+            Some(value) if is_secret(field) => (*value == SECRET_SET).then_some(SECRET_SET),
+            Some(value) => match validate_value(field, value) {
+                Ok(()) => Some(value.clone()),
+                Err(e) => {
+                    log::warn!("ignoring stored setting: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        values.insert(
+            field.key.clone(),
+            value.or_else(|| field.default.clone()).unwrap_or(Value::Null),
+        );
+    }
+    values
+}
+
+/// Replace `SECRET_SET` markers with the values from the secrets backend, for `ctx.settings`.
+/// A secret missing from the backend resolves to `null`.
+pub fn reveal_secrets(
+    fields: &[SettingField],
+    values: &mut Map<String, Value>,
+    scope: &str,
+    backend: &dyn SecretBackend,
+) {
+    for field in fields.iter().filter(|field| is_secret(field)) {
+        if values.get(&field.key) != Some(&SECRET_SET) {
+            continue;
+        }
+        let secret = match backend.get(scope, &field.key) {
+            Ok(secret) => secret,
+            Err(e) => {
+                log::warn!("failed to read secret setting {}: {}", field.key, e);
+                None
+            }
+        };
+        let value = secret.map(Value::String).unwrap_or(Value::Null);
+        values.insert(field.key.clone(), value);
+    }
+}
+
+/// Move submitted secret values into the secrets backend, leaving `SECRET_SET` in `values`.
+/// A secret left out of the submission (or submitted empty) is deleted; `SECRET_SET` keeps it.
+pub fn store_secrets(
+    fields: &[SettingField],
+    values: &mut Map<String, Value>,
+    scope: &str,
+    backend: &dyn SecretBackend,
+) -> Result<(), String> {
+    for field in fields.iter().filter(|field| is_secret(field)) {
+        match values.get(&field.key) {
+            Some(Value::String(secret)) if !secret.is_empty() => {
+                backend.set(scope, &field.key, secret)?;
+                values.insert(field.key.clone(), SECRET_SET);
+            }
+            Some(value) if *value == SECRET_SET => {}
+            _ => {
+                backend.delete(scope, &field.key)?;
+                values.remove(&field.key);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validate values submitted by the settings form; returns only schema keys. A secret field
+/// may be submitted as `SECRET_SET` to keep its current value.
+pub fn validate_submission(
+    fields: &[SettingField],
+    submitted: &Map<String, Value>,
+) -> Result<Map<String, Value>, Vec<String>> {
+    let mut errors = Vec::new();
+    let mut values = Map::new();
+    for key in submitted.keys() {
+        if !fields.iter().any(|field| field.key == *key) {
+            errors.push(format!("unknown setting: {}", key));
+        }
+    }
+    for field in fields {
+        match submitted.get(&field.key).filter(|value| !value.is_null()) {
+            Some(value) if is_secret(field) && *value == SECRET_SET => {
+                values.insert(field.key.clone(), SECRET_SET);
+            }
+            Some(value) => match validate_value(field, value) {
+                Ok(()) => {
+                    values.insert(field.key.clone(), value.clone());
+                }
+                Err(e) => errors.push(e),
+            },
+            None if field.required && field.default.is_none() => {
+                errors.push(format!("{} is required", field.key));
+            }
+            None => {}
+        }
+    }
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(errors)
+    }
+}
+
+/// First required field without a resolved value, if any.
+pub fn missing_required<'a>(
+    fields: &'a [SettingField],
+    values: &Map<String, Value>,
+) -> Option<&'a SettingField> {
+    fields.iter().find(|field| {
+        field.required
+            && match values.get(&field.key) {
+                None | Some(Value::Null) => true,
+                Some(Value::String(s)) => s.trim().is_empty(),
+                Some(_) => false,
+            }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_engine::test_dir;
+    use serde_json::json;
+
+    fn fields() -> Vec<SettingField> {
+        serde_json::from_value(json!([
+            { "key": "apiBase", "label": "API base", "type": "string", "pattern": "^https://", "default": "https://api.example.com" },
+            { "key": "orgId", "label": "Organization", "type": "string", "required": true },
+            { "key": "token", "label": "Token", "type": "secret" },
+            { "key": "beta", "label": "Beta", "type": "boolean", "default": false },
+            { "key": "region", "label": "Region", "type": "enum", "options": ["us", "eu"], "default": "us" },
+            { "key": "credentials", "label": "Credentials", "type": "path" }
+        ]))
+        .expect("fields parse")
+    }
+
+    #[test]
+    fn resolve_prefers_valid_stored_values_then_defaults() {
+        let fields = fields();
+        let stored = json!({ "region": "eu", "apiBase": "http://insecure", "unknown": 1 });
+        let values = resolve(&fields, stored.as_object());
+
+        assert_eq!(values["region"], json!("eu"));
+        assert_eq!(values["apiBase"], json!("https://api.example.com"));
+        assert_eq!(values["beta"], json!(false));
+        assert_eq!(values["orgId"], Value::Null);
+        assert!(!values.contains_key("unknown"));
+    }
+
+    #[test]
+    fn missing_required_reports_first_unset_field() {
+        let fields = fields();
+        let values = resolve(&fields, None);
+        assert_eq!(missing_required(&fields, &values).map(|f| f.key.as_str()), Some("orgId"));
+
+        let values = resolve(&fields, json!({ "orgId": "org-1" }).as_object());
+        assert!(missing_required(&fields, &values).is_none());
+    }
+
+    #[test]
+    fn validate_submission_collects_every_error() {
+        let fields = fields();
+        let submitted = json!({ "beta": "yes", "region": "apac", "extra": true });
+        let errors = validate_submission(&fields, submitted.as_object().unwrap()).unwrap_err();
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert!(errors.iter().any(|e| e == "orgId is required"));
+        assert!(errors.iter().any(|e| e == "unknown setting: extra"));
+    }
+
+    #[test]
+    fn secret_values_stay_out_of_the_settings_store() {
+        let fields = fields();
+        let dir = test_dir("settings-secrets");
+        let backend = crate::plugin_engine::secrets::EncryptedFileBackend::new(dir.clone());
+        let scope = secrets_scope("example");
+
+        let submitted = json!({ "orgId": "org-1", "token": "sk-live-123" });
+        let mut values = validate_submission(&fields, submitted.as_object().unwrap()).unwrap();
+        store_secrets(&fields, &mut values, &scope, &backend).unwrap();
+
+        let store = json!({ "pluginSettingValues": { "example": values } });
+        let settings_json = serde_json::to_string(&store).unwrap();
+        assert!(!settings_json.contains("sk-live-123"), "{}", settings_json);
+        assert_eq!(values["token"], SECRET_SET);
+
+        let shown = resolve(&fields, Some(&values));
+        assert_eq!(shown["token"], SECRET_SET, "the form only learns that a value is set");
+
+        let mut ctx_settings = resolve(&fields, Some(&values));
+        reveal_secrets(&fields, &mut ctx_settings, &scope, &backend);
+        assert_eq!(ctx_settings["token"], json!("sk-live-123"));
+
+        let kept = json!({ "orgId": "org-1", "token": true });
+        let mut values = validate_submission(&fields, kept.as_object().unwrap()).unwrap();
+        store_secrets(&fields, &mut values, &scope, &backend).unwrap();
+        assert_eq!(backend.get(&scope, "token").unwrap().as_deref(), Some("sk-live-123"));
+
+        let cleared = json!({ "orgId": "org-1", "token": "" });
+        let mut values = validate_submission(&fields, cleared.as_object().unwrap()).unwrap();
+        store_secrets(&fields, &mut values, &scope, &backend).unwrap();
+        assert!(!values.contains_key("token"));
+        assert_eq!(backend.get(&scope, "token").unwrap(), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn resolve_ignores_plaintext_secrets() {
+        let fields = fields();
+        let values = resolve(&fields, json!({ "token": "sk-live-123" }).as_object());
+        assert_eq!(values["token"], Value::Null);
+    }
+
+    #[test]
+    fn validate_field_rejects_bad_defaults_and_empty_enums() {
+        let bad: Vec<SettingField> = serde_json::from_value(json!([
+            { "key": "region", "label": "Region", "type": "enum", "options": ["us"], "default": "eu" },
+            { "key": "mode", "label": "Mode", "type": "enum", "options": [] },
+            { "key": "path", "label": "Path", "type": "path", "default": "" }
+        ]))
+        .unwrap();
+        for field in &bad {
+            assert!(validate_field(field).is_err(), "{} should be invalid", field.key);
+        }
+        for field in &fields() {
+            assert!(validate_field(field).is_ok(), "{} should be valid", field.key);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{Connection, OpenFlags, Statement};
+use serde_json::{Map, Value as JsonValue};
+use std::time::Duration;
+
+/// How long a write waits on a database locked by its owning app (e.g. Cursor).
+const BUSY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `file:` URI opening the database immutable, so WAL/SHM files are never touched. Plain
+/// read-only opens can fail on WAL databases while the owner holds the shm lock (e.g.
+/// after macOS sleep).
+pub fn immutable_uri(path: &str) -> String {
+    let path = if std::path::MAIN_SEPARATOR == '\\' {
+        path.replace('\\', "/")
+    } else {
+        path.to_string()
+    };
+    // Percent-encode characters with meaning in URIs (% must be first!)
+    let encoded = path
+        .replace('%', "%25")
+        .replace(' ', "%20")
+        .replace('#', "%23")
+        .replace('?', "%3F");
+    let bytes = encoded.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    if has_drive {
+        format!("file:///{}?immutable=1", encoded)
+    } else {
+        format!("file:{}?immutable=1", encoded)
+    }
+}
+
+/// Run a single read-only statement and return its rows as a JSON array of objects.
+/// Integers and reals become numbers, text strings, blobs base64 strings.
+pub fn query(path: &str, sql: &str, params: Option<&JsonValue>) -> Result<JsonValue, String> {
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY
+        | OpenFlags::SQLITE_OPEN_URI
+        | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    let conn = Connection::open_with_flags(immutable_uri(path), flags)
+        .map_err(|e| format!("sqlite open failed: {}", e))?;
+    let mut stmt = conn.prepare(sql).map_err(|e| format!("sqlite error: {}", e))?;
+    bind_params(&mut stmt, params)?;
+
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let mut rows = stmt.raw_query();
+    let mut out = Vec::new();
+    while let Some(row) = rows.next().map_err(|e| format!("sqlite error: {}", e))? {
+        let mut obj = Map::new();
+        for (idx, column) in columns.iter().enumerate() {
+            let value = row.get_ref(idx).map_err(|e| format!("sqlite error: {}", e))?;
+            obj.insert(column.clone(), decode_value(value));
+        }
+        out.push(JsonValue::Object(obj));
+    }
+    Ok(JsonValue::Array(out))
+}
+
+/// Run SQL against the database with write access and return the number of changed rows.
+/// With `params` the SQL must be a single statement; without, it may be a batch.
+pub fn exec(path: &str, sql: &str, params: Option<&JsonValue>) -> Result<usize, String> {
+    let flags = OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    let conn =
+        Connection::open_with_flags(path, flags).map_err(|e| format!("sqlite open failed: {}", e))?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("sqlite error: {}", e))?;
+
+    if params.is_none_or(JsonValue::is_null) {
+        conn.execute_batch(sql)
+            .map_err(|e| format!("sqlite error: {}", e))?;
+        return Ok(conn.changes() as usize);
+    }
+    let mut stmt = conn.prepare(sql).map_err(|e| format!("sqlite error: {}", e))?;
+    bind_params(&mut stmt, params)?;
+    stmt.raw_execute().map_err(|e| format!("sqlite error: {}", e))
+}
+
+/// Bind an array (positional `?`) or object (named `:name`, `@name`, `$name`) of params.
+fn bind_params(stmt: &mut Statement<'_>, params: Option<&JsonValue>) -> Result<(), String> {
+    let expected = stmt.parameter_count();
+    match params {
+        None | Some(JsonValue::Null) => {
+            if expected > 0 {
+                return Err(format!("sqlite statement expects {} parameter(s)", expected));
+            }
+        }
+        Some(JsonValue::Array(values)) => {
+            if values.len() != expected {
+                return Err(format!(
+                    "sqlite statement expects {} parameter(s), got {}",
+                    expected,
+                    values.len()
+                ));
+            }
+            for (idx, value) in values.iter().enumerate() {
+                stmt.raw_bind_parameter(idx + 1, encode_value(value)?)
+                    .map_err(|e| format!("sqlite bind failed: {}", e))?;
+            }
+        }
+        Some(JsonValue::Object(values)) => {
+            if values.len() != expected {
+                return Err(format!(
+                    "sqlite statement expects {} parameter(s), got {}",
+                    expected,
+                    values.len()
+                ));
+            }
+            for (name, value) in values {
+                let candidates: Vec<String> = if name.starts_with([':', '@', '$']) {
+                    vec![name.clone()]
+                } else {
+                    [":", "@", "$"].iter().map(|prefix| format!("{}{}", prefix, name)).collect()
+                };
+                let idx = candidates
+                    .iter()
+                    .find_map(|candidate| stmt.parameter_index(candidate).ok().flatten())
+                    .ok_or_else(|| format!("sqlite statement has no parameter named {}", name))?;
+                stmt.raw_bind_parameter(idx, encode_value(value)?)
+                    .map_err(|e| format!("sqlite bind failed: {}", e))?;
+            }
+        }
+        Some(_) => return Err("sqlite params must be an array or object".to_string()),
+    }
+    Ok(())
+}
+
+fn encode_value(value: &JsonValue) -> Result<SqlValue, String> {
+    match value {
+        JsonValue::Null => Ok(SqlValue::Null),
+        JsonValue::Bool(b) => Ok(SqlValue::Integer(*b as i64)),
+        JsonValue::Number(n) => Ok(match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or(f64::NAN)),
+        }),
+        JsonValue::String(s) => Ok(SqlValue::Text(s.clone())),
+        _ => Err("sqlite params must be strings, numbers, booleans or null".to_string()),
+    }
+}
+
+fn decode_value(value: ValueRef<'_>) -> JsonValue {
+    match value {
+        ValueRef::Null => JsonValue::Null,
+        ValueRef::Integer(i) => JsonValue::from(i),
+        ValueRef::Real(f) => JsonValue::from(f),
+        ValueRef::Text(bytes) => JsonValue::String(String::from_utf8_lossy(bytes).into_owned()),
+        ValueRef::Blob(bytes) => JsonValue::String(STANDARD.encode(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_engine::test_dir;
+    use serde_json::json;
+
+    fn temp_db(label: &str) -> String {
+        // A space and `#` in the path check that the immutable URI escapes them.
+        let path = test_dir(&format!("sqlite #{}", label)).join("state.vscdb").to_string_lossy().to_string();
+        let conn = Connection::open(&path).expect("create db");
+        conn.execute_batch(
+            "CREATE TABLE ItemTable (key TEXT PRIMARY KEY, value BLOB);
+             CREATE TABLE Stats (name TEXT, count INTEGER, ratio REAL, raw BLOB);
+             INSERT INTO Stats VALUES ('a', 3, 0.5, x'DEADBEEF'), ('b', NULL, NULL, NULL);",
+        )
+        .expect("seed db");
+        path
+    }
+
+    #[test]
+    fn query_decodes_typed_columns() {
+        let path = temp_db("types");
+        let rows = query(&path, "SELECT * FROM Stats ORDER BY name", None).expect("query");
+        assert_eq!(
+            rows,
+            json!([
+                { "name": "a", "count": 3, "ratio": 0.5, "raw": "3q2+7w==" },
+                { "name": "b", "count": null, "ratio": null, "raw": null }
+            ])
+        );
+    }
+
+    #[test]
+    fn query_binds_positional_and_named_params() {
+        let path = temp_db("params");
+        let rows = query(&path, "SELECT name FROM Stats WHERE count = ?", Some(&json!([3])))
+            .expect("positional");
+        assert_eq!(rows, json!([{ "name": "a" }]));
+
+        let rows = query(
+            &path,
+            "SELECT name FROM Stats WHERE name = :name",
+            Some(&json!({ "name": "b" })),
+        )
+        .expect("named");
+        assert_eq!(rows, json!([{ "name": "b" }]));
+
+        let err = query(&path, "SELECT name FROM Stats WHERE count = ?", None).unwrap_err();
+        assert!(err.contains("expects 1 parameter"), "{}", err);
+    }
+
+    #[test]
+    fn query_is_read_only() {
+        let path = temp_db("readonly");
+        let err = query(&path, "DELETE FROM Stats", None).unwrap_err();
+        assert!(err.contains("readonly") || err.contains("read-only"), "{}", err);
+    }
+
+    #[test]
+    fn exec_writes_with_bound_values() {
+        let path = temp_db("exec");
+        let value = "it's a \"quoted\" value; DROP TABLE ItemTable;";
+        let changed = exec(
+            &path,
+            "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?1, ?2)",
+            Some(&json!(["token", value])),
+        )
+        .expect("exec");
+        assert_eq!(changed, 1);
+
+        let rows = query(&path, "SELECT value FROM ItemTable WHERE key = ?", Some(&json!(["token"])))
+            .expect("query");
+        assert_eq!(rows, json!([{ "value": value }]));
+    }
+
+    #[test]
+    fn immutable_uri_encodes_special_chars_and_drive_letters() {
+        assert_eq!(
+            immutable_uri("/Users/me/App Support/#1?.db"),
+            "file:/Users/me/App%20Support/%231%3F.db?immutable=1"
+        );
+        assert_eq!(immutable_uri("C:/Users/me/state.vscdb"), "file:///C:/Users/me/state.vscdb?immutable=1");
+    }
+}
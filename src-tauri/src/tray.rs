@@ -1,11 +1,61 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use serde::Deserialize;
 use tauri::image::Image;
-use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, MenuItemKind, PredefinedMenuItem, Submenu};
 use tauri::path::BaseDirectory;
 use tauri::tray::{MouseButtonState, TrayIconBuilder, TrayIconEvent};
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_opener::OpenerExt;
 use tauri_plugin_store::StoreExt;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::plugin_engine::manifest::ManifestLink;
+use crate::plugin_engine::currency::{self, ConvertedAmount};
+use crate::plugin_engine::humanize::{self, NumberStyle, ProgressText};
+use crate::plugin_engine::runtime::{MetricLine, PluginOutput, ProgressFormat};
 
 const LOG_LEVEL_STORE_KEY: &str = "logLevel";
+const PLUGIN_SETTINGS_STORE_KEY: &str = "plugins";
+const PINNED_PROVIDERS_STORE_KEY: &str = "pinnedProviders";
+const TRAY_TOOLTIP: &str = "OpenUsage";
+const PINNED_TRAY_ID_PREFIX: &str = "tray-pin:";
+const PINNED_ICON_SIZE: u32 = 32;
+
+const PROVIDER_REFRESH_PREFIX: &str = "provider_refresh:";
+const PROVIDER_COPY_PREFIX: &str = "provider_copy:";
+const PROVIDER_LINK_PREFIX: &str = "provider_link:";
+const PROVIDER_PIN_PREFIX: &str = "provider_pin:";
+
+/// Provider entries at the top of the tray menu, replaced after every probe batch.
+struct ProviderMenuSection {
+    menu: Menu<Wry>,
+    items: Mutex<Vec<MenuItemKind<Wry>>>,
+}
+
+/// Subset of the frontend plugin settings needed to mirror the panel's order.
+#[derive(Debug, Default, Deserialize)]
+struct StoredPluginSettings {
+    #[serde(default)]
+    order: Vec<String>,
+    #[serde(default)]
+    disabled: Vec<String>,
+}
+
+/// Provider ids that currently have their own tray icon.
+#[derive(Default)]
+struct PinnedTrayIcons(Mutex<Vec<String>>);
+
+struct ProviderEntry {
+    id: String,
+    name: String,
+    primary_candidates: Vec<String>,
+    links: Vec<ManifestLink>,
+    output: PluginOutput,
+}
 
 fn get_stored_log_level(app_handle: &AppHandle) -> log::LevelFilter {
     let store = match app_handle.store("settings.json") {
@@ -78,11 +128,16 @@ pub fn create(app_handle: &AppHandle) -> tauri::Result<()> {
     let quit = MenuItem::with_id(app_handle, "quit", "Quit", true, None::<&str>)?;
 
     let menu = Menu::with_items(app_handle, &[&show_stats, &go_to_settings, &log_level_submenu, &separator, &about, &quit])?;
+    app_handle.manage(ProviderMenuSection {
+        menu: menu.clone(),
+        items: Mutex::new(Vec::new()),
+    });
+    app_handle.manage(PinnedTrayIcons::default());
 
     TrayIconBuilder::with_id("tray")
         .icon(icon)
         .icon_as_template(true)
-        .tooltip("OpenUsage")
+        .tooltip(TRAY_TOOLTIP)
         .menu(&menu)
         .show_menu_on_left_click(false)
         .on_menu_event(move |app_handle, event| {
@@ -118,7 +173,7 @@ pub fn create(app_handle: &AppHandle) -> tauri::Result<()> {
                         let _ = item.set_checked(*level == selected_level);
                     }
                 }
-                _ => {}
+                id => handle_provider_menu_event(app_handle, id),
             }
         })
         .on_tray_icon_event(|tray, event| {
@@ -133,5 +188,806 @@ pub fn create(app_handle: &AppHandle) -> tauri::Result<()> {
         })
         .build(app_handle)?;
 
+    sync_pinned_icons(app_handle);
+
+    Ok(())
+}
+
+/// Rebuild the provider section of the tray menu and the tooltip from the latest probe outputs.
+pub fn update_provider_metrics(app_handle: &AppHandle) {
+    let entries = enabled_provider_entries(app_handle);
+    let now = OffsetDateTime::now_utc();
+
+    if let Err(e) = rebuild_provider_section(app_handle, &entries, now) {
+        log::warn!("tray: failed to rebuild provider menu: {}", e);
+    }
+    sync_pinned_icons(app_handle);
+
+    if let Some(tray) = app_handle.tray_by_id("tray") {
+        let lines: Vec<String> = entries
+            .iter()
+            .map(|entry| provider_tooltip_line(&entry.name, &entry.primary_candidates, &entry.output))
+            .collect();
+        if let Err(e) = tray.set_tooltip(Some(tooltip_text(&lines))) {
+            log::warn!("tray: failed to set tooltip: {}", e);
+        }
+    }
+}
+
+pub fn get_stored_pinned_providers(app_handle: &AppHandle) -> Vec<String> {
+    let Ok(store) = app_handle.store("settings.json") else {
+        return Vec::new();
+    };
+    store
+        .get(PINNED_PROVIDERS_STORE_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the pinned provider ids and create/remove their tray icons to match.
+pub fn set_pinned_providers(app_handle: &AppHandle, plugin_ids: Vec<String>) {
+    let mut seen = HashSet::new();
+    let pinned: Vec<String> = plugin_ids
+        .into_iter()
+        .filter(|id| seen.insert(id.clone()))
+        .collect();
+    log::info!("Pinned tray providers changing to {:?}", pinned);
+    if let Ok(store) = app_handle.store("settings.json") {
+        store.set(PINNED_PROVIDERS_STORE_KEY, serde_json::json!(pinned));
+        let _ = store.save();
+    }
+    sync_pinned_icons(app_handle);
+}
+
+fn pinned_tray_id(plugin_id: &str) -> String {
+    format!("{}{}", PINNED_TRAY_ID_PREFIX, plugin_id)
+}
+
+/// Bring the per-provider tray icons in line with the stored pins and latest outputs.
+fn sync_pinned_icons(app_handle: &AppHandle) {
+    let settings = get_stored_plugin_settings(app_handle);
+    let pinned: Vec<String> = get_stored_pinned_providers(app_handle)
+        .into_iter()
+        .filter(|id| !settings.disabled.contains(id))
+        .collect();
+
+    let icons: Vec<(String, String, Vec<u8>)> = {
+        let state = app_handle.state::<Mutex<crate::AppState>>();
+        let Ok(locked) = state.lock() else {
+            return;
+        };
+        pinned
+            .iter()
+            .filter_map(|id| {
+                let provider = locked.providers.iter().find(|provider| provider.id() == id)?;
+                let manifest = provider.manifest();
+                let outputs = locked.latest_outputs.get(id).map(Vec::as_slice).unwrap_or_default();
+                let (percent, tooltip) =
+                    pinned_icon_state(&manifest.name, &manifest.primary_candidates(), outputs);
+                let rgba = crate::tray_render::render_provider_icon(
+                    manifest.brand_color.as_deref(),
+                    percent,
+                    PINNED_ICON_SIZE,
+                );
+                Some((id.clone(), tooltip, rgba))
+            })
+            .collect()
+    };
+
+    let Some(active) = app_handle.try_state::<PinnedTrayIcons>() else {
+        return;
+    };
+    let mut active = active.0.lock().unwrap_or_else(|e| e.into_inner());
+
+    for id in active.iter() {
+        if !icons.iter().any(|(pinned_id, _, _)| pinned_id == id) {
+            log::debug!("tray: removing pinned icon for {}", id);
+            let _ = app_handle.remove_tray_by_id(&pinned_tray_id(id));
+        }
+    }
+
+    for (plugin_id, tooltip, rgba) in &icons {
+        let tray_id = pinned_tray_id(plugin_id);
+        let icon = Image::new_owned(rgba.clone(), PINNED_ICON_SIZE, PINNED_ICON_SIZE);
+        if let Some(tray) = app_handle.tray_by_id(&tray_id) {
+            if let Err(e) = tray.set_icon(Some(icon)) {
+                log::warn!("tray: failed to update pinned icon for {}: {}", plugin_id, e);
+            }
+            let _ = tray.set_tooltip(Some(tooltip));
+            continue;
+        }
+
+        log::debug!("tray: creating pinned icon for {}", plugin_id);
+        let click_id = plugin_id.clone();
+        let result = TrayIconBuilder::with_id(&tray_id)
+            .icon(icon)
+            .icon_as_template(false)
+            .tooltip(tooltip)
+            .show_menu_on_left_click(false)
+            .on_tray_icon_event(move |tray, event| {
+                if let TrayIconEvent::Click {
+                    button_state: MouseButtonState::Up,
+                    ..
+                } = event
+                {
+                    log::debug!("pinned tray click: showing {}", click_id);
+                    let app_handle = tray.app_handle();
+                    crate::window::show_window(app_handle);
+                    let _ = app_handle.emit("tray:navigate", click_id.as_str());
+                }
+            })
+            .build(app_handle);
+        if let Err(e) = result {
+            log::warn!("tray: failed to create pinned icon for {}: {}", plugin_id, e);
+        }
+    }
+
+    *active = icons.into_iter().map(|(id, _, _)| id).collect();
+}
+
+fn get_stored_plugin_settings(app_handle: &AppHandle) -> StoredPluginSettings {
+    let Ok(store) = app_handle.store("settings.json") else {
+        return StoredPluginSettings::default();
+    };
+    store
+        .get(PLUGIN_SETTINGS_STORE_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Ids of the providers the user turned off in the panel settings.
+pub fn disabled_plugin_ids(app_handle: &AppHandle) -> Vec<String> {
+    get_stored_plugin_settings(app_handle).disabled
+}
+
+/// Providers with a cached output that are not disabled, in the user's panel order.
+fn enabled_provider_entries(app_handle: &AppHandle) -> Vec<ProviderEntry> {
+    let settings = get_stored_plugin_settings(app_handle);
+    let disabled: HashSet<&str> = settings.disabled.iter().map(String::as_str).collect();
+
+    let state = app_handle.state::<Mutex<crate::AppState>>();
+    let Ok(locked) = state.lock() else {
+        return Vec::new();
+    };
+
+    let mut entries: Vec<ProviderEntry> = locked
+        .providers
+        .iter()
+        .filter(|provider| !disabled.contains(provider.id()))
+        .flat_map(|provider| {
+            let manifest = provider.manifest();
+            let outputs = locked
+                .latest_outputs
+                .get(&manifest.id)
+                .cloned()
+                .unwrap_or_default();
+            outputs.into_iter().map(|output| ProviderEntry {
+                id: manifest.id.clone(),
+                name: display_name(&manifest.name, output.account.as_deref()),
+                primary_candidates: manifest.primary_candidates(),
+                links: manifest.links.clone(),
+                output,
+            })
+        })
+        .collect();
+    entries.sort_by_key(|entry| {
+        settings
+            .order
+            .iter()
+            .position(|id| *id == entry.id)
+            .unwrap_or(usize::MAX)
+    });
+    entries
+}
+
+fn rebuild_provider_section(
+    app_handle: &AppHandle,
+    entries: &[ProviderEntry],
+    now: OffsetDateTime,
+) -> tauri::Result<()> {
+    let Some(section) = app_handle.try_state::<ProviderMenuSection>() else {
+        return Ok(());
+    };
+    let mut items = section.items.lock().unwrap_or_else(|e| e.into_inner());
+
+    for item in items.drain(..) {
+        section.menu.remove(&item)?;
+    }
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let mut new_items = Vec::with_capacity(entries.len() + 1);
+    for (idx, entry) in entries.iter().enumerate() {
+        new_items.push(MenuItemKind::Submenu(build_provider_submenu(app_handle, entry, idx, now)?));
+    }
+    new_items.push(MenuItemKind::Predefined(PredefinedMenuItem::separator(app_handle)?));
+
+    for (position, item) in new_items.iter().enumerate() {
+        section.menu.insert(item, position)?;
+    }
+    *items = new_items;
     Ok(())
 }
+
+fn build_provider_submenu(
+    app_handle: &AppHandle,
+    entry: &ProviderEntry,
+    position: usize,
+    now: OffsetDateTime,
+) -> tauri::Result<Submenu<Wry>> {
+    let mut actions = vec![
+        MenuItem::with_id(app_handle, format!("{}{}", PROVIDER_REFRESH_PREFIX, entry.id), "Refresh", true, None::<&str>)?,
+        MenuItem::with_id(app_handle, format!("{}{}", PROVIDER_COPY_PREFIX, entry.id), "Copy Usage", true, None::<&str>)?,
+    ];
+    for (idx, link) in entry.links.iter().enumerate() {
+        actions.push(MenuItem::with_id(
+            app_handle,
+            format!("{}{}:{}", PROVIDER_LINK_PREFIX, entry.id, idx),
+            format!("Open {}", link.label),
+            true,
+            None::<&str>,
+        )?);
+    }
+    let is_pinned = get_stored_pinned_providers(app_handle).contains(&entry.id);
+    let pin = CheckMenuItem::with_id(
+        app_handle,
+        format!("{}{}", PROVIDER_PIN_PREFIX, entry.id),
+        "Pin to Tray",
+        true,
+        is_pinned,
+        None::<&str>,
+    )?;
+    let mut action_refs: Vec<&dyn IsMenuItem<Wry>> =
+        actions.iter().map(|item| item as &dyn IsMenuItem<Wry>).collect();
+    action_refs.push(&pin);
+
+    Submenu::with_id_and_items(
+        app_handle,
+        format!("provider:{}:{}", entry.id, position),
+        provider_summary(&entry.name, &entry.primary_candidates, &entry.output, now),
+        true,
+        &action_refs,
+    )
+}
+
+fn handle_provider_menu_event(app_handle: &AppHandle, id: &str) {
+    if let Some(plugin_id) = id.strip_prefix(PROVIDER_REFRESH_PREFIX) {
+        log::info!("tray: refresh requested for {}", plugin_id);
+        let _ = app_handle.emit("tray:refresh-provider", plugin_id);
+    } else if let Some(plugin_id) = id.strip_prefix(PROVIDER_COPY_PREFIX) {
+        let outputs = {
+            let state = app_handle.state::<Mutex<crate::AppState>>();
+            let Ok(locked) = state.lock() else {
+                return;
+            };
+            locked.latest_outputs.get(plugin_id).cloned().unwrap_or_default()
+        };
+        if outputs.is_empty() {
+            return;
+        }
+        let now = OffsetDateTime::now_utc();
+        let text = outputs
+            .iter()
+            .map(|output| clipboard_text(output, now))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        if let Err(e) = app_handle.clipboard().write_text(text) {
+            log::warn!("tray: failed to copy usage for {}: {}", plugin_id, e);
+        }
+    } else if let Some(plugin_id) = id.strip_prefix(PROVIDER_PIN_PREFIX) {
+        let mut pinned = get_stored_pinned_providers(app_handle);
+        if pinned.iter().any(|id| id == plugin_id) {
+            pinned.retain(|id| id != plugin_id);
+        } else {
+            pinned.push(plugin_id.to_string());
+        }
+        set_pinned_providers(app_handle, pinned);
+    } else if let Some(rest) = id.strip_prefix(PROVIDER_LINK_PREFIX) {
+        let Some((plugin_id, idx)) = rest.rsplit_once(':') else {
+            return;
+        };
+        let Ok(idx) = idx.parse::<usize>() else {
+            return;
+        };
+        let url = {
+            let state = app_handle.state::<Mutex<crate::AppState>>();
+            let Ok(locked) = state.lock() else {
+                return;
+            };
+            locked
+                .providers
+                .iter()
+                .find(|provider| provider.id() == plugin_id)
+                .and_then(|provider| provider.manifest().links.get(idx))
+                .map(|link| link.url.clone())
+        };
+        if let Some(url) = url {
+            log::info!("tray: opening {} link for {}", url, plugin_id);
+            if let Err(e) = app_handle.opener().open_url(url, None::<&str>) {
+                log::warn!("tray: failed to open link for {}: {}", plugin_id, e);
+            }
+        }
+    }
+}
+
+/// Provider name, suffixed with the account label for multi-account plugins.
+fn display_name(name: &str, account: Option<&str>) -> String {
+    match account {
+        Some(account) => format!("{} ({})", name, account),
+        None => name.to_string(),
+    }
+}
+
+/// Error message when the output is a single error badge (same rule as the panel).
+fn error_message(output: &PluginOutput) -> Option<&str> {
+    match output.lines.as_slice() {
+        [MetricLine::Badge { label, text, .. }] if label == "Error" && output.sections.is_empty() => {
+            Some(text)
+        }
+        _ => None,
+    }
+}
+
+/// First available primary candidate, falling back to the first progress line.
+fn primary_line<'a>(candidates: &[String], output: &'a PluginOutput) -> Option<&'a MetricLine> {
+    // Candidates are manifest line ids, which the runtime gives every declared line.
+    let find_progress = |wanted: &str| {
+        output
+            .all_lines()
+            .find(|line| matches!(line, MetricLine::Progress { .. }) && line.id() == Some(wanted))
+    };
+    candidates
+        .iter()
+        .find_map(|candidate| find_progress(candidate))
+        .or_else(|| {
+            output
+                .all_lines()
+                .find(|line| matches!(line, MetricLine::Progress { .. }))
+        })
+}
+
+/// Primary metric as a 0-100 share of its limit, for the pinned icon gauge.
+fn primary_percent(candidates: &[String], output: &PluginOutput) -> Option<f64> {
+    if error_message(output).is_some() {
+        return None;
+    }
+    match primary_line(candidates, output)? {
+        MetricLine::Progress { used, limit, .. } => Some(used / limit * 100.0),
+        _ => None,
+    }
+}
+
+fn format_count(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+/// Money lines are shown in the display currency when the backend converted them.
+fn format_progress_value(
+    used: f64,
+    limit: f64,
+    format: &ProgressFormat,
+    converted: Option<&ConvertedAmount>,
+    humanized: Option<&ProgressText>,
+) -> String {
+    if let Some(humanized) = humanized {
+        return humanized.summary.clone();
+    }
+    if let Some(converted) = converted {
+        return format!(
+            "{} / {}",
+            currency::format_amount(converted.used, &converted.code),
+            currency::format_amount(converted.limit, &converted.code)
+        );
+    }
+    match format {
+        ProgressFormat::Percent => format!("{}%", (used / limit * 100.0).round() as i64),
+        ProgressFormat::Dollars => format!("${:.2} / ${:.2}", used, limit),
+        ProgressFormat::Currency { code } => format!(
+            "{} / {}",
+            currency::format_amount(used, code),
+            currency::format_amount(limit, code)
+        ),
+        ProgressFormat::Count { suffix } => {
+            format!("{} / {} {}", format_count(used), format_count(limit), suffix)
+        }
+        ProgressFormat::Tokens
+        | ProgressFormat::Requests
+        | ProgressFormat::Duration { .. }
+        | ProgressFormat::Bytes => {
+            humanize::summary(used, limit, format, &NumberStyle::default()).unwrap_or_default()
+        }
+    }
+}
+
+/// Same buckets as `ctx.fmt.resetIn` in the plugin utils.
+fn format_reset_in(seconds: i64) -> String {
+    let total_minutes = seconds / 60;
+    let total_hours = total_minutes / 60;
+    let days = total_hours / 24;
+    if days > 0 {
+        format!("{}d {}h", days, total_hours % 24)
+    } else if total_hours > 0 {
+        format!("{}h {}m", total_hours, total_minutes % 60)
+    } else if total_minutes > 0 {
+        format!("{}m", total_minutes)
+    } else {
+        "<1m".to_string()
+    }
+}
+
+fn reset_suffix(resets_at: Option<&str>, now: OffsetDateTime) -> Option<String> {
+    let resets_at = OffsetDateTime::parse(resets_at?, &Rfc3339).ok()?;
+    let seconds = (resets_at - now).whole_seconds();
+    if seconds < 0 {
+        return None;
+    }
+    Some(format!("resets {}", format_reset_in(seconds)))
+}
+
+/// Usage past the limit is overflow; at the hard limit the provider blocks further use.
+fn limit_note(used: f64, limit: f64, hard_limit: Option<f64>) -> Option<&'static str> {
+    if hard_limit.is_some_and(|hard| used >= hard) {
+        Some("hard limit reached")
+    } else if used > limit {
+        Some("over limit")
+    } else {
+        None
+    }
+}
+
+/// "Session 64% · resets 2h 10m" for progress lines, "Label: value" otherwise.
+fn line_summary(line: &MetricLine, now: OffsetDateTime) -> String {
+    match line {
+        MetricLine::Progress {
+            label,
+            used,
+            limit,
+            format,
+            resets_at,
+            converted,
+            humanized,
+            hard_limit,
+            ..
+        } => {
+            let mut parts = vec![format!(
+                "{} {}",
+                label,
+                format_progress_value(*used, *limit, format, converted.as_ref(), humanized.as_deref())
+            )];
+            parts.extend(limit_note(*used, *limit, *hard_limit).map(str::to_string));
+            parts.extend(reset_suffix(resets_at.as_deref(), now));
+            parts.join(" · ")
+        }
+        MetricLine::Text { label, value, .. } => format!("{}: {}", label, value),
+        MetricLine::Badge { label, text, .. } => format!("{}: {}", label, text),
+        MetricLine::Sparkline {
+            label, points, unit, ..
+        } => match points.last() {
+            Some(point) => format!("{}: {} {}", label, format_count(point.value), unit),
+            None => label.clone(),
+        },
+        MetricLine::Table { label, rows, .. } => {
+            let rows: Vec<String> = rows.iter().map(|row| row.join(" · ")).collect();
+            format!("{}: {}", label, rows.join("; "))
+        }
+        MetricLine::Link { label, url, .. } => format!("{}: {}", label, url),
+    }
+}
+
+/// Tray menu label, e.g. "Codex — Session 64% · resets 2h 10m".
+fn provider_summary(
+    name: &str,
+    candidates: &[String],
+    output: &PluginOutput,
+    now: OffsetDateTime,
+) -> String {
+    if error_message(output).is_some() {
+        return format!("{} — Error", name);
+    }
+    match primary_line(candidates, output) {
+        Some(line) => format!("{} — {}", name, line_summary(line, now)),
+        None => name.to_string(),
+    }
+}
+
+fn provider_tooltip_line(name: &str, candidates: &[String], output: &PluginOutput) -> String {
+    if error_message(output).is_some() {
+        return format!("{}: Error", name);
+    }
+    match primary_line(candidates, output) {
+        Some(MetricLine::Progress {
+            used,
+            limit,
+            format,
+            converted,
+            humanized,
+            ..
+        }) => format!(
+            "{}: {}",
+            name,
+            format_progress_value(*used, *limit, format, converted.as_ref(), humanized.as_deref())
+        ),
+        _ => name.to_string(),
+    }
+}
+
+/// Gauge and tooltip of a pinned icon. Pins are per plugin, so a multi-account plugin shows
+/// its most used account and lists every account in the tooltip.
+fn pinned_icon_state(name: &str, candidates: &[String], outputs: &[PluginOutput]) -> (Option<f64>, String) {
+    let percent = outputs
+        .iter()
+        .filter_map(|output| primary_percent(candidates, output))
+        .reduce(f64::max);
+    let tooltip = if outputs.is_empty() {
+        name.to_string()
+    } else {
+        outputs
+            .iter()
+            .map(|output| {
+                provider_tooltip_line(&display_name(name, output.account.as_deref()), candidates, output)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    (percent, tooltip)
+}
+
+fn tooltip_text(provider_lines: &[String]) -> String {
+    let mut lines = vec![TRAY_TOOLTIP.to_string()];
+    lines.extend(provider_lines.iter().cloned());
+    lines.join("\n")
+}
+
+/// Plain-text dump of every line, copied by the "Copy Usage" menu action.
+fn clipboard_text(output: &PluginOutput, now: OffsetDateTime) -> String {
+    let name = display_name(&output.display_name, output.account.as_deref());
+    let mut lines = vec![match &output.plan {
+        Some(plan) => format!("{} · {}", name, plan),
+        None => name,
+    }];
+    lines.extend(output.lines.iter().map(|line| line_summary(line, now)));
+    for section in &output.sections {
+        lines.push(format!("{}:", section.title));
+        lines.extend(
+            section
+                .lines
+                .iter()
+                .map(|line| format!("  {}", line_summary(line, now))),
+        );
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_engine::runtime::OutputSection;
+
+    fn output(lines: Vec<MetricLine>) -> PluginOutput {
+        PluginOutput {
+            provider_id: "codex".to_string(),
+            display_name: "Codex".to_string(),
+            plan: Some("Plus".to_string()),
+            lines,
+            icon_url: String::new(),
+            account: None,
+            account_id: None,
+            sections: Vec::new(),
+        }
+    }
+
+    fn percent_line(label: &str, used: f64, resets_at: Option<&str>) -> MetricLine {
+        MetricLine::Progress {
+            id: None,
+            label: label.to_string(),
+            used,
+            limit: 100.0,
+            format: ProgressFormat::Percent,
+            resets_at: resets_at.map(str::to_string),
+            period_duration_ms: None,
+            color: None,
+            segments: Vec::new(),
+            soft_limit: None,
+            hard_limit: None,
+            converted: None,
+            humanized: None,
+        }
+    }
+
+    fn with_id(mut line: MetricLine, line_id: &str) -> MetricLine {
+        if let MetricLine::Progress { id, .. } = &mut line {
+            *id = Some(line_id.to_string());
+        }
+        line
+    }
+
+    fn now() -> OffsetDateTime {
+        OffsetDateTime::parse("2026-02-01T10:00:00Z", &Rfc3339).unwrap()
+    }
+
+    #[test]
+    fn reset_in_matches_plugin_formatter_buckets() {
+        assert_eq!(format_reset_in(180_000), "2d 2h");
+        assert_eq!(format_reset_in(7_800), "2h 10m");
+        assert_eq!(format_reset_in(300), "5m");
+        assert_eq!(format_reset_in(30), "<1m");
+    }
+
+    #[test]
+    fn summary_uses_primary_candidate_with_reset() {
+        let output = output(vec![
+            percent_line("Weekly", 20.0, None),
+            with_id(percent_line("Session", 64.0, Some("2026-02-01T12:10:00Z")), "session"),
+        ]);
+        let summary = provider_summary("Codex", &["session".to_string()], &output, now());
+        assert_eq!(summary, "Codex — Session 64% · resets 2h 10m");
+    }
+
+    #[test]
+    fn summary_falls_back_to_first_progress_line() {
+        let output = output(vec![percent_line("Weekly", 20.4, None)]);
+        let summary = provider_summary("Codex", &["session".to_string()], &output, now());
+        assert_eq!(summary, "Codex — Weekly 20%");
+    }
+
+    #[test]
+    fn summary_reports_error_output() {
+        let output = output(vec![MetricLine::Badge {
+            id: None,
+            label: "Error".to_string(),
+            text: "Not logged in".to_string(),
+            color: None,
+            subtitle: None,
+        }]);
+        assert_eq!(provider_summary("Codex", &[], &output, now()), "Codex — Error");
+        assert_eq!(provider_tooltip_line("Codex", &[], &output), "Codex: Error");
+    }
+
+    #[test]
+    fn primary_percent_uses_share_of_limit() {
+        let spend = output(vec![MetricLine::Progress {
+            id: None,
+            label: "Spend".to_string(),
+            used: 5.0,
+            limit: 20.0,
+            format: ProgressFormat::Dollars,
+            resets_at: None,
+            period_duration_ms: None,
+            color: None,
+            segments: Vec::new(),
+            soft_limit: None,
+            hard_limit: None,
+            converted: None,
+            humanized: None,
+        }]);
+        assert_eq!(primary_percent(&[], &spend), Some(25.0));
+        assert_eq!(primary_percent(&[], &output(vec![])), None);
+    }
+
+    #[test]
+    fn money_lines_use_the_converted_amount() {
+        let converted = ConvertedAmount {
+            code: "EUR".to_string(),
+            used: 4.0,
+            limit: 16.0,
+            rate: 0.8,
+            rates_date: "2026-10-01".to_string(),
+        };
+        assert_eq!(
+            format_progress_value(5.0, 20.0, &ProgressFormat::Dollars, Some(&converted), None),
+            "€4.00 / €16.00"
+        );
+        let gbp = ProgressFormat::Currency { code: "GBP".to_string() };
+        assert_eq!(format_progress_value(5.0, 20.0, &gbp, None, None), "£5.00 / £20.00");
+    }
+
+    #[test]
+    fn summary_notes_overflow_past_the_limit() {
+        let mut line = percent_line("Session", 120.0, None);
+        assert_eq!(line_summary(&line, now()), "Session 120% · over limit");
+        if let MetricLine::Progress { hard_limit, .. } = &mut line {
+            *hard_limit = Some(120.0);
+        }
+        assert_eq!(line_summary(&line, now()), "Session 120% · hard limit reached");
+        assert_eq!(line_summary(&percent_line("Session", 100.0, None), now()), "Session 100%");
+    }
+
+    #[test]
+    fn typed_lines_use_the_humanized_summary() {
+        let humanized = ProgressText {
+            used: "1,2M tokens".to_string(),
+            limit: "5M tokens".to_string(),
+            left: "3,8M tokens".to_string(),
+            summary: "1,2M / 5M tokens".to_string(),
+        };
+        let tokens = ProgressFormat::Tokens;
+        assert_eq!(
+            format_progress_value(1_200_000.0, 5_000_000.0, &tokens, None, Some(&humanized)),
+            "1,2M / 5M tokens"
+        );
+        assert_eq!(
+            format_progress_value(1_200_000.0, 5_000_000.0, &tokens, None, None),
+            "1.2M / 5M tokens"
+        );
+    }
+
+    #[test]
+    fn clipboard_text_indents_section_lines() {
+        let mut output = output(vec![percent_line("Session", 64.0, None)]);
+        output.sections = vec![OutputSection {
+            title: "Gemini 3 Pro".to_string(),
+            lines: vec![with_id(percent_line("Requests", 20.0, None), "requests")],
+            collapsed: true,
+        }];
+        assert_eq!(
+            clipboard_text(&output, now()),
+            "Codex · Plus\nSession 64%\nGemini 3 Pro:\n  Requests 20%"
+        );
+        assert_eq!(primary_percent(&["requests".to_string()], &output), Some(20.0));
+    }
+
+    #[test]
+    fn primary_line_is_matched_by_id() {
+        let renamed = with_id(percent_line("5h window", 40.0, None), "session");
+        let output = output(vec![percent_line("Weekly", 10.0, None), renamed]);
+        assert_eq!(primary_percent(&["session".to_string()], &output), Some(40.0));
+    }
+
+    #[test]
+    fn pinned_icons_show_the_most_used_account() {
+        let mut personal = output(vec![percent_line("Session", 20.0, None)]);
+        personal.account = Some("Personal".to_string());
+        let mut work = output(vec![percent_line("Session", 85.0, None)]);
+        work.account = Some("Work".to_string());
+        let (percent, tooltip) = pinned_icon_state("Codex", &[], &[personal, work]);
+        assert_eq!(percent, Some(85.0));
+        assert_eq!(tooltip, "Codex (Personal): 20%\nCodex (Work): 85%");
+        assert_eq!(pinned_icon_state("Codex", &[], &[]), (None, "Codex".to_string()));
+    }
+
+    #[test]
+    fn clipboard_text_names_the_account() {
+        let mut work = output(vec![]);
+        work.account = Some("Work".to_string());
+        work.plan = None;
+        assert_eq!(clipboard_text(&work, now()), "Codex (Work)");
+    }
+
+    #[test]
+    fn clipboard_text_lists_every_line() {
+        let output = output(vec![
+            percent_line("Session", 64.0, None),
+            MetricLine::Progress {
+                id: None,
+                label: "Credits".to_string(),
+                used: 12.5,
+                limit: 50.0,
+                format: ProgressFormat::Dollars,
+                resets_at: None,
+                period_duration_ms: None,
+                color: None,
+                segments: Vec::new(),
+                soft_limit: None,
+                hard_limit: None,
+                converted: None,
+                humanized: None,
+            },
+            MetricLine::Text {
+                id: None,
+                label: "Account".to_string(),
+                value: "work".to_string(),
+                color: None,
+                subtitle: None,
+            },
+        ]);
+        assert_eq!(
+            clipboard_text(&output, now()),
+            "Codex · Plus\nSession 64%\nCredits $12.50 / $50.00\nAccount: work"
+        );
+    }
+}
@@ -0,0 +1,158 @@
+/// Fallback ring color when a plugin has no (or an invalid) `brandColor`.
+const DEFAULT_RGB: [u8; 3] = [0x6b, 0x72, 0x80];
+/// Alpha of the unfilled part of the ring.
+const TRACK_ALPHA: f64 = 0.3;
+const RING_THICKNESS: f64 = 4.0;
+
+/// 3x5 bitmap digits, one row per byte (bit 2 = left column).
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// Parse `#RRGGBB` (or `RRGGBB`) into RGB components.
+pub fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).ok();
+    Some([channel(0..2)?, channel(2..4)?, channel(4..6)?])
+}
+
+/// Render a square RGBA tray icon: a ring gauge filled clockwise from the top in the
+/// brand color, with the rounded percentage drawn in the middle. `percent: None`
+/// renders an empty track (no data yet).
+pub fn render_provider_icon(brand_color: Option<&str>, percent: Option<f64>, size: u32) -> Vec<u8> {
+    let rgb = brand_color.and_then(parse_hex_color).unwrap_or(DEFAULT_RGB);
+    let fraction = percent
+        .filter(|p| p.is_finite())
+        .map(|p| (p / 100.0).clamp(0.0, 1.0));
+
+    let mut rgba = vec![0u8; (size * size * 4) as usize];
+    let half = size as f64 / 2.0;
+    let outer = half - 0.5;
+    let inner = outer - RING_THICKNESS;
+
+    for y in 0..size {
+        for x in 0..size {
+            let dx = x as f64 + 0.5 - half;
+            let dy = y as f64 + 0.5 - half;
+            let r = (dx * dx + dy * dy).sqrt();
+            let coverage = (outer - r + 0.5).clamp(0.0, 1.0) * (r - inner + 0.5).clamp(0.0, 1.0);
+            if coverage <= 0.0 {
+                continue;
+            }
+            // Clockwise angle from 12 o'clock, normalized to [0, 1)
+            let angle = dx.atan2(-dy).rem_euclid(std::f64::consts::TAU) / std::f64::consts::TAU;
+            let filled = fraction.is_some_and(|f| angle < f);
+            let alpha = if filled { 1.0 } else { TRACK_ALPHA };
+            put_pixel(&mut rgba, size, x, y, rgb, alpha * coverage);
+        }
+    }
+
+    if let Some(fraction) = fraction {
+        draw_number(&mut rgba, size, (fraction * 100.0).round() as u32, rgb);
+    }
+    rgba
+}
+
+fn put_pixel(rgba: &mut [u8], size: u32, x: u32, y: u32, rgb: [u8; 3], alpha: f64) {
+    let idx = ((y * size + x) * 4) as usize;
+    rgba[idx] = rgb[0];
+    rgba[idx + 1] = rgb[1];
+    rgba[idx + 2] = rgb[2];
+    rgba[idx + 3] = (alpha * 255.0).round() as u8;
+}
+
+/// Draw `value` centered, scaled so it fits inside the ring.
+fn draw_number(rgba: &mut [u8], size: u32, value: u32, rgb: [u8; 3]) {
+    let digits: Vec<usize> = value.to_string().bytes().map(|b| (b - b'0') as usize).collect();
+    let count = digits.len() as u32;
+    let spacing = if count > 2 { 1 } else { 2 };
+    let inner_width = size.saturating_sub(2 * (RING_THICKNESS as u32 + 2));
+    let scale = (1..=(size / 16).max(1))
+        .rev()
+        .find(|scale| count * 3 * scale + (count - 1) * spacing <= inner_width)
+        .unwrap_or(1);
+
+    let width = count * 3 * scale + (count - 1) * spacing;
+    let height = 5 * scale;
+    let left = size.saturating_sub(width) / 2;
+    let top = size.saturating_sub(height) / 2;
+
+    for (i, digit) in digits.iter().enumerate() {
+        let digit_left = left + i as u32 * (3 * scale + spacing);
+        for (row, bits) in DIGITS[*digit].iter().enumerate() {
+            for col in 0..3u32 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                for sy in 0..scale {
+                    for sx in 0..scale {
+                        let x = digit_left + col * scale + sx;
+                        let y = top + row as u32 * scale + sy;
+                        if x < size && y < size {
+                            put_pixel(rgba, size, x, y, rgb, 1.0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alpha_at(rgba: &[u8], size: u32, x: u32, y: u32) -> u8 {
+        rgba[((y * size + x) * 4 + 3) as usize]
+    }
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(parse_hex_color("#74AA9C"), Some([0x74, 0xaa, 0x9c]));
+        assert_eq!(parse_hex_color("de7356"), Some([0xde, 0x73, 0x56]));
+        assert_eq!(parse_hex_color("#fff"), None);
+        assert_eq!(parse_hex_color("#zzzzzz"), None);
+    }
+
+    #[test]
+    fn ring_fills_clockwise_from_top() {
+        let size = 32;
+        let rgba = render_provider_icon(Some("#ff0000"), Some(25.0), size);
+        // Right of center on the ring (3 o'clock) is the end of the first quarter: still track.
+        // Top-right diagonal lies inside the filled quarter.
+        let filled = alpha_at(&rgba, size, 24, 5);
+        let track = alpha_at(&rgba, size, 5, 24);
+        assert_eq!(filled, 255);
+        assert!(track > 0 && track < 255, "expected translucent track, got {}", track);
+        assert_eq!(&rgba[((5 * size + 24) * 4) as usize..][..3], &[0xff, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn no_percent_renders_track_without_digits() {
+        let size = 32;
+        let rgba = render_provider_icon(None, None, size);
+        assert_eq!(alpha_at(&rgba, size, size / 2, size / 2), 0);
+        assert!(alpha_at(&rgba, size, 24, 5) > 0);
+    }
+
+    #[test]
+    fn digits_fit_for_full_usage() {
+        let size = 32;
+        let rgba = render_provider_icon(Some("#000000"), Some(100.0), size);
+        // "100" is drawn in the middle row band
+        let drawn = (0..size).filter(|x| alpha_at(&rgba, size, *x, size / 2) == 255).count();
+        assert!(drawn > 0);
+    }
+}
@@ -287,6 +287,39 @@ describe("App", () => {
     await screen.findByText("Now")
   })
 
+  it("shows one card per account for multi-account plugins", async () => {
+    state.loadPluginSettingsMock.mockResolvedValueOnce({ order: ["a"], disabled: [] })
+    render(<App />)
+    await waitFor(() => expect(state.startBatchMock).toHaveBeenCalled())
+    const result = (accountId: string, account: string, value: string) => ({
+      providerId: "a",
+      displayName: "Alpha",
+      iconUrl: "icon-a",
+      account,
+      accountId,
+      lines: [{ type: "text", label: "Plan", value }],
+    })
+    state.probeHandlers?.onResult(result("personal", "Personal", "Plus"))
+    state.probeHandlers?.onResult(result("work", "Work", "Team"))
+    // Labels may repeat; results are kept apart by account id
+    state.probeHandlers?.onResult(result("work-eu", "Work", "Pro"))
+    state.probeHandlers?.onBatchComplete()
+
+    await screen.findByText("Team")
+    expect(screen.getByText("Plus")).toBeInTheDocument()
+    expect(screen.getByText("Pro")).toBeInTheDocument()
+    expect(screen.getByText("Personal")).toBeInTheDocument()
+    expect(screen.getAllByText("Work")).toHaveLength(2)
+    expect(screen.getAllByText("Alpha")).toHaveLength(3)
+
+    // A new result for one account replaces only that account
+    state.probeHandlers?.onResult(result("work", "Work", "Enterprise"))
+    await screen.findByText("Enterprise")
+    expect(screen.getByText("Plus")).toBeInTheDocument()
+    expect(screen.getByText("Pro")).toBeInTheDocument()
+    expect(screen.queryByText("Team")).not.toBeInTheDocument()
+  })
+
   it("updates tray icon on probe results when plugin has a primary progress", async () => {
     state.invokeMock.mockImplementation(async (cmd: string) => {
       if (cmd === "list_plugins") {
@@ -749,6 +782,65 @@ describe("App", () => {
     await waitFor(() => expect(state.startBatchMock).toHaveBeenCalledWith(["b"]))
   })
 
+  it("lists unsupported plugins in settings but leaves them out of probe batches", async () => {
+    state.invokeMock.mockImplementation(async (cmd: string) => {
+      if (cmd === "list_plugins") {
+        return [
+          { id: "a", name: "Alpha", iconUrl: "icon-a", primaryProgressLabel: null, lines: [] },
+          { id: "b", name: "Beta", iconUrl: "icon-b", primaryProgressLabel: null, lines: [], supported: false, unsupportedReason: "Beta is only available on macOS." },
+        ]
+      }
+      return null
+    })
+    state.loadPluginSettingsMock.mockResolvedValueOnce({ order: ["a", "b"], disabled: [] })
+    render(<App />)
+    await waitFor(() => expect(state.startBatchMock).toHaveBeenCalledWith(["a"]))
+    expect(screen.queryByText("Beta")).not.toBeInTheDocument()
+
+    const settingsButtons = await screen.findAllByRole("button", { name: "Settings" })
+    await userEvent.click(settingsButtons[0])
+    expect(await screen.findByText("Beta is only available on macOS.")).toBeInTheDocument()
+  })
+
+  it("shows plugin diagnostics in settings", async () => {
+    state.invokeMock.mockImplementation(async (cmd: string) => {
+      if (cmd === "list_plugins") {
+        return [{ id: "a", name: "Alpha", iconUrl: "icon-a", primaryProgressLabel: null, lines: [] }]
+      }
+      if (cmd === "get_plugin_diagnostics") {
+        return [
+          {
+            file: "/plugins/a/plugin.json",
+            id: "a",
+            loaded: true,
+            diagnostics: [
+              { severity: "warning", file: "/plugins/a/plugin.json", pointer: "/brandColor", message: "brandColor 'red' is not a #rrggbb color; using the default", suggestion: null },
+            ],
+          },
+          {
+            file: "/plugins/broken/plugin.json",
+            id: null,
+            loaded: false,
+            diagnostics: [
+              { severity: "error", file: "/plugins/broken/plugin.json", pointer: "", message: "plugin.json is not valid JSON", suggestion: null },
+            ],
+          },
+        ]
+      }
+      return null
+    })
+    render(<App />)
+    await waitFor(() => expect(state.invokeMock).toHaveBeenCalledWith("get_plugin_diagnostics"))
+
+    const settingsButtons = await screen.findAllByRole("button", { name: "Settings" })
+    await userEvent.click(settingsButtons[0])
+    expect(
+      await screen.findByText("/brandColor: brandColor 'red' is not a #rrggbb color; using the default")
+    ).toBeInTheDocument()
+    expect(screen.getByText("/plugins/broken/plugin.json")).toBeInTheDocument()
+    expect(screen.getByText("plugin.json is not valid JSON")).toBeInTheDocument()
+  })
+
   it("uses fallback monitor sizing when monitor missing", async () => {
     state.currentMonitorMock.mockResolvedValueOnce(null)
     render(<App />)
@@ -936,6 +1028,40 @@ describe("App", () => {
     vi.useRealTimers()
   })
 
+  it("skips plugins with a refreshInterval until it has nearly passed", async () => {
+    vi.useFakeTimers()
+    state.invokeMock.mockImplementation(async (cmd: string) => {
+      if (cmd === "list_plugins") {
+        return [
+          { id: "a", name: "Alpha", iconUrl: "icon-a", lines: [] },
+          { id: "b", name: "Beta", iconUrl: "icon-b", lines: [], refreshInterval: 12 },
+        ]
+      }
+      return null
+    })
+    state.loadAutoUpdateIntervalMock.mockResolvedValueOnce(5)
+    state.loadPluginSettingsMock.mockResolvedValueOnce({ order: ["a", "b"], disabled: [] })
+
+    render(<App />)
+    await vi.waitFor(() => expect(state.startBatchMock).toHaveBeenCalledWith(["a", "b"]))
+    state.startBatchMock.mockClear()
+
+    // 5 minutes after start: Beta's 12 minutes have not passed.
+    await vi.advanceTimersByTimeAsync(5 * 60 * 1000)
+    expect(state.startBatchMock).toHaveBeenLastCalledWith(["a"])
+
+    // 10 minutes: within half a tick of 12, so Beta is probed now rather than at 15.
+    await vi.advanceTimersByTimeAsync(5 * 60 * 1000)
+    expect(state.startBatchMock).toHaveBeenLastCalledWith(["a", "b"])
+
+    // 15 minutes: Beta was just refreshed.
+    await vi.advanceTimersByTimeAsync(5 * 60 * 1000)
+    expect(state.startBatchMock).toHaveBeenLastCalledWith(["a"])
+    expect(state.startBatchMock).toHaveBeenCalledTimes(3)
+
+    vi.useRealTimers()
+  })
+
   it("logs error when auto-update batch fails", async () => {
     vi.useFakeTimers()
     const errorSpy = vi.spyOn(console, "error").mockImplementation(() => {})
@@ -10,7 +10,8 @@ import { PanelFooter } from "@/components/panel-footer"
 import { OverviewPage } from "@/pages/overview"
 import { ProviderDetailPage } from "@/pages/provider-detail"
 import { SettingsPage } from "@/pages/settings"
-import type { PluginMeta, PluginOutput } from "@/lib/plugin-types"
+import type { PluginDisplayState, PluginMeta, PluginOutput, PluginReport } from "@/lib/plugin-types"
+import { pluginStateKey, providerStates, withoutAccountStates } from "@/lib/plugin-states"
 import { track } from "@/lib/analytics"
 import { getTrayIconSizePx, renderTrayBarsIcon } from "@/lib/tray-bars-icon"
 import { getTrayPrimaryBars } from "@/lib/tray-primary-progress"
@@ -19,13 +20,17 @@ import { useAppUpdate } from "@/hooks/use-app-update"
 import {
   arePluginSettingsEqual,
   DEFAULT_AUTO_UPDATE_INTERVAL,
+  DEFAULT_DISPLAY_CURRENCY,
   DEFAULT_DISPLAY_MODE,
   DEFAULT_TRAY_ICON_STYLE,
   DEFAULT_TRAY_SHOW_PERCENTAGE,
   DEFAULT_THEME_MODE,
+  displayCurrencies,
   getEnabledPluginIds,
+  getProbePluginIds,
   isTrayPercentageMandatory,
   loadAutoUpdateInterval,
+  loadDisplayCurrency,
   loadDisplayMode,
   loadPluginSettings,
   loadTrayShowPercentage,
@@ -33,6 +38,7 @@ import {
   loadThemeMode,
   normalizePluginSettings,
   saveAutoUpdateInterval,
+  saveDisplayCurrency,
   saveDisplayMode,
   savePluginSettings,
   saveTrayShowPercentage,
@@ -40,6 +46,7 @@ import {
   saveThemeMode,
   type AutoUpdateIntervalMinutes,
   type DisplayMode,
+  type ExchangeRates,
   type PluginSettings,
   type TrayIconStyle,
   type ThemeMode,
@@ -53,6 +60,8 @@ const TRAY_SETTINGS_DEBOUNCE_MS = 2000;
 const TRAY_PROBE_DEBOUNCE_MS = 500;
 
 type PluginState = {
+  /** Label of the account profile the state belongs to. */
+  account: string | null
   data: PluginOutput | null
   loading: boolean
   error: string | null
@@ -66,14 +75,18 @@ function App() {
   const [canScrollDown, setCanScrollDown] = useState(false);
   const [pluginStates, setPluginStates] = useState<Record<string, PluginState>>({})
   const [pluginsMeta, setPluginsMeta] = useState<PluginMeta[]>([])
+  const [pluginReports, setPluginReports] = useState<PluginReport[]>([])
   const [pluginSettings, setPluginSettings] = useState<PluginSettings | null>(null)
   const [autoUpdateInterval, setAutoUpdateInterval] = useState<AutoUpdateIntervalMinutes>(
     DEFAULT_AUTO_UPDATE_INTERVAL
   )
   const [autoUpdateNextAt, setAutoUpdateNextAt] = useState<number | null>(null)
   const [autoUpdateResetToken, setAutoUpdateResetToken] = useState(0)
+  const autoRefreshedAtRef = useRef(new Map<string, number>())
   const [themeMode, setThemeMode] = useState<ThemeMode>(DEFAULT_THEME_MODE)
   const [displayMode, setDisplayMode] = useState<DisplayMode>(DEFAULT_DISPLAY_MODE)
+  const [displayCurrency, setDisplayCurrency] = useState(DEFAULT_DISPLAY_CURRENCY)
+  const [currencyOptions, setCurrencyOptions] = useState<string[]>([DEFAULT_DISPLAY_CURRENCY])
   const [trayIconStyle, setTrayIconStyle] = useState<TrayIconStyle>(DEFAULT_TRAY_ICON_STYLE)
   const [trayShowPercentage, setTrayShowPercentage] = useState(DEFAULT_TRAY_SHOW_PERCENTAGE)
   const [maxPanelHeightPx, setMaxPanelHeightPx] = useState<number | null>(null)
@@ -244,19 +257,22 @@ function App() {
   }, [pluginsMeta.length, pluginSettings, scheduleTrayIconUpdate, trayReady])
 
 
+  // One entry per account for plugins probed once per account profile
   const displayPlugins = useMemo(() => {
     if (!pluginSettings) return []
     const disabledSet = new Set(pluginSettings.disabled)
     const metaById = new Map(pluginsMeta.map((plugin) => [plugin.id, plugin]))
     return pluginSettings.order
       .filter((id) => !disabledSet.has(id))
-      .map((id) => {
+      .flatMap((id): PluginDisplayState[] => {
         const meta = metaById.get(id)
-        if (!meta) return null
-        const state = pluginStates[id] ?? { data: null, loading: false, error: null, lastManualRefreshAt: null }
-        return { meta, ...state }
+        if (!meta || meta.supported === false) return []
+        return providerStates(pluginStates, id).map(({ accountId, state }) => ({
+          meta,
+          accountId,
+          ...(state ?? { account: null, data: null, loading: false, error: null, lastManualRefreshAt: null }),
+        }))
       })
-      .filter((plugin): plugin is { meta: PluginMeta } & PluginState => Boolean(plugin))
   }, [pluginSettings, pluginStates, pluginsMeta])
 
   // Derive enabled plugin list for nav icons
@@ -267,7 +283,7 @@ function App() {
     return pluginSettings.order
       .filter((id) => !disabledSet.has(id))
       .map((id) => metaById.get(id))
-      .filter((p): p is PluginMeta => Boolean(p))
+      .filter((p): p is PluginMeta => Boolean(p) && p?.supported !== false)
       .map((p) => ({ id: p.id, name: p.name, iconUrl: p.iconUrl, brandColor: p.brandColor }))
   }, [pluginSettings, pluginsMeta])
 
@@ -294,9 +310,9 @@ function App() {
   }, [activeView, navPlugins])
 
   // Get the selected plugin for detail view
-  const selectedPlugin = useMemo(() => {
-    if (activeView === "home" || activeView === "settings") return null
-    return displayPlugins.find((p) => p.meta.id === activeView) ?? null
+  const selectedPlugins = useMemo(() => {
+    if (activeView === "home" || activeView === "settings") return []
+    return displayPlugins.filter((p) => p.meta.id === activeView)
   }, [activeView, displayPlugins])
 
 
@@ -404,7 +420,7 @@ function App() {
   }, [activeView, displayPlugins]);
 
   const getErrorMessage = useCallback((output: PluginOutput) => {
-    if (output.lines.length !== 1) return null
+    if (output.lines.length !== 1 || output.sections?.length) return null
     const line = output.lines[0]
     if (line.type === "badge" && line.label === "Error") {
       return line.text || "Couldn't update data. Try again?"
@@ -412,12 +428,19 @@ function App() {
     return null
   }, [])
 
+  // Account states are dropped so removed account profiles do not linger
   const setLoadingForPlugins = useCallback((ids: string[]) => {
     setPluginStates((prev) => {
-      const next = { ...prev }
+      const next = withoutAccountStates(prev, ids)
       for (const id of ids) {
         const existing = prev[id]
-        next[id] = { data: null, loading: true, error: null, lastManualRefreshAt: existing?.lastManualRefreshAt ?? null }
+        next[id] = {
+          account: null,
+          data: null,
+          loading: true,
+          error: null,
+          lastManualRefreshAt: existing?.lastManualRefreshAt ?? null,
+        }
       }
       return next
     })
@@ -425,10 +448,16 @@ function App() {
 
   const setErrorForPlugins = useCallback((ids: string[], error: string) => {
     setPluginStates((prev) => {
-      const next = { ...prev }
+      const next = withoutAccountStates(prev, ids)
       for (const id of ids) {
         const existing = prev[id]
-        next[id] = { data: null, loading: false, error, lastManualRefreshAt: existing?.lastManualRefreshAt ?? null }
+        next[id] = {
+          account: null,
+          data: null,
+          loading: false,
+          error,
+          lastManualRefreshAt: existing?.lastManualRefreshAt ?? null,
+        }
       }
       return next
     })
@@ -446,22 +475,36 @@ function App() {
           error: errorMessage.slice(0, 200),
         })
       }
+      // Multi-account plugins send one result per account, so the flag is kept until the batch completes
       const isManual = manualRefreshIdsRef.current.has(output.providerId)
-      if (isManual) {
-        manualRefreshIdsRef.current.delete(output.providerId)
-      }
-      setPluginStates((prev) => ({
-        ...prev,
-        [output.providerId]: {
-          data: errorMessage ? null : output,
-          loading: false,
-          error: errorMessage,
-          // Only set cooldown timestamp for successful manual refreshes
-          lastManualRefreshAt: (!errorMessage && isManual)
-            ? Date.now()
-            : (prev[output.providerId]?.lastManualRefreshAt ?? null),
-        },
-      }))
+      // Only set cooldown timestamp for successful manual refreshes
+      const manualRefreshAt = !errorMessage && isManual ? Date.now() : null
+      const key = pluginStateKey(output.providerId, output.accountId)
+      setPluginStates((prev) => {
+        const own = prev[output.providerId]
+        const next = {
+          ...prev,
+          [key]: {
+            account: output.account ?? null,
+            data: errorMessage ? null : output,
+            loading: false,
+            error: errorMessage,
+            lastManualRefreshAt:
+              manualRefreshAt ?? prev[key]?.lastManualRefreshAt ?? own?.lastManualRefreshAt ?? null,
+          },
+        }
+        // The provider's own state outlives its account states, so it keeps the cooldown too
+        if (key !== output.providerId && manualRefreshAt !== null) {
+          next[output.providerId] = {
+            account: null,
+            data: own?.data ?? null,
+            loading: own?.loading ?? false,
+            error: own?.error ?? null,
+            lastManualRefreshAt: manualRefreshAt,
+          }
+        }
+        return next
+      })
 
       // Regenerate tray icon on every probe result (debounced to avoid churn).
       scheduleTrayIconUpdate("probe", TRAY_PROBE_DEBOUNCE_MS)
@@ -469,7 +512,9 @@ function App() {
     [getErrorMessage, scheduleTrayIconUpdate]
   )
 
-  const handleBatchComplete = useCallback(() => {}, [])
+  const handleBatchComplete = useCallback(() => {
+    manualRefreshIdsRef.current.clear()
+  }, [])
 
   const { startBatch } = useProbeEvents({
     onResult: handleProbeResult,
@@ -516,6 +561,13 @@ function App() {
           console.error("Failed to load display mode:", error)
         }
 
+        let storedDisplayCurrency = DEFAULT_DISPLAY_CURRENCY
+        try {
+          storedDisplayCurrency = await loadDisplayCurrency()
+        } catch (error) {
+          console.error("Failed to load display currency:", error)
+        }
+
         let storedTrayIconStyle = DEFAULT_TRAY_ICON_STYLE
         try {
           storedTrayIconStyle = await loadTrayIconStyle()
@@ -539,9 +591,10 @@ function App() {
           setAutoUpdateInterval(storedInterval)
           setThemeMode(storedThemeMode)
           setDisplayMode(storedDisplayMode)
+          setDisplayCurrency(storedDisplayCurrency)
           setTrayIconStyle(storedTrayIconStyle)
           setTrayShowPercentage(normalizedTrayShowPercentage)
-          const enabledIds = getEnabledPluginIds(normalized)
+          const enabledIds = getProbePluginIds(normalized, availablePlugins)
           setLoadingForPlugins(enabledIds)
           try {
             await startBatch(enabledIds)
@@ -578,20 +631,31 @@ function App() {
       setAutoUpdateNextAt(null)
       return
     }
-    const enabledIds = getEnabledPluginIds(pluginSettings)
+    const enabledIds = getProbePluginIds(pluginSettings, pluginsMetaRef.current)
     if (enabledIds.length === 0) {
       setAutoUpdateNextAt(null)
       return
     }
     const intervalMs = autoUpdateInterval * 60_000
     const scheduleNext = () => setAutoUpdateNextAt(Date.now() + intervalMs)
+    const startedAt = Date.now()
     scheduleNext()
     const interval = setInterval(() => {
-      setLoadingForPlugins(enabledIds)
-      startBatch(enabledIds).catch((error) => {
-        console.error("Failed to start auto-update batch:", error)
-        setErrorForPlugins(enabledIds, "Failed to start probe")
+      const now = Date.now()
+      // Plugins with their own refreshInterval skip ticks until it has passed
+      const dueIds = enabledIds.filter((id) => {
+        const minutes = pluginsMetaRef.current.find((plugin) => plugin.id === id)?.refreshInterval
+        const lastAt = autoRefreshedAtRef.current.get(id) ?? startedAt
+        return !minutes || now - lastAt >= minutes * 60_000 - intervalMs / 2
       })
+      for (const id of dueIds) autoRefreshedAtRef.current.set(id, now)
+      if (dueIds.length > 0) {
+        setLoadingForPlugins(dueIds)
+        startBatch(dueIds).catch((error) => {
+          console.error("Failed to start auto-update batch:", error)
+          setErrorForPlugins(dueIds, "Failed to start probe")
+        })
+      }
       scheduleNext()
     }, intervalMs)
     return () => clearInterval(interval)
@@ -644,6 +708,7 @@ function App() {
 
   const handleRetryPlugin = useCallback(
     (id: string) => {
+      if (pluginsMetaRef.current.find((plugin) => plugin.id === id)?.supported === false) return
       track("provider_refreshed", { provider_id: id })
       resetAutoUpdateSchedule()
       // Mark as manual refresh
@@ -657,6 +722,25 @@ function App() {
     [resetAutoUpdateSchedule, setLoadingForPlugins, setErrorForPlugins, startBatch]
   )
 
+  // Refresh a single provider from its tray menu entry
+  useEffect(() => {
+    if (!isTauri()) return
+    let cancelled = false
+    let unlisten: (() => void) | null = null
+
+    listen<string>("tray:refresh-provider", (event) => {
+      handleRetryPlugin(event.payload)
+    }).then((fn) => {
+      if (cancelled) fn()
+      else unlisten = fn
+    }).catch(console.error)
+
+    return () => {
+      cancelled = true
+      unlisten?.()
+    }
+  }, [handleRetryPlugin])
+
   const handleThemeModeChange = useCallback((mode: ThemeMode) => {
     track("setting_changed", { setting: "theme", value: mode })
     setThemeMode(mode)
@@ -675,6 +759,26 @@ function App() {
     })
   }, [scheduleTrayIconUpdate])
 
+  const handleDisplayCurrencyChange = useCallback((code: string) => {
+    track("setting_changed", { setting: "display_currency", value: code })
+    setDisplayCurrency(code)
+    // Conversion happens in the backend, so refresh once the setting is stored.
+    void saveDisplayCurrency(code)
+      .then(() => {
+        if (!pluginSettings) return
+        const enabledIds = getProbePluginIds(pluginSettings, pluginsMetaRef.current)
+        if (enabledIds.length === 0) return
+        setLoadingForPlugins(enabledIds)
+        return startBatch(enabledIds).catch((error) => {
+          console.error("Failed to start probe batch:", error)
+          setErrorForPlugins(enabledIds, "Failed to start probe")
+        })
+      })
+      .catch((error) => {
+        console.error("Failed to save display currency:", error)
+      })
+  }, [pluginSettings, setLoadingForPlugins, setErrorForPlugins, startBatch])
+
   const handleTrayIconStyleChange = useCallback((style: TrayIconStyle) => {
     track("setting_changed", { setting: "tray_icon_style", value: style })
     const mandatory = isTrayPercentageMandatory(style)
@@ -722,9 +826,40 @@ function App() {
     })
   }, [pluginSettings])
 
+  useEffect(() => {
+    let isMounted = true
+    invoke<ExchangeRates | null>("get_exchange_rates")
+      .then((table) => {
+        if (isMounted && table) setCurrencyOptions(displayCurrencies(table))
+      })
+      .catch((error) => {
+        console.error("Failed to load exchange rates:", error)
+      })
+    return () => {
+      isMounted = false
+    }
+  }, [])
+
+  useEffect(() => {
+    let isMounted = true
+    invoke<PluginReport[] | null>("get_plugin_diagnostics")
+      .then((reports) => {
+        if (isMounted) setPluginReports(reports ?? [])
+      })
+      .catch((error) => {
+        console.error("Failed to load plugin diagnostics:", error)
+      })
+    return () => {
+      isMounted = false
+    }
+  }, [])
+
   const settingsPlugins = useMemo(() => {
     if (!pluginSettings) return []
     const pluginMap = new Map(pluginsMeta.map((plugin) => [plugin.id, plugin]))
+    const reportMap = new Map(
+      pluginReports.filter((report) => report.loaded && report.id).map((report) => [report.id, report])
+    )
     return pluginSettings.order
       .map((id) => {
         const meta = pluginMap.get(id)
@@ -733,12 +868,18 @@ function App() {
           id,
           name: meta.name,
           enabled: !pluginSettings.disabled.includes(id),
+          unsupportedReason:
+            meta.supported === false ? meta.unsupportedReason || "Not supported on this system" : null,
+          diagnostics: reportMap.get(id)?.diagnostics ?? [],
         }
       })
-      .filter((plugin): plugin is { id: string; name: string; enabled: boolean } =>
-        Boolean(plugin)
-      )
-  }, [pluginSettings, pluginsMeta])
+      .filter((plugin): plugin is NonNullable<typeof plugin> => Boolean(plugin))
+  }, [pluginSettings, pluginsMeta, pluginReports])
+
+  const failedPluginReports = useMemo(
+    () => pluginReports.filter((report) => !report.loaded),
+    [pluginReports]
+  )
 
   const handleReorder = useCallback(
     (orderedIds: string[]) => {
@@ -764,13 +905,17 @@ function App() {
       track("provider_toggled", { provider_id: id, enabled: wasDisabled ? "true" : "false" })
       const disabled = new Set(pluginSettings.disabled)
 
+      const supported = pluginsMetaRef.current.find((plugin) => plugin.id === id)?.supported !== false
       if (wasDisabled) {
         disabled.delete(id)
-        setLoadingForPlugins([id])
-        startBatch([id]).catch((error) => {
-          console.error("Failed to start probe for enabled plugin:", error)
-          setErrorForPlugins([id], "Failed to start probe")
-        })
+        // Unsupported plugins are listed but never probed
+        if (supported) {
+          setLoadingForPlugins([id])
+          startBatch([id]).catch((error) => {
+            console.error("Failed to start probe for enabled plugin:", error)
+            setErrorForPlugins([id], "Failed to start probe")
+          })
+        }
       } else {
         disabled.add(id)
         // No probe needed for disable
@@ -825,6 +970,7 @@ function App() {
       return (
         <SettingsPage
           plugins={settingsPlugins}
+          failedPlugins={failedPluginReports}
           onReorder={handleReorder}
           onToggle={handleToggle}
           autoUpdateInterval={autoUpdateInterval}
@@ -833,6 +979,9 @@ function App() {
           onThemeModeChange={handleThemeModeChange}
           displayMode={displayMode}
           onDisplayModeChange={handleDisplayModeChange}
+          displayCurrency={displayCurrency}
+          displayCurrencies={currencyOptions}
+          onDisplayCurrencyChange={handleDisplayCurrencyChange}
           trayIconStyle={trayIconStyle}
           onTrayIconStyleChange={handleTrayIconStyleChange}
           trayShowPercentage={trayShowPercentage}
@@ -842,12 +991,12 @@ function App() {
       )
     }
     // Provider detail view
-    const handleRetry = selectedPlugin
-      ? () => handleRetryPlugin(selectedPlugin.meta.id)
+    const handleRetry = selectedPlugins.length > 0
+      ? () => handleRetryPlugin(selectedPlugins[0].meta.id)
       : /* v8 ignore next */ undefined
     return (
       <ProviderDetailPage
-        plugin={selectedPlugin}
+        plugins={selectedPlugins}
         onRetry={handleRetry}
         displayMode={displayMode}
       />
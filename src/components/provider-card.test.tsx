@@ -152,6 +152,73 @@ describe("ProviderCard", () => {
     expect(screen.getByRole("progressbar")).toHaveAttribute("aria-valuenow", "58")
   })
 
+  it("stacks segments and marks the limit when usage overflows", () => {
+    render(
+      <ProviderCard
+        name="Overage"
+        displayMode="used"
+        lines={[
+          {
+            type: "progress",
+            label: "Requests",
+            used: 600,
+            limit: 500,
+            hardLimit: 1000,
+            format: { kind: "count", suffix: "requests" },
+            segments: [
+              { kind: "included", label: "Included", used: 500 },
+              { kind: "overage", label: "Overage", used: 100, color: "#ef4444" },
+            ],
+          },
+        ]}
+      />
+    )
+    expect(screen.getByText("Over limit")).toBeInTheDocument()
+    expect(screen.getByRole("progressbar")).toHaveAttribute("aria-valuenow", "120")
+    expect(screen.getByRole("progressbar")).toHaveAttribute("aria-valuemax", "200")
+    expect(screen.getByTitle("Included")).toHaveStyle({ width: "50%" })
+    expect(screen.getByTitle("Overage")).toHaveStyle({ width: "10%" })
+    expect(screen.getByTestId("progress-marker")).toHaveStyle({ left: "50%" })
+  })
+
+  it("shows backend-humanized labels for typed formats", () => {
+    render(
+      <ProviderCard
+        name="Tokens"
+        displayMode="left"
+        lines={[
+          {
+            type: "progress",
+            label: "Tokens",
+            used: 1_200_000,
+            limit: 5_000_000,
+            format: { kind: "tokens" },
+            humanized: { used: "1.2M tokens", limit: "5M tokens", left: "3.8M tokens", summary: "1.2M / 5M tokens" },
+          },
+        ]}
+      />
+    )
+    expect(screen.getByText("3.8M tokens left")).toBeInTheDocument()
+    expect(screen.getByText("5M tokens limit")).toBeInTheDocument()
+  })
+
+  it("renders sections and honors the collapsed hint", async () => {
+    render(
+      <ProviderCard
+        name="Models"
+        displayMode="used"
+        sections={[
+          { title: "Gemini", lines: [{ type: "text", label: "Status", value: "ok" }] },
+          { title: "Claude", collapsed: true, lines: [{ type: "text", label: "Tier", value: "max" }] },
+        ]}
+      />
+    )
+    expect(screen.getByText("ok")).toBeInTheDocument()
+    expect(screen.queryByText("max")).not.toBeInTheDocument()
+    await userEvent.click(screen.getByRole("button", { name: "Claude" }))
+    expect(screen.getByText("max")).toBeInTheDocument()
+  })
+
   it("shows resets secondary text when resetsAt is present", () => {
     vi.useFakeTimers()
     const now = new Date("2026-02-02T00:00:00.000Z")
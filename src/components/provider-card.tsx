@@ -1,5 +1,6 @@
-import { useMemo } from "react"
-import { Hourglass, RefreshCw } from "lucide-react"
+import { useMemo, useState } from "react"
+import { openUrl } from "@tauri-apps/plugin-opener"
+import { ChevronRight, ExternalLink, Hourglass, RefreshCw } from "lucide-react"
 import { Badge } from "@/components/ui/badge"
 import { Button } from "@/components/ui/button"
 import { Progress } from "@/components/ui/progress"
@@ -9,18 +10,21 @@ import { SkeletonLines } from "@/components/skeleton-lines"
 import { PluginError } from "@/components/plugin-error"
 import { useNowTicker } from "@/hooks/use-now-ticker"
 import { REFRESH_COOLDOWN_MS, type DisplayMode } from "@/lib/settings"
-import type { ManifestLine, MetricLine } from "@/lib/plugin-types"
-import { clamp01 } from "@/lib/utils"
+import type { ManifestLine, MetricLine, OutputSection, ProgressSegmentKind } from "@/lib/plugin-types"
+import { clamp01, cn } from "@/lib/utils"
 import { calculatePaceStatus, type PaceStatus } from "@/lib/pace-status"
 import { buildPaceDetailText, formatCompactDuration, getPaceStatusText } from "@/lib/pace-tooltip"
 
 interface ProviderCardProps {
   name: string
+  /** Account profile label, for plugins probed once per account. */
+  account?: string | null
   plan?: string
   showSeparator?: boolean
   loading?: boolean
   error?: string | null
   lines?: MetricLine[]
+  sections?: OutputSection[]
   skeletonLines?: ManifestLine[]
   lastManualRefreshAt?: number | null
   onRetry?: () => void
@@ -37,6 +41,30 @@ export function formatNumber(value: number) {
   }).format(value)
 }
 
+/** Segments without a color of their own. */
+const SEGMENT_CLASSES: Record<ProgressSegmentKind, string> = {
+  included: "bg-primary",
+  reserved: "bg-primary/40",
+  overage: "bg-destructive",
+}
+
+/** Money in an ISO 4217 currency, e.g. "$12.34" or "€5". */
+function formatMoney(value: number, code: string) {
+  if (Number.isNaN(value)) value = 0
+  const fractionDigits = Number.isInteger(value) ? 0 : 2
+  try {
+    return new Intl.NumberFormat("en-US", {
+      style: "currency",
+      currency: code,
+      currencyDisplay: "narrowSymbol",
+      minimumFractionDigits: fractionDigits,
+      maximumFractionDigits: fractionDigits,
+    }).format(value)
+  } catch {
+    return `${formatNumber(value)} ${code}`
+  }
+}
+
 function formatCount(value: number) {
   if (!Number.isFinite(value)) return "0"
   const maximumFractionDigits = Number.isInteger(value) ? 0 : 2
@@ -98,11 +126,13 @@ function PaceIndicator({
 
 export function ProviderCard({
   name,
+  account = null,
   plan,
   showSeparator = true,
   loading = false,
   error = null,
   lines = [],
+  sections = [],
   skeletonLines = [],
   lastManualRefreshAt,
   onRetry,
@@ -115,20 +145,18 @@ export function ProviderCard({
     return remaining > 0 ? remaining : 0
   }, [lastManualRefreshAt])
 
-  // Filter lines based on scope - match by label since runtime lines can differ from manifest
-  const overviewLabels = new Set(
-    skeletonLines
-      .filter(line => line.scope === "overview")
-      .map(line => line.label)
-  )
+  // Filter lines based on scope - match by id, or by label for lines the backend gave no id
+  const overviewManifestLines = skeletonLines.filter(line => line.scope === "overview")
+  const overviewIds = new Set(overviewManifestLines.map(line => line.id))
+  const overviewLabels = new Set(overviewManifestLines.map(line => line.label))
   const filteredSkeletonLines = scopeFilter === "all"
     ? skeletonLines
-    : skeletonLines.filter(line => line.scope === "overview")
+    : overviewManifestLines
   const filteredLines = scopeFilter === "all"
     ? lines
-    : lines.filter(line => overviewLabels.has(line.label))
+    : lines.filter(line => line.id ? overviewIds.has(line.id) : overviewLabels.has(line.label))
 
-  const hasResetCountdown = filteredLines.some(
+  const hasResetCountdown = [...filteredLines, ...sections.flatMap((section) => section.lines)].some(
     (line) => line.type === "progress" && Boolean(line.resetsAt)
   )
 
@@ -162,6 +190,9 @@ export function ProviderCard({
         <div className="flex items-center justify-between mb-2">
           <div className="relative flex items-center">
             <h2 className="text-lg font-semibold" style={{ transform: "translateZ(0)" }}>{name}</h2>
+            {account && (
+              <span className="ml-1.5 text-sm text-muted-foreground">{account}</span>
+            )}
             {onRetry && (
               loading ? (
                 <Button
@@ -238,6 +269,14 @@ export function ProviderCard({
                 now={now}
               />
             ))}
+            {sections.map((section) => (
+              <SectionBlock
+                key={section.title}
+                section={section}
+                displayMode={displayMode}
+                now={now}
+              />
+            ))}
           </div>
         )}
       </div>
@@ -246,6 +285,43 @@ export function ProviderCard({
   )
 }
 
+function SectionBlock({
+  section,
+  displayMode,
+  now,
+}: {
+  section: OutputSection
+  displayMode: DisplayMode
+  now: number
+}) {
+  const [open, setOpen] = useState(!section.collapsed)
+  return (
+    <div>
+      <button
+        type="button"
+        aria-expanded={open}
+        onClick={() => setOpen((value) => !value)}
+        className="flex w-full items-center gap-1 text-xs font-medium uppercase tracking-wide text-muted-foreground hover:text-foreground"
+      >
+        <ChevronRight className={cn("h-3 w-3 transition-transform", open && "rotate-90")} />
+        {section.title}
+      </button>
+      {open && (
+        <div className="mt-2 space-y-4">
+          {section.lines.map((line, index) => (
+            <MetricLineRenderer
+              key={`${line.label}-${index}`}
+              line={line}
+              displayMode={displayMode}
+              now={now}
+            />
+          ))}
+        </div>
+      )}
+    </div>
+  )
+}
+
 function MetricLineRenderer({
   line,
   displayMode,
@@ -300,6 +376,98 @@ function MetricLineRenderer({
     )
   }
 
+  if (line.type === "sparkline") {
+    const values = line.points.map((point) => point.value)
+    const min = Math.min(...values)
+    const max = Math.max(...values)
+    const range = max - min || 1
+    const step = values.length > 1 ? 100 / (values.length - 1) : 0
+    const points = values
+      .map((value, index) => `${(index * step).toFixed(2)},${(22 - ((value - min) / range) * 20).toFixed(2)}`)
+      .join(" ")
+    const latest = values[values.length - 1]
+    return (
+      <div>
+        <div className="flex justify-between items-center h-[22px]">
+          <span className="text-sm text-muted-foreground flex-shrink-0">{line.label}</span>
+          <span className="text-sm text-muted-foreground tabular-nums">
+            {formatCount(latest)} {line.unit}
+          </span>
+        </div>
+        <svg
+          viewBox="0 0 100 24"
+          preserveAspectRatio="none"
+          className="w-full h-6 text-primary"
+          role="img"
+          aria-label={`${line.label} trend`}
+        >
+          <polyline
+            points={values.length > 1 ? points : `0,12 100,12`}
+            fill="none"
+            stroke={line.color ?? "currentColor"}
+            strokeWidth={1.5}
+            vectorEffect="non-scaling-stroke"
+          />
+        </svg>
+        {line.subtitle && (
+          <div className="text-xs text-muted-foreground text-right -mt-0.5">{line.subtitle}</div>
+        )}
+      </div>
+    )
+  }
+
+  if (line.type === "table") {
+    return (
+      <div>
+        <div className="text-sm font-medium mb-1.5">{line.label}</div>
+        <table className="w-full text-xs text-muted-foreground tabular-nums">
+          <thead>
+            <tr>
+              {line.columns.map((column, index) => (
+                <th key={index} className={cn("font-medium pb-1", index === 0 ? "text-left" : "text-right")}>
+                  {column}
+                </th>
+              ))}
+            </tr>
+          </thead>
+          <tbody>
+            {line.rows.map((row, rowIndex) => (
+              <tr key={rowIndex}>
+                {row.map((cell, index) => (
+                  <td key={index} className={cn("py-0.5 truncate max-w-0", index === 0 ? "text-left" : "text-right")}>
+                    {cell}
+                  </td>
+                ))}
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      </div>
+    )
+  }
+
+  if (line.type === "link") {
+    return (
+      <div>
+        <div className="flex justify-between items-center h-[22px]">
+          <button
+            type="button"
+            className="text-sm text-muted-foreground hover:text-foreground inline-flex items-center gap-1"
+            onClick={() => {
+              openUrl(line.url).catch(console.error)
+            }}
+          >
+            {line.label}
+            <ExternalLink className="h-3 w-3" />
+          </button>
+        </div>
+        {line.subtitle && (
+          <div className="text-xs text-muted-foreground -mt-0.5">{line.subtitle}</div>
+        )}
+      </div>
+    )
+  }
+
   if (line.type === "progress") {
     const resetsAtMs = line.resetsAt ? Date.parse(line.resetsAt) : Number.NaN
     const hasPaceContext = Number.isFinite(resetsAtMs) && Number.isFinite(line.periodDurationMs)
@@ -307,24 +475,66 @@ function MetricLineRenderer({
       displayMode === "used"
         ? line.used
         : Math.max(0, line.limit - line.used)
-    const percent = Math.round(clamp01(shownAmount / line.limit) * 10000) / 100
+    // Past the limit the bar stretches to the hard limit (or to usage) and the limit
+    // becomes a tick mark, so overflow stays visible instead of pinning the bar at 100%.
+    const barScale = displayMode === "used"
+      ? Math.max(line.hardLimit ?? line.limit, line.used)
+      : line.limit
+    const percent = Math.round(clamp01(shownAmount / barScale) * 10000) / 100
+    const toBarPercent = (value: number) => Math.round(clamp01(value / barScale) * 10000) / 100
+    // Screen readers get usage relative to the limit, whatever the bar is scaled to.
+    const toLimitPercent = (value: number) => (line.limit > 0 ? Math.round((value / line.limit) * 100) : 0)
+    const markers = displayMode === "used"
+      ? [line.limit, line.softLimit]
+          .filter((mark): mark is number => typeof mark === "number" && mark < barScale)
+          .map(toBarPercent)
+      : undefined
+    const segments = displayMode === "used" && line.segments?.length
+      ? line.segments.map((segment) => ({
+          value: toBarPercent(segment.used),
+          label: segment.label,
+          color: segment.color ?? undefined,
+          className: SEGMENT_CLASSES[segment.kind],
+        }))
+      : undefined
+    const isOverLimit = line.used > line.limit
+    const isHardLimitReached = line.hardLimit != null && line.used >= line.hardLimit
     const leftSuffix = displayMode === "left" ? " left" : ""
 
+    // Money lines show the backend's display-currency conversion when there is one.
+    const moneyCode =
+      line.converted?.code ??
+      (line.format.kind === "currency" ? line.format.code : line.format.kind === "dollars" ? "USD" : null)
+    const moneyRate = line.converted ? line.converted.rate : 1
+    const countSuffix =
+      line.format.kind === "count"
+        ? line.format.suffix
+        : line.format.kind === "duration"
+          ? line.format.unit
+          : line.format.kind
+    // Tokens, requests, durations and bytes come humanized from the backend.
+    const humanized = line.humanized
+    const humanizedShown = humanized && (displayMode === "used" ? humanized.used : humanized.left)
+
     const primaryText =
       line.format.kind === "percent"
         ? `${Math.round(shownAmount)}%${leftSuffix}`
-        : line.format.kind === "dollars"
-          ? `$${formatNumber(shownAmount)}${leftSuffix}`
-          : `${formatCount(shownAmount)} ${line.format.suffix}${leftSuffix}`
+        : moneyCode
+          ? `${formatMoney(shownAmount * moneyRate, moneyCode)}${leftSuffix}`
+          : humanizedShown
+            ? `${humanizedShown}${leftSuffix}`
+            : `${formatCount(shownAmount)} ${countSuffix}${leftSuffix}`
 
     const secondaryText =
       line.resetsAt
         ? formatResetIn(now, line.resetsAt)
         : line.format.kind === "percent"
           ? `${line.limit}% cap`
-          : line.format.kind === "dollars"
-            ? `$${formatNumber(line.limit)} limit`
-            : `${formatCount(line.limit)} ${line.format.suffix}`
+          : moneyCode
+            ? `${formatMoney(line.limit * moneyRate, moneyCode)} limit`
+            : humanized
+              ? `${humanized.limit} limit`
+              : `${formatCount(line.limit)} ${countSuffix}`
 
     // Calculate pace status if we have reset time and period duration
     const paceResult = hasPaceContext
@@ -352,10 +562,19 @@ function MetricLineRenderer({
           {paceStatus && (
             <PaceIndicator status={paceStatus} detailText={paceDetailText} isLimitReached={isLimitReached} />
           )}
+          {isOverLimit && (
+            <span className="text-xs font-normal text-destructive">
+              {isHardLimitReached ? "Hard limit reached" : "Over limit"}
+            </span>
+          )}
         </div>
         <Progress
           value={percent}
-          indicatorColor={line.color}
+          indicatorColor={isOverLimit && !line.color ? "var(--destructive)" : line.color}
+          segments={segments}
+          markers={markers}
+          aria-valuenow={toLimitPercent(shownAmount)}
+          aria-valuemax={Math.max(100, toLimitPercent(barScale))}
         />
         <div className="flex justify-between items-center mt-1.5">
           <span className="text-xs text-muted-foreground tabular-nums">
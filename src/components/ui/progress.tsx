@@ -2,18 +2,39 @@ import * as React from "react"
 
 import { cn } from "@/lib/utils"
 
+/** Stacked part of the bar; `value` is its width in percent of the bar. */
+export interface ProgressSegment {
+  value: number
+  label: string
+  color?: string
+  className?: string
+}
+
 interface ProgressProps extends React.HTMLAttributes<HTMLDivElement> {
   value?: number
   indicatorColor?: string
+  segments?: ProgressSegment[]
+  /** Tick positions in percent of the bar, e.g. the limit on a bar scaled to a hard limit. */
+  markers?: number[]
 }
 
+const clampPercent = (value: number) => Math.min(100, Math.max(0, value))
+
 const Progress = React.forwardRef<HTMLDivElement, ProgressProps>(
-  ({ className, value = 0, indicatorColor, ...props }, ref) => {
-    const clamped = Math.min(100, Math.max(0, value))
+  ({ className, value = 0, indicatorColor, segments, markers, ...props }, ref) => {
+    const clamped = clampPercent(value)
     const indicatorStyle = indicatorColor
       ? { backgroundColor: indicatorColor }
       : undefined
 
+    // Later segments are cut off once the bar is full.
+    let remaining = 100
+    const stacked = (segments ?? []).map((segment) => {
+      const width = Math.min(remaining, clampPercent(segment.value))
+      remaining -= width
+      return { ...segment, width }
+    })
+
     return (
       <div
         ref={ref}
@@ -24,10 +45,31 @@ const Progress = React.forwardRef<HTMLDivElement, ProgressProps>(
         className={cn("relative h-3 w-full overflow-hidden rounded-full bg-muted dark:bg-[#353537]", className)}
         {...props}
       >
-        <div
-          className="h-full transition-all bg-primary"
-          style={{ width: `${clamped}%`, ...indicatorStyle }}
-        />
+        {segments ? (
+          <div className="flex h-full">
+            {stacked.map((segment) => (
+              <div
+                key={segment.label}
+                title={segment.label}
+                className={cn("h-full transition-all", segment.color ? undefined : (segment.className ?? "bg-primary"))}
+                style={{ width: `${segment.width}%`, backgroundColor: segment.color }}
+              />
+            ))}
+          </div>
+        ) : (
+          <div
+            className="h-full transition-all bg-primary"
+            style={{ width: `${clamped}%`, ...indicatorStyle }}
+          />
+        )}
+        {markers?.map((marker) => (
+          <div
+            key={marker}
+            data-testid="progress-marker"
+            className="absolute inset-y-0 w-px bg-foreground/60"
+            style={{ left: `${clampPercent(marker)}%` }}
+          />
+        ))}
       </div>
     )
   }
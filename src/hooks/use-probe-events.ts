@@ -92,9 +92,11 @@ export function useProbeEvents({ onResult, onBatchComplete }: UseProbeEventsOpti
         : `batch-${Date.now()}-${Math.random().toString(16).slice(2)}`
 
     activeBatchIds.current.add(batchId)
+    // Progress labels humanized by the backend follow the UI locale.
+    const locale = typeof navigator !== "undefined" ? navigator.language : undefined
     const args = pluginIds
-      ? { batchId, pluginIds }
-      : { batchId }
+      ? { batchId, pluginIds, locale }
+      : { batchId, locale }
     try {
       const result = await invoke<ProbeBatchStarted>("start_probe_batch", args)
       return result.pluginIds
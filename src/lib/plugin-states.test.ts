@@ -0,0 +1,25 @@
+import { describe, expect, it } from "vitest"
+import { pluginStateKey, providerStates, withoutAccountStates } from "@/lib/plugin-states"
+
+describe("plugin states", () => {
+  it("keys account results by provider and account id", () => {
+    expect(pluginStateKey("codex")).toBe("codex")
+    expect(pluginStateKey("codex", null)).toBe("codex")
+    expect(pluginStateKey("codex", "work")).toBe("codex:work")
+  })
+
+  it("lists account states ahead of the provider's own", () => {
+    const states = { codex: 1, "codex:personal": 2, "codex:work": 3, "codex-next": 4 }
+    expect(providerStates(states, "codex")).toEqual([
+      { accountId: "personal", state: 2 },
+      { accountId: "work", state: 3 },
+    ])
+    expect(providerStates(states, "codex-next")).toEqual([{ accountId: null, state: 4 }])
+    expect(providerStates(states, "missing")).toEqual([{ accountId: null, state: undefined }])
+  })
+
+  it("drops account states of the given providers only", () => {
+    const states = { codex: 1, "codex:work": 3, "claude:work": 5 }
+    expect(withoutAccountStates(states, ["codex"])).toEqual({ codex: 1, "claude:work": 5 })
+  })
+})
@@ -0,0 +1,29 @@
+/**
+ * Probe results are stored per provider, or per provider and account id for plugins probed
+ * once per account profile. Provider ids never contain ":", so the account id follows one.
+ * Account labels may repeat, so they are not used in keys.
+ */
+export function pluginStateKey(providerId: string, accountId?: string | null): string {
+  return accountId ? `${providerId}:${accountId}` : providerId
+}
+
+/** States of one provider: one per account once account results arrived, else its own. */
+export function providerStates<T>(
+  states: Record<string, T | undefined>,
+  providerId: string
+): { accountId: string | null; state: T | undefined }[] {
+  const prefix = `${providerId}:`
+  const accounts = Object.keys(states)
+    .filter((key) => key.startsWith(prefix))
+    .map((key) => ({ accountId: key.slice(prefix.length), state: states[key] }))
+  if (accounts.length > 0) return accounts
+  return [{ accountId: null, state: states[providerId] }]
+}
+
+/** Drops the per-account states of the given providers, e.g. before probing them again. */
+export function withoutAccountStates<T>(states: Record<string, T>, providerIds: string[]): Record<string, T> {
+  const prefixes = providerIds.map((id) => `${id}:`)
+  return Object.fromEntries(
+    Object.entries(states).filter(([key]) => !prefixes.some((prefix) => key.startsWith(prefix)))
+  )
+}
@@ -1,12 +1,36 @@
 export type ProgressFormat =
   | { kind: "percent" }
   | { kind: "dollars" }
+  | { kind: "currency"; code: string }
   | { kind: "count"; suffix: string }
+  | { kind: "tokens" }
+  | { kind: "requests" }
+  | { kind: "duration"; unit: "seconds" | "minutes" | "hours" }
+  | { kind: "bytes" }
 
+/** Backend labels for the tokens, requests, duration and bytes formats. */
+export type HumanizedProgress = {
+  used: string
+  limit: string
+  left: string
+  summary: string
+}
+
+/** Amounts of a money line in the user's display currency, set by the backend. */
+export type ConvertedAmount = {
+  code: string
+  used: number
+  limit: number
+  rate: number
+  ratesDate: string
+}
+
+/** `id` is set to the declared line's id when the line matches one in the manifest. */
 export type MetricLine =
-  | { type: "text"; label: string; value: string; color?: string; subtitle?: string }
+  | { type: "text"; id?: string | null; label: string; value: string; color?: string; subtitle?: string }
   | {
       type: "progress"
+      id?: string | null
       label: string
       used: number
       limit: number
@@ -14,13 +38,56 @@ export type MetricLine =
       resetsAt?: string
       periodDurationMs?: number
       color?: string
+      /** Stacked parts of `used`, in drawing order. */
+      segments?: ProgressSegment[]
+      softLimit?: number | null
+      hardLimit?: number | null
+      converted?: ConvertedAmount | null
+      humanized?: HumanizedProgress | null
+    }
+  | { type: "badge"; id?: string | null; label: string; text: string; color?: string; subtitle?: string }
+  | {
+      type: "sparkline"
+      id?: string | null
+      label: string
+      points: SparkPoint[]
+      unit: string
+      color?: string
+      subtitle?: string
     }
-  | { type: "badge"; label: string; text: string; color?: string; subtitle?: string }
+  | { type: "table"; id?: string | null; label: string; columns: string[]; rows: string[][] }
+  | { type: "link"; id?: string | null; label: string; url: string; subtitle?: string }
+
+export type ProgressSegmentKind = "included" | "reserved" | "overage"
+
+export type ProgressSegment = {
+  kind: ProgressSegmentKind
+  label: string
+  used: number
+  color?: string | null
+}
+
+export type SparkPoint = {
+  value: number
+  /** ISO timestamp of the value, when known. */
+  at?: string | null
+}
+
+export type LineScope = "overview" | "detail"
 
 export type ManifestLine = {
+  /** Stable key, kept when the label changes. */
+  id: string
   type: "text" | "progress" | "badge"
   label: string
-  scope: "overview" | "detail"
+  scope: LineScope
+}
+
+export type OutputSection = {
+  title: string
+  lines: MetricLine[]
+  /** Hint that the section starts folded. */
+  collapsed?: boolean
 }
 
 export type PluginOutput = {
@@ -29,6 +96,49 @@ export type PluginOutput = {
   plan?: string
   lines: MetricLine[]
   iconUrl: string
+  /** Account profile label when the plugin is probed for multiple accounts. */
+  account?: string | null
+  /** Id of that account profile; unique per plugin, unlike the label. */
+  accountId?: string | null
+  /** Titled groups of lines shown after `lines`. */
+  sections?: OutputSection[]
+}
+
+export type PluginLink = {
+  label: string
+  url: string
+}
+
+export type PluginSettingField = {
+  key: string
+  label: string
+  description?: string | null
+  required: boolean
+  default?: string | boolean | null
+} & (
+  | { type: "string"; pattern?: string; maxLength?: number }
+  | { type: "secret" }
+  | { type: "boolean" }
+  | { type: "enum"; options: string[] }
+  | { type: "path" }
+)
+
+/** A problem found in a plugin directory at startup (see `get_plugin_diagnostics`). */
+export type PluginDiagnostic = {
+  severity: "error" | "warning"
+  file: string
+  /** JSON pointer into `file`; empty for the whole file. */
+  pointer: string
+  message: string
+  suggestion?: string | null
+}
+
+export type PluginReport = {
+  /** The plugin's `plugin.json`. */
+  file: string
+  id?: string | null
+  loaded: boolean
+  diagnostics: PluginDiagnostic[]
 }
 
 export type PluginMeta = {
@@ -37,12 +147,25 @@ export type PluginMeta = {
   iconUrl: string
   brandColor?: string
   lines: ManifestLine[]
-  /** Ordered list of primary metric candidates. Frontend picks first available. */
+  /** Ordered ids of primary metric candidates. Frontend picks first available. */
   primaryCandidates: string[]
+  links?: PluginLink[]
+  settings?: PluginSettingField[]
+  /** Minimum minutes between automatic refreshes; the global interval applies when longer. */
+  refreshInterval?: number | null
+  /** False when the plugin cannot run on this OS; it is not probed. */
+  supported?: boolean
+  unsupportedReason?: string | null
+  /** Built into the app (Rust) rather than loaded from a JS plugin. */
+  native?: boolean
 }
 
 export type PluginDisplayState = {
   meta: PluginMeta
+  /** Account profile the state belongs to, for plugins probed once per account. */
+  accountId?: string | null
+  /** Label of that account profile. */
+  account?: string | null
   data: PluginOutput | null
   loading: boolean
   error: string | null
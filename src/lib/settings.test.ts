@@ -7,7 +7,9 @@ import {
   DEFAULT_TRAY_SHOW_PERCENTAGE,
   DEFAULT_THEME_MODE,
   arePluginSettingsEqual,
+  displayCurrencies,
   getEnabledPluginIds,
+  getProbePluginIds,
   loadAutoUpdateInterval,
   loadDisplayMode,
   loadPluginSettings,
@@ -96,6 +98,19 @@ describe("settings", () => {
     expect(getEnabledPluginIds({ order: ["a", "b"], disabled: ["b"] })).toEqual(["a"])
   })
 
+  it("leaves unsupported plugins out of probe ids", () => {
+    const plugins = [
+      { id: "a", name: "A", iconUrl: "", lines: [], primaryCandidates: [] },
+      { id: "b", name: "B", iconUrl: "", lines: [], primaryCandidates: [], supported: false, unsupportedReason: "macOS only" },
+    ]
+    expect(getProbePluginIds({ order: ["a", "b"], disabled: [] }, plugins)).toEqual(["a"])
+  })
+
+  it("lists the rate table base first and the other currencies by code", () => {
+    const table = { base: "USD", date: "2026-01-01", rates: { USD: 1, JPY: 150, EUR: 0.9, XAU: 0.0004 } }
+    expect(displayCurrencies(table)).toEqual(["USD", "EUR", "JPY", "XAU"])
+  })
+
   it("loads default auto-update interval when missing", async () => {
     await expect(loadAutoUpdateInterval()).resolves.toBe(DEFAULT_AUTO_UPDATE_INTERVAL)
   })
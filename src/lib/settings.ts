@@ -25,12 +25,28 @@ const THEME_MODE_KEY = "themeMode";
 const DISPLAY_MODE_KEY = "displayMode";
 const TRAY_ICON_STYLE_KEY = "trayIconStyle";
 const TRAY_SHOW_PERCENTAGE_KEY = "trayShowPercentage";
+const DISPLAY_CURRENCY_KEY = "displayCurrency";
 
 export const DEFAULT_AUTO_UPDATE_INTERVAL: AutoUpdateIntervalMinutes = 15;
 export const DEFAULT_THEME_MODE: ThemeMode = "system";
 export const DEFAULT_DISPLAY_MODE: DisplayMode = "left";
 export const DEFAULT_TRAY_ICON_STYLE: TrayIconStyle = "bars";
 export const DEFAULT_TRAY_SHOW_PERCENTAGE = false;
+export const DEFAULT_DISPLAY_CURRENCY = "USD";
+
+/** Exchange-rate table from `get_exchange_rates`: the built-in one or the user's override. */
+export type ExchangeRates = {
+  base: string;
+  date: string;
+  /** Units of each currency per unit of `base`. */
+  rates: Record<string, number>;
+};
+
+/** Currencies money lines can be converted to: the base first, then the others by code. */
+export function displayCurrencies(table: ExchangeRates): string[] {
+  const others = Object.keys(table.rates).filter((code) => code !== table.base).sort();
+  return [table.base, ...others];
+}
 
 const AUTO_UPDATE_INTERVALS: AutoUpdateIntervalMinutes[] = [5, 15, 30, 60];
 const THEME_MODES: ThemeMode[] = ["system", "light", "dark"];
@@ -185,6 +201,17 @@ export async function saveDisplayMode(mode: DisplayMode): Promise<void> {
   await store.save();
 }
 
+export async function loadDisplayCurrency(): Promise<string> {
+  const stored = await store.get<unknown>(DISPLAY_CURRENCY_KEY);
+  if (typeof stored === "string" && /^[A-Z]{3}$/.test(stored)) return stored;
+  return DEFAULT_DISPLAY_CURRENCY;
+}
+
+export async function saveDisplayCurrency(code: string): Promise<void> {
+  await store.set(DISPLAY_CURRENCY_KEY, code);
+  await store.save();
+}
+
 export function isTrayIconStyle(value: unknown): value is TrayIconStyle {
   return typeof value === "string" && TRAY_ICON_STYLES.includes(value as TrayIconStyle);
 }
@@ -218,3 +245,9 @@ export function getEnabledPluginIds(settings: PluginSettings): string[] {
   const disabledSet = new Set(settings.disabled);
   return settings.order.filter((id) => !disabledSet.has(id));
 }
+
+// Enabled plugins that can run on this OS; unsupported plugins are listed but never probed.
+export function getProbePluginIds(settings: PluginSettings, plugins: PluginMeta[]): string[] {
+  const unsupported = new Set(plugins.filter((plugin) => plugin.supported === false).map((plugin) => plugin.id));
+  return getEnabledPluginIds(settings).filter((id) => !unsupported.has(id));
+}
@@ -17,7 +17,7 @@ describe("getTrayPrimaryBars", () => {
       id,
       name: id.toUpperCase(),
       iconUrl: "",
-      primaryCandidates: ["Usage"],
+      primaryCandidates: ["usage"],
       lines: [],
     }))
 
@@ -37,7 +37,7 @@ describe("getTrayPrimaryBars", () => {
           id: "a",
           name: "A",
           iconUrl: "",
-          primaryCandidates: ["Session"],
+          primaryCandidates: ["session"],
           lines: [],
         },
       ],
@@ -47,7 +47,7 @@ describe("getTrayPrimaryBars", () => {
     expect(bars).toEqual([{ id: "a", fraction: undefined }])
   })
 
-  it("computes fraction from matching progress label and clamps 0..1", () => {
+  it("computes fraction from the matching progress line and clamps 0..1", () => {
     const bars = getTrayPrimaryBars({
       displayMode: "used",
       pluginsMeta: [
@@ -55,7 +55,7 @@ describe("getTrayPrimaryBars", () => {
           id: "a",
           name: "A",
           iconUrl: "",
-          primaryCandidates: ["Plan usage"],
+          primaryCandidates: ["plan-usage"],
           lines: [],
         },
       ],
@@ -69,6 +69,7 @@ describe("getTrayPrimaryBars", () => {
             lines: [
               {
                 type: "progress",
+                id: "plan-usage",
                 label: "Plan usage",
                 used: 150,
                 limit: 100,
@@ -85,6 +86,81 @@ describe("getTrayPrimaryBars", () => {
     expect(bars).toEqual([{ id: "a", fraction: 1 }])
   })
 
+  it("matches candidates by line id when the label changed", () => {
+    const bars = getTrayPrimaryBars({
+      displayMode: "used",
+      pluginsMeta: [
+        {
+          id: "a",
+          name: "A",
+          iconUrl: "",
+          primaryCandidates: ["session"],
+          lines: [],
+        },
+      ],
+      pluginSettings: { order: ["a"], disabled: [] },
+      pluginStates: {
+        a: {
+          data: {
+            providerId: "a",
+            displayName: "A",
+            iconUrl: "",
+            lines: [
+              {
+                type: "progress",
+                label: "Session",
+                used: 90,
+                limit: 100,
+                format: { kind: "percent" },
+              },
+              {
+                type: "progress",
+                id: "session",
+                label: "5h window",
+                used: 25,
+                limit: 100,
+                format: { kind: "percent" },
+              },
+            ],
+          },
+          loading: false,
+          error: null,
+        },
+      },
+    })
+
+    expect(bars).toEqual([{ id: "a", fraction: 0.25 }])
+  })
+
+  it("finds the primary line inside sections", () => {
+    const bars = getTrayPrimaryBars({
+      displayMode: "used",
+      pluginsMeta: [{ id: "a", name: "A", iconUrl: "", primaryCandidates: ["session"], lines: [] }],
+      pluginSettings: { order: ["a"], disabled: [] },
+      pluginStates: {
+        a: {
+          data: {
+            providerId: "a",
+            displayName: "A",
+            iconUrl: "",
+            lines: [],
+            sections: [
+              {
+                title: "Pro",
+                lines: [
+                  { type: "progress", id: "session", label: "Session", used: 40, limit: 100, format: { kind: "percent" } },
+                ],
+              },
+            ],
+          },
+          loading: false,
+          error: null,
+        },
+      },
+    })
+    expect(bars).toEqual([{ id: "a", fraction: 0.4 }])
+  })
+
   it("does not compute fraction when limit is 0", () => {
     const bars = getTrayPrimaryBars({
       pluginsMeta: [
@@ -92,7 +168,7 @@ describe("getTrayPrimaryBars", () => {
           id: "a",
           name: "A",
           iconUrl: "",
-          primaryCandidates: ["Plan usage"],
+          primaryCandidates: ["plan-usage"],
           lines: [],
         },
       ],
@@ -106,6 +182,7 @@ describe("getTrayPrimaryBars", () => {
             lines: [
               {
                 type: "progress",
+                id: "plan-usage",
                 label: "Plan usage",
                 used: 10,
                 limit: 0,
@@ -129,7 +206,7 @@ describe("getTrayPrimaryBars", () => {
           id: "a",
           name: "A",
           iconUrl: "",
-          primaryCandidates: ["Session"],
+          primaryCandidates: ["session"],
           lines: [],
         },
       ],
@@ -143,6 +220,7 @@ describe("getTrayPrimaryBars", () => {
             lines: [
               {
                 type: "progress",
+                id: "session",
                 label: "Session",
                 used: 25,
                 limit: 100,
@@ -158,6 +236,33 @@ describe("getTrayPrimaryBars", () => {
     expect(bars).toEqual([{ id: "a", fraction: 0.75 }])
   })
 
+  it("uses the most used account of a multi-account plugin", () => {
+    const accountState = (account: string, used: number) => ({
+      data: {
+        providerId: "a",
+        displayName: "A",
+        iconUrl: "",
+        account,
+        lines: [
+          { type: "progress" as const, id: "session", label: "Session", used, limit: 100, format: { kind: "percent" as const } },
+        ],
+      },
+      loading: false,
+      error: null,
+    })
+    const args = {
+      pluginsMeta: [{ id: "a", name: "A", iconUrl: "", primaryCandidates: ["session"], lines: [] }],
+      pluginSettings: { order: ["a"], disabled: [] },
+      pluginStates: {
+        a: { data: null, loading: true, error: null },
+        "a:Personal": accountState("Personal", 20),
+        "a:Work": accountState("Work", 60),
+      },
+    }
+    expect(getTrayPrimaryBars({ ...args, displayMode: "used" })).toEqual([{ id: "a", fraction: 0.6 }])
+    expect(getTrayPrimaryBars({ ...args, displayMode: "left" })).toEqual([{ id: "a", fraction: 0.4 }])
+  })
+
   it("picks first available candidate from primaryCandidates", () => {
     const bars = getTrayPrimaryBars({
       displayMode: "used",
@@ -166,7 +271,7 @@ describe("getTrayPrimaryBars", () => {
           id: "a",
           name: "A",
           iconUrl: "",
-          primaryCandidates: ["Credits", "Plan usage"], // Credits first, Plan usage fallback
+          primaryCandidates: ["credits", "plan-usage"], // Credits first, Plan usage fallback
           lines: [],
         },
       ],
@@ -181,6 +286,7 @@ describe("getTrayPrimaryBars", () => {
               // Only Plan usage available, Credits missing
               {
                 type: "progress",
+                id: "plan-usage",
                 label: "Plan usage",
                 used: 50,
                 limit: 100,
@@ -204,7 +310,7 @@ describe("getTrayPrimaryBars", () => {
           id: "a",
           name: "A",
           iconUrl: "",
-          primaryCandidates: ["Credits", "Plan usage"],
+          primaryCandidates: ["credits", "plan-usage"],
           lines: [],
         },
       ],
@@ -218,6 +324,7 @@ describe("getTrayPrimaryBars", () => {
             lines: [
               {
                 type: "progress",
+                id: "credits",
                 label: "Credits",
                 used: 20,
                 limit: 100,
@@ -225,6 +332,7 @@ describe("getTrayPrimaryBars", () => {
               },
               {
                 type: "progress",
+                id: "plan-usage",
                 label: "Plan usage",
                 used: 80,
                 limit: 100,
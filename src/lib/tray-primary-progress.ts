@@ -2,6 +2,7 @@ import type { PluginMeta, PluginOutput } from "@/lib/plugin-types"
 import type { PluginSettings } from "@/lib/settings"
 import { DEFAULT_DISPLAY_MODE, type DisplayMode } from "@/lib/settings"
 import { clamp01 } from "@/lib/utils"
+import { providerStates } from "@/lib/plugin-states"
 
 type PluginState = {
   data: PluginOutput | null
@@ -23,6 +24,24 @@ function isProgressLine(line: PluginOutput["lines"][number]): line is ProgressLi
   return line.type === "progress"
 }
 
+// Section lines count too, like in the tray menu.
+function progressLines(data: PluginOutput): ProgressLine[] {
+  const sectionLines = (data.sections ?? []).flatMap((section) => section.lines)
+  return [...data.lines, ...sectionLines].filter(isProgressLine)
+}
+
+/** Used share of the first primary candidate found in `data`, clamped to 0..1. */
+function primaryUsedFraction(primaryCandidates: string[], data: PluginOutput | null): number | undefined {
+  if (!data) return undefined
+  // Candidates are manifest line ids, which the backend gives every declared line.
+  const lines = progressLines(data)
+  const primaryId = primaryCandidates.find((candidate) => lines.some((line) => line.id === candidate))
+  if (!primaryId) return undefined
+  const primaryLine = lines.find((line) => line.id === primaryId)
+  if (!primaryLine || primaryLine.limit <= 0) return undefined
+  return clamp01(primaryLine.used / primaryLine.limit)
+}
+
 export function getTrayPrimaryBars(args: {
   pluginsMeta: PluginMeta[]
   pluginSettings: PluginSettings | null
@@ -45,29 +64,16 @@ export function getTrayPrimaryBars(args: {
     // Skip if no primary candidates defined
     if (!meta.primaryCandidates || meta.primaryCandidates.length === 0) continue
 
-    const state = pluginStates[id]
-    const data = state?.data ?? null
-
-    let fraction: number | undefined
-    if (data) {
-      // Find first candidate that exists in runtime data
-      const primaryLabel = meta.primaryCandidates.find((label) =>
-        data.lines.some((line) => isProgressLine(line) && line.label === label)
-      )
-      if (primaryLabel) {
-        const primaryLine = data.lines.find(
-          (line): line is ProgressLine =>
-            isProgressLine(line) && line.label === primaryLabel
-        )
-        if (primaryLine && primaryLine.limit > 0) {
-          const shownAmount =
-            displayMode === "used"
-              ? primaryLine.used
-              : primaryLine.limit - primaryLine.used
-          fraction = clamp01(shownAmount / primaryLine.limit)
-        }
+    // Multi-account plugins show their most used account
+    let usedFraction: number | undefined
+    for (const { state } of providerStates(pluginStates, id)) {
+      const fraction = primaryUsedFraction(meta.primaryCandidates, state?.data ?? null)
+      if (fraction !== undefined && (usedFraction === undefined || fraction > usedFraction)) {
+        usedFraction = fraction
       }
     }
+    const fraction =
+      usedFraction === undefined ? undefined : displayMode === "used" ? usedFraction : 1 - usedFraction
 
     out.push({ id, fraction })
     if (out.length >= maxBars) break
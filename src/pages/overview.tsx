@@ -21,13 +21,15 @@ export function OverviewPage({ plugins, onRetryPlugin, displayMode }: OverviewPa
     <div>
       {plugins.map((plugin, index) => (
         <ProviderCard
-          key={plugin.meta.id}
+          key={`${plugin.meta.id}:${plugin.accountId ?? ""}`}
           name={plugin.meta.name}
+          account={plugin.account}
           plan={plugin.data?.plan}
           showSeparator={index < plugins.length - 1}
           loading={plugin.loading}
           error={plugin.error}
           lines={plugin.data?.lines ?? []}
+          sections={plugin.data?.sections ?? []}
           skeletonLines={plugin.meta.lines}
           lastManualRefreshAt={plugin.lastManualRefreshAt}
           onRetry={onRetryPlugin ? () => onRetryPlugin(plugin.meta.id) : undefined}
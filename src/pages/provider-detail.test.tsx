@@ -5,7 +5,7 @@ import { ProviderDetailPage } from "@/pages/provider-detail"
 
 describe("ProviderDetailPage", () => {
   it("shows not found when plugin missing", () => {
-    render(<ProviderDetailPage plugin={null} displayMode="used" />)
+    render(<ProviderDetailPage plugins={[]} displayMode="used" />)
     expect(screen.getByText("Provider not found")).toBeInTheDocument()
   })
 
@@ -13,29 +13,58 @@ describe("ProviderDetailPage", () => {
     render(
       <ProviderDetailPage
         displayMode="used"
-        plugin={{
+        plugins={[{
           meta: { id: "a", name: "Alpha", iconUrl: "", lines: [] },
           data: { providerId: "a", displayName: "Alpha", iconUrl: "", lines: [] },
           loading: false,
           error: null,
           lastManualRefreshAt: null,
-        }}
+        }]}
       />
     )
     expect(screen.getAllByText("Alpha").length).toBeGreaterThan(0)
   })
 
+  it("renders a card per account", () => {
+    const meta = { id: "a", name: "Alpha", iconUrl: "", lines: [] }
+    const state = (account: string, value: string) => ({
+      meta,
+      accountId: account.toLowerCase(),
+      account,
+      data: {
+        providerId: "a",
+        displayName: "Alpha",
+        iconUrl: "",
+        account,
+        lines: [{ type: "text" as const, label: "Plan", value }],
+      },
+      loading: false,
+      error: null,
+      lastManualRefreshAt: null,
+    })
+    render(
+      <ProviderDetailPage
+        displayMode="used"
+        plugins={[state("Personal", "Plus"), state("Work", "Team")]}
+      />
+    )
+    expect(screen.getAllByText("Alpha")).toHaveLength(2)
+    expect(screen.getByText("Personal")).toBeInTheDocument()
+    expect(screen.getByText("Work")).toBeInTheDocument()
+    expect(screen.getByText("Team")).toBeInTheDocument()
+  })
+
   it("renders when plugin data is null (still shows provider name)", () => {
     render(
       <ProviderDetailPage
         displayMode="used"
-        plugin={{
+        plugins={[{
           meta: { id: "a", name: "Alpha", iconUrl: "", lines: [] },
           data: null,
           loading: false,
           error: null,
           lastManualRefreshAt: null,
-        }}
+        }]}
       />
     )
     expect(screen.getAllByText("Alpha").length).toBeGreaterThan(0)
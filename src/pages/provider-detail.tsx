@@ -3,13 +3,14 @@ import type { PluginDisplayState } from "@/lib/plugin-types"
 import type { DisplayMode } from "@/lib/settings"
 
 interface ProviderDetailPageProps {
-  plugin: PluginDisplayState | null
+  /** The provider's state, or one per account for plugins probed once per account. */
+  plugins: PluginDisplayState[]
   onRetry?: () => void
   displayMode: DisplayMode
 }
 
-export function ProviderDetailPage({ plugin, onRetry, displayMode }: ProviderDetailPageProps) {
-  if (!plugin) {
+export function ProviderDetailPage({ plugins, onRetry, displayMode }: ProviderDetailPageProps) {
+  if (plugins.length === 0) {
     return (
       <div className="text-center text-muted-foreground py-8">
         Provider not found
@@ -18,18 +19,25 @@ export function ProviderDetailPage({ plugin, onRetry, displayMode }: ProviderDet
   }
 
   return (
-    <ProviderCard
-      name={plugin.meta.name}
-      plan={plugin.data?.plan}
-      showSeparator={false}
-      loading={plugin.loading}
-      error={plugin.error}
-      lines={plugin.data?.lines ?? []}
-      skeletonLines={plugin.meta.lines}
-      lastManualRefreshAt={plugin.lastManualRefreshAt}
-      onRetry={onRetry}
-      scopeFilter="all"
-      displayMode={displayMode}
-    />
+    <div>
+      {plugins.map((plugin, index) => (
+        <ProviderCard
+          key={plugin.accountId ?? ""}
+          name={plugin.meta.name}
+          account={plugin.account}
+          plan={plugin.data?.plan}
+          showSeparator={index < plugins.length - 1}
+          loading={plugin.loading}
+          error={plugin.error}
+          lines={plugin.data?.lines ?? []}
+          sections={plugin.data?.sections ?? []}
+          skeletonLines={plugin.meta.lines}
+          lastManualRefreshAt={plugin.lastManualRefreshAt}
+          onRetry={onRetry}
+          scopeFilter="all"
+          displayMode={displayMode}
+        />
+      ))}
+    </div>
   )
 }
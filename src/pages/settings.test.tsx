@@ -53,6 +53,9 @@ const defaultProps = {
   onThemeModeChange: vi.fn(),
   displayMode: "used" as const,
   onDisplayModeChange: vi.fn(),
+  displayCurrency: "USD",
+  displayCurrencies: ["USD", "EUR"],
+  onDisplayCurrencyChange: vi.fn(),
   trayIconStyle: "bars" as const,
   onTrayIconStyleChange: vi.fn(),
   trayShowPercentage: false,
@@ -85,6 +88,72 @@ describe("SettingsPage", () => {
     expect(onToggle).toHaveBeenCalledWith("b")
   })
 
+  it("shows unsupported plugins with their reason and does not toggle them", async () => {
+    const onToggle = vi.fn()
+    render(
+      <SettingsPage
+        {...defaultProps}
+        trayIconStyle="textOnly"
+        plugins={[
+          { id: "c", name: "Gamma", enabled: true, unsupportedReason: "Gamma is only available on macOS." },
+        ]}
+        onToggle={onToggle}
+      />
+    )
+    expect(screen.getByText("Gamma is only available on macOS.")).toBeInTheDocument()
+    const checkboxes = screen.getAllByRole("checkbox")
+    const pluginCheckbox = checkboxes[checkboxes.length - 1]
+    expect(pluginCheckbox).toHaveAttribute("aria-checked", "false")
+    await userEvent.click(pluginCheckbox)
+    expect(onToggle).not.toHaveBeenCalled()
+  })
+
+  it("shows manifest warnings and plugins that failed to load", () => {
+    render(
+      <SettingsPage
+        {...defaultProps}
+        plugins={[
+          {
+            id: "a",
+            name: "Alpha",
+            enabled: true,
+            diagnostics: [
+              {
+                severity: "warning",
+                file: "/plugins/a/plugin.json",
+                pointer: "/links/0/url",
+                message: "link 'Docs' is not an http(s) URL; ignoring",
+                suggestion: "use an https:// URL",
+              },
+            ],
+          },
+        ]}
+        failedPlugins={[
+          {
+            file: "/plugins/broken/plugin.json",
+            id: "broken",
+            loaded: false,
+            diagnostics: [
+              {
+                severity: "error",
+                file: "/plugins/broken/plugin.json",
+                pointer: "/lines/0/scope",
+                message: "unknown scope 'sidebar'",
+                suggestion: null,
+              },
+            ],
+          },
+        ]}
+      />
+    )
+    expect(
+      screen.getByText("/links/0/url: link 'Docs' is not an http(s) URL; ignoring (use an https:// URL)")
+    ).toBeInTheDocument()
+    expect(screen.getByText("Not loaded")).toBeInTheDocument()
+    expect(screen.getByText("broken")).toBeInTheDocument()
+    expect(screen.getByText("/lines/0/scope: unknown scope 'sidebar'")).toBeInTheDocument()
+  })
+
   it("reorders plugins on drag end", () => {
     const onReorder = vi.fn()
     render(
@@ -164,6 +233,27 @@ describe("SettingsPage", () => {
     expect(onDisplayModeChange).toHaveBeenCalledWith("left")
   })
 
+  it("offers the currencies of the exchange-rate table", async () => {
+    const onDisplayCurrencyChange = vi.fn()
+    render(
+      <SettingsPage
+        {...defaultProps}
+        displayCurrencies={["USD", "EUR", "XAU"]}
+        onDisplayCurrencyChange={onDisplayCurrencyChange}
+      />
+    )
+    const select = screen.getByLabelText("Display currency")
+    expect(screen.getAllByRole("option").map((option) => option.textContent)).toEqual(["USD", "EUR", "XAU"])
+    await userEvent.selectOptions(select, "XAU")
+    expect(onDisplayCurrencyChange).toHaveBeenCalledWith("XAU")
+  })
+
+  it("keeps a stored currency the rate table no longer has as a disabled option", () => {
+    render(<SettingsPage {...defaultProps} displayCurrency="SEK" displayCurrencies={["USD", "EUR"]} />)
+    const option = screen.getByRole("option", { name: "SEK (no exchange rate)" })
+    expect(option).toBeDisabled()
+  })
+
   it("renders tray icon style section", () => {
     render(<SettingsPage {...defaultProps} />)
     expect(screen.getByText("Bar Icon")).toBeInTheDocument()
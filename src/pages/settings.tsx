@@ -29,12 +29,22 @@ import {
   type ThemeMode,
   type TrayIconStyle,
 } from "@/lib/settings";
+import type { PluginDiagnostic, PluginReport } from "@/lib/plugin-types";
 import { cn } from "@/lib/utils";
 
 interface PluginConfig {
   id: string;
   name: string;
   enabled: boolean;
+  /** Set when the plugin cannot run on this OS; it cannot be enabled. */
+  unsupportedReason?: string | null;
+  /** Problems found in its plugin.json; the plugin loaded without the affected parts. */
+  diagnostics?: PluginDiagnostic[];
+}
+
+function formatDiagnostic(diagnostic: PluginDiagnostic): string {
+  const text = diagnostic.pointer ? `${diagnostic.pointer}: ${diagnostic.message}` : diagnostic.message;
+  return diagnostic.suggestion ? `${text} (${diagnostic.suggestion})` : text;
 }
 
 const PREVIEW_BAR_TRACK_PX = 20;
@@ -240,15 +250,24 @@ function SortablePluginItem({
       <span
         className={cn(
           "flex-1 text-sm",
-          !plugin.enabled && "text-muted-foreground"
+          (!plugin.enabled || plugin.unsupportedReason) && "text-muted-foreground"
         )}
       >
         {plugin.name}
+        {plugin.unsupportedReason && (
+          <span className="block text-xs">{plugin.unsupportedReason}</span>
+        )}
+        {plugin.diagnostics?.map((diagnostic, index) => (
+          <span key={index} className="block text-xs text-muted-foreground">
+            {formatDiagnostic(diagnostic)}
+          </span>
+        ))}
       </span>
 
       <Checkbox
         key={`${plugin.id}-${plugin.enabled}`}
-        checked={plugin.enabled}
+        checked={plugin.enabled && !plugin.unsupportedReason}
+        disabled={Boolean(plugin.unsupportedReason)}
         onCheckedChange={() => onToggle(plugin.id)}
       />
     </div>
@@ -257,6 +276,8 @@ function SortablePluginItem({
 
 interface SettingsPageProps {
   plugins: PluginConfig[];
+  /** Plugin directories that failed to load, with the reasons. */
+  failedPlugins?: PluginReport[];
   onReorder: (orderedIds: string[]) => void;
   onToggle: (id: string) => void;
   autoUpdateInterval: AutoUpdateIntervalMinutes;
@@ -265,6 +286,10 @@ interface SettingsPageProps {
   onThemeModeChange: (value: ThemeMode) => void;
   displayMode: DisplayMode;
   onDisplayModeChange: (value: DisplayMode) => void;
+  displayCurrency: string;
+  /** Currencies of the exchange-rate table in use. */
+  displayCurrencies: string[];
+  onDisplayCurrencyChange: (value: string) => void;
   trayIconStyle: TrayIconStyle;
   onTrayIconStyleChange: (value: TrayIconStyle) => void;
   trayShowPercentage: boolean;
@@ -274,6 +299,7 @@ interface SettingsPageProps {
 
 export function SettingsPage({
   plugins,
+  failedPlugins = [],
   onReorder,
   onToggle,
   autoUpdateInterval,
@@ -282,6 +308,9 @@ export function SettingsPage({
   onThemeModeChange,
   displayMode,
   onDisplayModeChange,
+  displayCurrency,
+  displayCurrencies,
+  onDisplayCurrencyChange,
   trayIconStyle,
   onTrayIconStyleChange,
   trayShowPercentage,
@@ -368,6 +397,29 @@ export function SettingsPage({
           </div>
         </div>
       </section>
+      <section>
+        <h3 className="text-lg font-semibold mb-0">Currency</h3>
+        <p className="text-sm text-muted-foreground mb-2">
+          Spend is converted with offline exchange rates
+        </p>
+        <select
+          aria-label="Display currency"
+          className="w-full h-8 rounded-md border bg-background px-2 text-sm"
+          value={displayCurrency}
+          onChange={(event) => onDisplayCurrencyChange(event.target.value)}
+        >
+          {displayCurrencies.map((code) => (
+            <option key={code} value={code}>
+              {code}
+            </option>
+          ))}
+          {!displayCurrencies.includes(displayCurrency) && (
+            <option value={displayCurrency} disabled>
+              {displayCurrency} (no exchange rate)
+            </option>
+          )}
+        </select>
+      </section>
       <section>
         <h3 className="text-lg font-semibold mb-0">Bar Icon</h3>
         <p className="text-sm text-muted-foreground mb-2">
@@ -471,6 +523,23 @@ export function SettingsPage({
             </SortableContext>
           </DndContext>
         </div>
+        {failedPlugins.length > 0 && (
+          <div className="mt-2 space-y-1" aria-label="Plugins not loaded">
+            <p className="text-sm font-medium">Not loaded</p>
+            {failedPlugins.map((report) => (
+              <div key={report.file} className="px-3 py-2 rounded-md bg-muted/50 text-sm">
+                <span className="block">{report.id ?? report.file}</span>
+                {report.diagnostics
+                  .filter((diagnostic) => diagnostic.severity === "error")
+                  .map((diagnostic, index) => (
+                    <span key={index} className="block text-xs text-destructive">
+                      {formatDiagnostic(diagnostic)}
+                    </span>
+                  ))}
+              </div>
+            ))}
+          </div>
+        )}
       </section>
     </div>
   );